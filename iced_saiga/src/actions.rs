@@ -3,5 +3,7 @@ pub enum Action {
     Redraw,
     Shutdown,
     ChangeTitle(String),
+    Bell,
+    OpenLink(String),
     Ignore,
 }
@@ -3,14 +3,19 @@ use saiga_backend::{
     event::{Event, EventListener, Notify as _, OnResize as _, WindowSize},
     event_loop::{EventLoop, Notifier},
     grid::{Dimensions, Grid, Scroll},
-    index::{Column, Line, Point},
-    selection::{SelectionRange, SelectionType},
+    index::{Column, Line, Point, Side},
+    selection::{Selection, SelectionRange, SelectionType},
     sync::FairMutex,
-    term::{self, cell::Cell, Term, TermMode},
+    term::{
+        self,
+        cell::{Cell, Hyperlink},
+        color::Colors,
+        viewport_to_point, RenderableCursor, Term, TermMode,
+    },
     tty,
 };
-use saiga_vte::ansi::handler::CursorStyle;
-use std::{borrow::Cow, io, sync::Arc};
+use saiga_vte::ansi::handler::{CursorStyle, Rgb};
+use std::{borrow::Cow, io, ops::RangeInclusive, sync::Arc};
 use tokio::sync::mpsc;
 
 use crate::{actions::Action, settings::BackendSettings};
@@ -18,15 +23,17 @@ use crate::{actions::Action, settings::BackendSettings};
 #[derive(Debug, Clone)]
 pub enum BackendCommand {
     Write(Vec<u8>),
+    Paste(String),
     Scroll(i32),
     Resize(Option<Size<f32>>, Option<Size<f32>>),
     SelectStart(SelectionType, (f32, f32)),
     SelectUpdate((f32, f32)),
     MouseReport(MouseButton, Modifiers, Point, bool),
+    OpenLink((f32, f32)),
     ProcessTermEvent(Event),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub enum MouseMode {
     Sgr,
     Normal(bool),
@@ -44,7 +51,7 @@ impl From<TermMode> for MouseMode {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub enum MouseButton {
     LeftButton = 0,
     MiddleButton = 1,
@@ -77,7 +84,10 @@ impl Backend {
             ..Default::default()
         };
 
-        let config = term::Config::default();
+        let config = term::Config {
+            bold_is_bright: settings.bold_is_bright,
+            ..Default::default()
+        };
 
         let term_size = TermSize {
             cell_width: font_size.width as u16,
@@ -97,7 +107,10 @@ impl Backend {
             cursor: cursor.clone(),
             term_mode: *term.mode(),
             cursor_style: term.cursor_style(),
+            renderable_cursor: term.renderable_cursor(),
             term_size,
+            bold_is_bright: term.bold_is_bright(),
+            colors: *term.colors(),
         };
 
         let term = Arc::new(FairMutex::new(term));
@@ -128,11 +141,19 @@ impl Backend {
                 }
                 Event::Exit => Action::Shutdown,
                 Event::Title(title) => Action::ChangeTitle(title),
+                Event::Bell => Action::Bell,
                 Event::PtyWrite(text) => {
                     self.write(text.into_bytes());
 
                     Action::Ignore
                 }
+                Event::WindowStateRequest(_) | Event::WindowPositionRequest(_) => {
+                    if let Some(report) = window_report(&event) {
+                        self.write(report.into_bytes());
+                    }
+
+                    Action::Ignore
+                }
                 _ => Action::Ignore,
             },
             BackendCommand::Write(input) => {
@@ -141,34 +162,92 @@ impl Backend {
 
                 Action::Ignore
             }
+            BackendCommand::Paste(data) => {
+                self.write(paste_input(&term, &data));
+                term.scroll_display(Scroll::Bottom);
+
+                Action::Ignore
+            }
             BackendCommand::Resize(layout_size, font_measure) => {
                 self.resize(&mut term, layout_size, font_measure);
                 self.internal_sync(&mut term);
 
                 Action::Redraw
             }
+            BackendCommand::SelectStart(selection_type, (x, y)) => {
+                self.start_selection(&mut term, selection_type, x, y);
+                self.internal_sync(&mut term);
+
+                Action::Redraw
+            }
+            BackendCommand::SelectUpdate((x, y)) => {
+                self.update_selection(&mut term, x, y);
+                self.internal_sync(&mut term);
+
+                Action::Redraw
+            }
+            BackendCommand::MouseReport(button, modifiers, point, pressed) => {
+                let mode = *term.mode();
+                if mode.intersects(TermMode::MOUSE_MODE) {
+                    let report = mouse_report(mode.into(), button, modifiers, point, pressed);
+                    if let Some(report) = report {
+                        self.write(report);
+                    }
+                }
+
+                Action::Ignore
+            }
+            BackendCommand::OpenLink((x, y)) => {
+                let display_offset = term.grid().display_offset();
+                let point = selection_point(x, y, &self.size, display_offset);
+
+                match hyperlink_at(term.grid(), point) {
+                    Some((hyperlink, _)) => Action::OpenLink(hyperlink.uri().to_owned()),
+                    None => Action::Ignore,
+                }
+            }
             _ => Action::Ignore, // BackendCommand::Scroll(delta) => {
                                  //     self.scroll(&mut term, delta);
                                  //     self.internal_sync(&mut term);
                                  //     action = Action::Redraw;
                                  // }
-                                 // BackendCommand::SelectStart(selection_type, (x, y)) => {
-                                 //     self.start_selection(&mut term, selection_type, x, y);
-                                 //     self.internal_sync(&mut term);
-                                 //     action = Action::Redraw;
-                                 // }
-                                 // BackendCommand::SelectUpdate((x, y)) => {
-                                 //     self.update_selection(&mut term, x, y);
-                                 //     self.internal_sync(&mut term);
-                                 //     action = Action::Redraw;
-                                 // }
-                                 // BackendCommand::ProcessLink(link_action, point) => {
-                                 //     action = self.process_link_action(&term, link_action, point);
-                                 // }
-                                 // BackendCommand::MouseReport(button, modifiers, point, pressed) => {
-                                 //     self.process_mouse_report(button, modifiers, point, pressed);
-                                 //     action = Action::Redraw;
-                                 // }
+        }
+    }
+
+    /// Viewport cell under the pixel position `(x, y)`, relative to the grid origin.
+    pub fn cell_at(&self, x: f32, y: f32) -> Point {
+        selection_point(x, y, &self.size, 0)
+    }
+
+    /// Whether a hyperlink is under the pixel position `(x, y)`, relative to the grid origin.
+    pub fn has_link_at(&self, x: f32, y: f32) -> bool {
+        let term = self.term.lock();
+        let point = selection_point(x, y, &self.size, term.grid().display_offset());
+
+        hyperlink_at(term.grid(), point).is_some()
+    }
+
+    fn start_selection(
+        &mut self,
+        terminal: &mut Term<EventProxy>,
+        selection_type: SelectionType,
+        x: f32,
+        y: f32,
+    ) {
+        let display_offset = terminal.grid().display_offset();
+        let location = selection_point(x, y, &self.size, display_offset);
+        let side = selection_side(x, &self.size);
+
+        terminal.selection = Some(Selection::new(selection_type, location, side));
+    }
+
+    fn update_selection(&mut self, terminal: &mut Term<EventProxy>, x: f32, y: f32) {
+        let display_offset = terminal.grid().display_offset();
+        let location = selection_point(x, y, &self.size, display_offset);
+        let side = selection_side(x, &self.size);
+
+        if let Some(selection) = &mut terminal.selection {
+            selection.update(location, side);
         }
     }
 
@@ -199,7 +278,12 @@ impl Backend {
         }
     }
 
-    fn write<I: Into<Cow<'static, [u8]>>>(&self, input: I) {
+    /// Color set by the running application, if any.
+    pub(crate) fn color(&self, index: usize) -> Option<Rgb> {
+        self.term.lock().colors()[index]
+    }
+
+    pub(crate) fn write<I: Into<Cow<'static, [u8]>>>(&self, input: I) {
         self.notifier.notify(input);
     }
 
@@ -223,11 +307,156 @@ impl Backend {
         self.last_content.term_mode = *terminal.mode();
         self.last_content.term_size = self.size;
         self.last_content.cursor_style = terminal.cursor_style();
+        self.last_content.renderable_cursor = terminal.renderable_cursor();
+        self.last_content.colors = *terminal.colors();
     }
 
     pub fn renderable_content(&self) -> &RenderableContent {
         &self.last_content
     }
+
+    /// Text covered by the current selection, or an empty string.
+    pub fn selectable_content(&self) -> String {
+        self.term.lock().selection_to_string().unwrap_or_default()
+    }
+}
+
+/// Grid point under the pixel position `(x, y)`, relative to the grid origin.
+fn selection_point(x: f32, y: f32, size: &TermSize, display_offset: usize) -> Point {
+    let col = (x.max(0.0) / size.cell_width as f32) as usize;
+    let line = (y.max(0.0) / size.cell_height as f32) as usize;
+
+    let point = Point::new(
+        line.min(size.screen_lines() - 1),
+        Column(col.min(size.columns() - 1)),
+    );
+    viewport_to_point(display_offset, point)
+}
+
+/// Reply to a window state or position request from the terminal.
+fn window_report(event: &Event) -> Option<String> {
+    match event {
+        // Iconification is not tracked, so the window is always reported as open.
+        Event::WindowStateRequest(format) => Some(format(false)),
+        // The backend has no access to its window, so the position is reported as the origin.
+        Event::WindowPositionRequest(format) => Some(format(0, 0)),
+        _ => None,
+    }
+}
+
+/// Prepares clipboard contents for writing to the pty, based on the current terminal mode.
+///
+/// In bracketed paste mode the data is wrapped in `ESC [ 200 ~` and `ESC [ 201 ~`, with any
+/// embedded end marker removed so the pasted text can't terminate the paste early.
+fn paste_input<T>(term: &Term<T>, data: &str) -> Vec<u8> {
+    const PASTE_START: &str = "\x1b[200~";
+    const PASTE_END: &str = "\x1b[201~";
+
+    if !term.mode().contains(TermMode::BRACKETED_PASTE) {
+        return data.as_bytes().to_vec();
+    }
+
+    let mut data = data.to_owned();
+    while data.contains(PASTE_END) {
+        data = data.replace(PASTE_END, "");
+    }
+
+    format!("{PASTE_START}{data}{PASTE_END}").into_bytes()
+}
+
+/// Hyperlink under `point`, along with the columns it covers on that line.
+fn hyperlink_at(grid: &Grid<Cell>, point: Point) -> Option<(Hyperlink, RangeInclusive<Column>)> {
+    let row = &grid[point.line];
+    let hyperlink = row[point.column].hyperlink()?;
+    let is_same = |column: Column| row[column].hyperlink().as_ref() == Some(&hyperlink);
+
+    let mut start = point.column;
+    while start > Column(0) && is_same(start - 1) {
+        start -= 1;
+    }
+
+    let mut end = point.column;
+    while end < grid.last_column() && is_same(end + 1) {
+        end += 1;
+    }
+
+    Some((hyperlink, start..=end))
+}
+
+/// Encode a mouse event for the pty, `point` being relative to the viewport.
+///
+/// Returns `None` when the position can't be represented in the legacy encoding.
+fn mouse_report(
+    mode: MouseMode,
+    button: MouseButton,
+    modifiers: Modifiers,
+    point: Point,
+    pressed: bool,
+) -> Option<Vec<u8>> {
+    let mut code = button as u8;
+    if modifiers.shift() {
+        code += 4;
+    }
+    if modifiers.alt() {
+        code += 8;
+    }
+    if modifiers.control() {
+        code += 16;
+    }
+
+    let column = point.column.0;
+    let line = point.line.0.max(0) as usize;
+
+    match mode {
+        MouseMode::Sgr => {
+            let action = if pressed { 'M' } else { 'm' };
+            let report = format!("\x1b[<{};{};{}{}", code, column + 1, line + 1, action);
+            Some(report.into_bytes())
+        }
+        MouseMode::Normal(utf8) => {
+            // Releases don't identify the button in the legacy encoding.
+            if !pressed && code < 32 {
+                code = (code & !0b11) | 3;
+            }
+
+            let mut report = b"\x1b[M".to_vec();
+            report.push(32 + code);
+            encode_mouse_position(&mut report, column, utf8)?;
+            encode_mouse_position(&mut report, line, utf8)?;
+            Some(report)
+        }
+    }
+}
+
+/// Append a single 1-based coordinate offset by 32, UTF-8 encoded if `utf8` is set.
+fn encode_mouse_position(report: &mut Vec<u8>, position: usize, utf8: bool) -> Option<()> {
+    let value = 32 + 1 + position as u32;
+
+    if value < 0x80 {
+        report.push(value as u8);
+    } else if utf8 && value < 0x800 {
+        let mut buf = [0; 4];
+        let c = char::from_u32(value)?;
+        report.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+    } else if !utf8 && value <= 0xff {
+        report.push(value as u8);
+    } else {
+        return None;
+    }
+
+    Some(())
+}
+
+/// Half of the cell under `x` that the position falls into.
+fn selection_side(x: f32, size: &TermSize) -> Side {
+    let cell_width = size.cell_width as f32;
+    let cell_x = x.max(0.0) % cell_width;
+
+    if cell_x > cell_width / 2.0 {
+        Side::Right
+    } else {
+        Side::Left
+    }
 }
 
 pub struct RenderableContent {
@@ -237,6 +466,9 @@ pub struct RenderableContent {
     pub term_mode: TermMode,
     pub term_size: TermSize,
     pub cursor_style: CursorStyle,
+    pub renderable_cursor: RenderableCursor,
+    pub bold_is_bright: bool,
+    pub colors: Colors,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -304,3 +536,208 @@ impl EventListener for EventProxy {
         let _ = self.0.blocking_send(event);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use saiga_backend::event::VoidListener;
+    use saiga_vte::ansi::processor::Processor;
+
+    fn size() -> TermSize {
+        TermSize {
+            cell_width: 8,
+            cell_height: 16,
+            num_cols: 10,
+            num_lines: 4,
+            layout_width: 80.0,
+            layout_height: 64.0,
+        }
+    }
+
+    fn paste(data: &str, bracketed: bool) -> Vec<u8> {
+        let mut term = Term::new(term::Config::default(), &size(), VoidListener);
+        if bracketed {
+            Processor::new().advance(&mut term, b"\x1b[?2004h");
+        }
+
+        paste_input(&term, data)
+    }
+
+    #[test]
+    fn paste_without_bracketed_mode() {
+        assert_eq!(paste("ls -l\n", false), b"ls -l\n");
+        assert_eq!(paste("a\x1b[201~b", false), b"a\x1b[201~b");
+    }
+
+    #[test]
+    fn paste_with_bracketed_mode() {
+        assert_eq!(paste("ls -l\n", true), b"\x1b[200~ls -l\n\x1b[201~");
+    }
+
+    #[test]
+    fn paste_strips_end_marker() {
+        assert_eq!(
+            paste("a\x1b[201~rm -rf ~\n", true),
+            b"\x1b[200~arm -rf ~\n\x1b[201~"
+        );
+
+        // Removing a marker must not leave a new one behind.
+        assert_eq!(paste("\x1b[20\x1b[201~1~", true), b"\x1b[200~\x1b[201~");
+    }
+
+    #[test]
+    fn paste_reads_current_mode() {
+        let mut term = Term::new(term::Config::default(), &size(), VoidListener);
+        let mut parser = Processor::new();
+
+        // A password prompt turning bracketed paste off right before the paste.
+        parser.advance(&mut term, b"\x1b[?2004h");
+        assert_eq!(paste_input(&term, "secret"), b"\x1b[200~secret\x1b[201~");
+
+        parser.advance(&mut term, b"\x1b[?2004l");
+        assert_eq!(paste_input(&term, "secret"), b"secret");
+    }
+
+    #[test]
+    fn pixel_to_selection_point() {
+        let size = size();
+
+        assert_eq!(
+            selection_point(0.0, 0.0, &size, 0),
+            Point::new(Line(0), Column(0))
+        );
+        assert_eq!(
+            selection_point(20.0, 40.0, &size, 0),
+            Point::new(Line(2), Column(2))
+        );
+        // Positions past the grid are clamped to the last cell.
+        assert_eq!(
+            selection_point(500.0, 500.0, &size, 0),
+            Point::new(Line(3), Column(9))
+        );
+        // Scrolled into history.
+        assert_eq!(
+            selection_point(0.0, 0.0, &size, 2),
+            Point::new(Line(-2), Column(0))
+        );
+    }
+
+    #[test]
+    fn pixel_to_selection_side() {
+        let size = size();
+
+        assert_eq!(selection_side(1.0, &size), Side::Left);
+        assert_eq!(selection_side(7.0, &size), Side::Right);
+        assert_eq!(selection_side(17.0, &size), Side::Left);
+    }
+
+    #[test]
+    fn mouse_report_sgr() {
+        let point = Point::new(Line(4), Column(9));
+
+        let press = mouse_report(
+            MouseMode::Sgr,
+            MouseButton::LeftButton,
+            Modifiers::empty(),
+            point,
+            true,
+        );
+        assert_eq!(press.as_deref(), Some(&b"\x1b[<0;10;5M"[..]));
+
+        let release = mouse_report(
+            MouseMode::Sgr,
+            MouseButton::LeftButton,
+            Modifiers::CTRL,
+            point,
+            false,
+        );
+        assert_eq!(release.as_deref(), Some(&b"\x1b[<16;10;5m"[..]));
+    }
+
+    #[test]
+    fn mouse_report_normal() {
+        let point = Point::new(Line(4), Column(9));
+
+        let press = mouse_report(
+            MouseMode::Normal(false),
+            MouseButton::LeftButton,
+            Modifiers::empty(),
+            point,
+            true,
+        );
+        assert_eq!(press.as_deref(), Some(&b"\x1b[M\x20\x2a\x25"[..]));
+
+        let release = mouse_report(
+            MouseMode::Normal(false),
+            MouseButton::LeftButton,
+            Modifiers::empty(),
+            point,
+            false,
+        );
+        assert_eq!(release.as_deref(), Some(&b"\x1b[M\x23\x2a\x25"[..]));
+    }
+
+    #[test]
+    fn mouse_report_normal_large_coordinates() {
+        let point = Point::new(Line(0), Column(300));
+
+        let legacy = mouse_report(
+            MouseMode::Normal(false),
+            MouseButton::LeftButton,
+            Modifiers::empty(),
+            point,
+            true,
+        );
+        assert_eq!(legacy, None);
+
+        let utf8 = mouse_report(
+            MouseMode::Normal(true),
+            MouseButton::LeftButton,
+            Modifiers::empty(),
+            point,
+            true,
+        );
+        let mut expected = b"\x1b[M\x20".to_vec();
+        expected.extend_from_slice("\u{14d}".as_bytes());
+        expected.push(b'!');
+        assert_eq!(utf8, Some(expected));
+    }
+
+    #[test]
+    fn hyperlink_hit_test() {
+        let size = size();
+        let mut term = Term::new(term::Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(
+            &mut term,
+            b"ab\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\ cd",
+        );
+
+        // Pixel positions inside the first and last cell of the link.
+        for x in [17.0, 47.0] {
+            let point = selection_point(x, 5.0, &size, 0);
+            let (hyperlink, columns) = hyperlink_at(term.grid(), point).unwrap();
+
+            assert_eq!(hyperlink.uri(), "https://example.com");
+            assert_eq!(columns, Column(2)..=Column(5));
+        }
+
+        let point = selection_point(50.0, 5.0, &size, 0);
+        assert!(hyperlink_at(term.grid(), point).is_none());
+    }
+
+    #[test]
+    fn window_reports() {
+        let (sender, mut receiver) = mpsc::channel(4);
+        let mut term = Term::new(term::Config::default(), &size(), EventProxy(sender));
+
+        Processor::new().advance(&mut term, b"\x1b[11t\x1b[13t");
+
+        let state = receiver.try_recv().unwrap();
+        assert_eq!(window_report(&state).as_deref(), Some("\x1b[1t"));
+        let position = receiver.try_recv().unwrap();
+        assert_eq!(window_report(&position).as_deref(), Some("\x1b[3;0;0t"));
+        assert!(window_report(&Event::Wakeup).is_none());
+    }
+}
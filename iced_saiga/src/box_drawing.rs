@@ -0,0 +1,259 @@
+use iced::{Point, Rectangle, Size};
+
+/// Weight of a line leaving the cell center.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Weight {
+    None,
+    Light,
+    Heavy,
+}
+
+/// Lines leaving the cell center towards each edge.
+#[derive(Debug, Clone, Copy)]
+struct Lines {
+    left: Weight,
+    right: Weight,
+    up: Weight,
+    down: Weight,
+}
+
+impl Lines {
+    const fn new(left: u8, right: u8, up: u8, down: u8) -> Self {
+        const fn weight(weight: u8) -> Weight {
+            match weight {
+                0 => Weight::None,
+                1 => Weight::Light,
+                _ => Weight::Heavy,
+            }
+        }
+
+        Self {
+            left: weight(left),
+            right: weight(right),
+            up: weight(up),
+            down: weight(down),
+        }
+    }
+}
+
+/// Rectangles covering a box drawing character.
+///
+/// Returns `None` for characters which should be rendered by the font. The lines reach the cell
+/// edges, so adjacent cells connect without gaps.
+pub(crate) fn box_drawing_rects(c: char, origin: Point, cell_size: Size) -> Option<Vec<Rectangle>> {
+    let lines = lines(c)?;
+
+    let light = (cell_size.width / 8.0).round().max(1.0);
+    let thickness = |weight: Weight| match weight {
+        Weight::None => 0.0,
+        Weight::Light => light,
+        Weight::Heavy => light * 2.0,
+    };
+
+    // Top left corner of a line with the given thickness crossing the cell center.
+    let center_x = |width: f32| origin.x + ((cell_size.width - width) / 2.0).round();
+    let center_y = |height: f32| origin.y + ((cell_size.height - height) / 2.0).round();
+
+    // Horizontal lines extend past the center to cover the vertical ones and vice versa.
+    let vertical = thickness(lines.up.max(lines.down));
+    let horizontal = thickness(lines.left.max(lines.right));
+
+    let mut rects = Vec::new();
+
+    let width = thickness(lines.left);
+    if width > 0.0 {
+        let end = center_x(vertical.max(width)) + vertical.max(width);
+        rects.push(Rectangle::new(
+            Point::new(origin.x, center_y(width)),
+            Size::new(end - origin.x, width),
+        ));
+    }
+
+    let width = thickness(lines.right);
+    if width > 0.0 {
+        let start = center_x(vertical.max(width));
+        rects.push(Rectangle::new(
+            Point::new(start, center_y(width)),
+            Size::new(origin.x + cell_size.width - start, width),
+        ));
+    }
+
+    let height = thickness(lines.up);
+    if height > 0.0 {
+        let end = center_y(horizontal.max(height)) + horizontal.max(height);
+        rects.push(Rectangle::new(
+            Point::new(center_x(height), origin.y),
+            Size::new(height, end - origin.y),
+        ));
+    }
+
+    let height = thickness(lines.down);
+    if height > 0.0 {
+        let start = center_y(horizontal.max(height));
+        rects.push(Rectangle::new(
+            Point::new(center_x(height), start),
+            Size::new(height, origin.y + cell_size.height - start),
+        ));
+    }
+
+    Some(rects)
+}
+
+/// Light and heavy lines of the box drawing block.
+fn lines(c: char) -> Option<Lines> {
+    let lines = match c {
+        '─' => Lines::new(1, 1, 0, 0),
+        '━' => Lines::new(2, 2, 0, 0),
+        '│' => Lines::new(0, 0, 1, 1),
+        '┃' => Lines::new(0, 0, 2, 2),
+        '┌' => Lines::new(0, 1, 0, 1),
+        '┍' => Lines::new(0, 2, 0, 1),
+        '┎' => Lines::new(0, 1, 0, 2),
+        '┏' => Lines::new(0, 2, 0, 2),
+        '┐' => Lines::new(1, 0, 0, 1),
+        '┑' => Lines::new(2, 0, 0, 1),
+        '┒' => Lines::new(1, 0, 0, 2),
+        '┓' => Lines::new(2, 0, 0, 2),
+        '└' => Lines::new(0, 1, 1, 0),
+        '┕' => Lines::new(0, 2, 1, 0),
+        '┖' => Lines::new(0, 1, 2, 0),
+        '┗' => Lines::new(0, 2, 2, 0),
+        '┘' => Lines::new(1, 0, 1, 0),
+        '┙' => Lines::new(2, 0, 1, 0),
+        '┚' => Lines::new(1, 0, 2, 0),
+        '┛' => Lines::new(2, 0, 2, 0),
+        '├' => Lines::new(0, 1, 1, 1),
+        '┝' => Lines::new(0, 2, 1, 1),
+        '┞' => Lines::new(0, 1, 2, 1),
+        '┟' => Lines::new(0, 1, 1, 2),
+        '┠' => Lines::new(0, 1, 2, 2),
+        '┡' => Lines::new(0, 2, 2, 1),
+        '┢' => Lines::new(0, 2, 1, 2),
+        '┣' => Lines::new(0, 2, 2, 2),
+        '┤' => Lines::new(1, 0, 1, 1),
+        '┥' => Lines::new(2, 0, 1, 1),
+        '┦' => Lines::new(1, 0, 2, 1),
+        '┧' => Lines::new(1, 0, 1, 2),
+        '┨' => Lines::new(1, 0, 2, 2),
+        '┩' => Lines::new(2, 0, 2, 1),
+        '┪' => Lines::new(2, 0, 1, 2),
+        '┫' => Lines::new(2, 0, 2, 2),
+        '┬' => Lines::new(1, 1, 0, 1),
+        '┭' => Lines::new(2, 1, 0, 1),
+        '┮' => Lines::new(1, 2, 0, 1),
+        '┯' => Lines::new(2, 2, 0, 1),
+        '┰' => Lines::new(1, 1, 0, 2),
+        '┱' => Lines::new(2, 1, 0, 2),
+        '┲' => Lines::new(1, 2, 0, 2),
+        '┳' => Lines::new(2, 2, 0, 2),
+        '┴' => Lines::new(1, 1, 1, 0),
+        '┵' => Lines::new(2, 1, 1, 0),
+        '┶' => Lines::new(1, 2, 1, 0),
+        '┷' => Lines::new(2, 2, 1, 0),
+        '┸' => Lines::new(1, 1, 2, 0),
+        '┹' => Lines::new(2, 1, 2, 0),
+        '┺' => Lines::new(1, 2, 2, 0),
+        '┻' => Lines::new(2, 2, 2, 0),
+        '┼' => Lines::new(1, 1, 1, 1),
+        '┽' => Lines::new(2, 1, 1, 1),
+        '┾' => Lines::new(1, 2, 1, 1),
+        '┿' => Lines::new(2, 2, 1, 1),
+        '╀' => Lines::new(1, 1, 2, 1),
+        '╁' => Lines::new(1, 1, 1, 2),
+        '╂' => Lines::new(1, 1, 2, 2),
+        '╃' => Lines::new(2, 1, 2, 1),
+        '╄' => Lines::new(1, 2, 2, 1),
+        '╅' => Lines::new(2, 1, 1, 2),
+        '╆' => Lines::new(1, 2, 1, 2),
+        '╇' => Lines::new(2, 2, 2, 1),
+        '╈' => Lines::new(2, 2, 1, 2),
+        '╉' => Lines::new(2, 1, 2, 2),
+        '╊' => Lines::new(1, 2, 2, 2),
+        '╋' => Lines::new(2, 2, 2, 2),
+        '╴' => Lines::new(1, 0, 0, 0),
+        '╵' => Lines::new(0, 0, 1, 0),
+        '╶' => Lines::new(0, 1, 0, 0),
+        '╷' => Lines::new(0, 0, 0, 1),
+        '╸' => Lines::new(2, 0, 0, 0),
+        '╹' => Lines::new(0, 0, 2, 0),
+        '╺' => Lines::new(0, 2, 0, 0),
+        '╻' => Lines::new(0, 0, 0, 2),
+        '╼' => Lines::new(1, 2, 0, 0),
+        '╽' => Lines::new(0, 0, 1, 2),
+        '╾' => Lines::new(2, 1, 0, 0),
+        '╿' => Lines::new(0, 0, 2, 1),
+        _ => return None,
+    };
+
+    Some(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGIN: Point = Point::new(16.0, 32.0);
+    const CELL: Size = Size::new(8.0, 16.0);
+
+    fn bounds(rects: &[Rectangle]) -> Rectangle {
+        rects
+            .iter()
+            .skip(1)
+            .fold(rects[0], |bounds, rect| bounds.union(rect))
+    }
+
+    #[test]
+    fn cross_reaches_cell_edges() {
+        let rects = box_drawing_rects('┼', ORIGIN, CELL).unwrap();
+
+        assert_eq!(rects.len(), 4);
+        assert_eq!(bounds(&rects), Rectangle::new(ORIGIN, CELL));
+
+        // Horizontal halves meet in the middle.
+        let (left, right) = (rects[0], rects[1]);
+        assert_eq!(left.y, right.y);
+        assert!(left.x + left.width >= right.x);
+
+        // Vertical halves meet in the middle.
+        let (up, down) = (rects[2], rects[3]);
+        assert_eq!(up.x, down.x);
+        assert!(up.y + up.height >= down.y);
+    }
+
+    #[test]
+    fn horizontal_line_spans_cell_width() {
+        let rects = box_drawing_rects('─', ORIGIN, CELL).unwrap();
+        let bounds = bounds(&rects);
+
+        assert_eq!(bounds.x, ORIGIN.x);
+        assert_eq!(bounds.width, CELL.width);
+        assert_eq!(bounds.height, 1.0);
+        assert_eq!(bounds.y, ORIGIN.y + 8.0);
+    }
+
+    #[test]
+    fn corner_has_no_overhang() {
+        let rects = box_drawing_rects('┌', ORIGIN, CELL).unwrap();
+        let bounds = bounds(&rects);
+
+        // Lines leave towards the right and bottom edges only.
+        assert_eq!(bounds.x + bounds.width, ORIGIN.x + CELL.width);
+        assert_eq!(bounds.y + bounds.height, ORIGIN.y + CELL.height);
+        assert!(bounds.x > ORIGIN.x);
+        assert!(bounds.y > ORIGIN.y);
+    }
+
+    #[test]
+    fn heavy_lines_are_thicker() {
+        let light = box_drawing_rects('─', ORIGIN, CELL).unwrap();
+        let heavy = box_drawing_rects('━', ORIGIN, CELL).unwrap();
+
+        assert_eq!(heavy[0].height, light[0].height * 2.0);
+    }
+
+    #[test]
+    fn text_uses_font() {
+        assert!(box_drawing_rects('a', ORIGIN, CELL).is_none());
+        assert!(box_drawing_rects('┄', ORIGIN, CELL).is_none());
+    }
+}
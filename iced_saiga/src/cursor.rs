@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use iced::time::Instant;
+
+/// Tracks the visibility phase of a blinking cursor.
+#[derive(Debug, Clone)]
+pub(crate) struct CursorBlink {
+    interval: Duration,
+    visible: bool,
+    deadline: Instant,
+}
+
+impl CursorBlink {
+    pub fn new(interval: Duration, now: Instant) -> Self {
+        Self {
+            interval,
+            visible: true,
+            deadline: now + interval,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Instant at which the phase is toggled next.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Advance the blink timer to `now`.
+    ///
+    /// Returns `true` when the visibility changed.
+    pub fn update(&mut self, now: Instant) -> bool {
+        if self.interval.is_zero() || now < self.deadline {
+            return false;
+        }
+
+        // Catch up on every interval that elapsed since the last deadline.
+        let elapsed = (now - self.deadline).as_nanos() / self.interval.as_nanos();
+        let toggles = elapsed + 1;
+        self.deadline += self.interval * toggles as u32;
+
+        let changed = toggles % 2 == 1;
+        if changed {
+            self.visible = !self.visible;
+        }
+
+        changed
+    }
+
+    /// Show the cursor and restart the blink interval.
+    pub fn reset(&mut self, now: Instant) {
+        self.visible = true;
+        self.deadline = now + self.interval;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(500);
+
+    #[test]
+    fn starts_visible() {
+        let now = Instant::now();
+        let blink = CursorBlink::new(INTERVAL, now);
+
+        assert!(blink.is_visible());
+        assert_eq!(blink.deadline(), now + INTERVAL);
+    }
+
+    #[test]
+    fn toggles_at_deadline() {
+        let now = Instant::now();
+        let mut blink = CursorBlink::new(INTERVAL, now);
+
+        assert!(!blink.update(now + Duration::from_millis(499)));
+        assert!(blink.is_visible());
+
+        assert!(blink.update(now + INTERVAL));
+        assert!(!blink.is_visible());
+        assert_eq!(blink.deadline(), now + INTERVAL * 2);
+
+        assert!(blink.update(now + INTERVAL * 2));
+        assert!(blink.is_visible());
+    }
+
+    #[test]
+    fn catches_up_after_missed_intervals() {
+        let now = Instant::now();
+        let mut blink = CursorBlink::new(INTERVAL, now);
+
+        // Two toggles elapsed, phase ends up unchanged.
+        assert!(!blink.update(now + Duration::from_millis(1200)));
+        assert!(blink.is_visible());
+        assert_eq!(blink.deadline(), now + INTERVAL * 3);
+
+        // Three toggles elapsed, phase flips.
+        assert!(blink.update(now + Duration::from_millis(2600)));
+        assert!(!blink.is_visible());
+        assert_eq!(blink.deadline(), now + INTERVAL * 6);
+    }
+
+    #[test]
+    fn reset_shows_cursor() {
+        let now = Instant::now();
+        let mut blink = CursorBlink::new(INTERVAL, now);
+
+        blink.update(now + INTERVAL);
+        assert!(!blink.is_visible());
+
+        let later = now + Duration::from_millis(700);
+        blink.reset(later);
+        assert!(blink.is_visible());
+        assert_eq!(blink.deadline(), later + INTERVAL);
+    }
+
+    #[test]
+    fn zero_interval_never_blinks() {
+        let now = Instant::now();
+        let mut blink = CursorBlink::new(Duration::ZERO, now);
+
+        assert!(!blink.update(now + INTERVAL));
+        assert!(blink.is_visible());
+    }
+}
@@ -1,4 +1,4 @@
-use iced::{Font, Pixels, Size};
+use iced::{font::Family, Font, Pixels, Size};
 use iced_core::{
     alignment::{Horizontal, Vertical},
     text::{LineHeight, Paragraph, Shaping as TextShaping, Wrapping},
@@ -14,19 +14,52 @@ pub struct TermFont {
     pub(crate) font_type: Font,
     pub(crate) scale_factor: f32,
     pub(crate) measure: Size<f32>,
+    pub(crate) builtin_box_drawing: bool,
 }
 
 impl TermFont {
     pub fn new(settings: FontSettings) -> Self {
+        let font_type = with_fallback(settings.font_type, is_installed);
+
         Self {
             size: settings.size,
-            font_type: settings.font_type,
+            font_type,
             scale_factor: settings.scale_factor,
-            measure: measure_font(settings.size, settings.scale_factor, settings.font_type),
+            measure: measure_font(settings.size, settings.scale_factor, font_type),
+            builtin_box_drawing: settings.builtin_box_drawing,
         }
     }
 }
 
+/// Replace a named font family which isn't available with the generic monospace family.
+///
+/// Otherwise a missing font leaves the terminal blank or full of placeholder glyphs.
+fn with_fallback(font: Font, is_installed: impl Fn(&str) -> bool) -> Font {
+    match font.family {
+        Family::Name(name) if !is_installed(name) => {
+            log::warn!("Font family {name:?} is not available, falling back to monospace");
+            Font {
+                family: Family::Monospace,
+                ..font
+            }
+        }
+        _ => font,
+    }
+}
+
+/// Whether a font family with the given name is loaded.
+fn is_installed(name: &str) -> bool {
+    let mut font_system = iced_graphics::text::font_system()
+        .write()
+        .expect("Write font system");
+
+    font_system
+        .raw()
+        .db()
+        .faces()
+        .any(|face| face.families.iter().any(|(family, _)| family == name))
+}
+
 fn measure_font(font_size: f32, scale_factor: f32, font_type: Font) -> Size<f32> {
     let paragraph = paragraph::Paragraph::with_text(Text {
         content: "@",
@@ -42,3 +75,17 @@ fn measure_font(font_size: f32, scale_factor: f32, font_type: Font) -> Size<f32>
 
     paragraph.min_bounds()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_family_falls_back_to_monospace() {
+        let font = Font::with_name("Missing Font");
+
+        assert_eq!(with_fallback(font, |_| false).family, Family::Monospace);
+        assert_eq!(with_fallback(font, |_| true), font);
+        assert_eq!(with_fallback(Font::MONOSPACE, |_| false), Font::MONOSPACE);
+    }
+}
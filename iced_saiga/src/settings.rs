@@ -1,4 +1,6 @@
-use iced::Font;
+use std::time::Duration;
+
+use iced::{keyboard::Modifiers, Font, Padding};
 
 use crate::theme::ColorPalette;
 
@@ -13,17 +15,24 @@ pub struct Settings {
     pub font: FontSettings,
     pub backend: BackendSettings,
     pub theme: ThemeSettings,
+    pub cursor: CursorSettings,
+    pub display: DisplaySettings,
+    pub mouse: MouseSettings,
+    pub bell: BellSettings,
 }
 
 #[derive(Clone)]
 pub struct BackendSettings {
     pub shell: String,
+    /// Draw bold text with the bright variant of its color.
+    pub bold_is_bright: bool,
 }
 
 impl Default for BackendSettings {
     fn default() -> Self {
         Self {
             shell: DEFAULT_SHELL.to_string(),
+            bold_is_bright: false,
         }
     }
 }
@@ -33,6 +42,9 @@ pub struct FontSettings {
     pub size: f32,
     pub scale_factor: f32,
     pub font_type: iced::Font,
+    /// Draw box drawing characters as rectangles instead of font glyphs, so lines connect
+    /// across cells.
+    pub builtin_box_drawing: bool,
 }
 
 impl Default for FontSettings {
@@ -41,6 +53,7 @@ impl Default for FontSettings {
             size: 15.0,
             scale_factor: 1.3,
             font_type: Font::MONOSPACE,
+            builtin_box_drawing: true,
         }
     }
 }
@@ -55,3 +68,84 @@ impl ThemeSettings {
         Self { color_palette }
     }
 }
+
+#[derive(Debug, Clone, Default)]
+pub struct DisplaySettings {
+    /// Space between the widget edges and the grid.
+    pub padding: Padding,
+    /// Placement of the strikethrough line.
+    pub strikethrough: StrikethroughSettings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrikethroughSettings {
+    /// Vertical center of the line as a fraction of the cell height, from the top.
+    pub position: f32,
+    /// Line thickness in pixels. Derived from the cell height when `None`.
+    pub thickness: Option<f32>,
+}
+
+impl Default for StrikethroughSettings {
+    fn default() -> Self {
+        Self {
+            position: 0.5,
+            thickness: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MouseSettings {
+    /// Modifiers to hold while clicking a hyperlink to open it.
+    pub hyperlink_modifiers: Modifiers,
+}
+
+impl Default for MouseSettings {
+    fn default() -> Self {
+        Self {
+            hyperlink_modifiers: Modifiers::COMMAND,
+        }
+    }
+}
+
+/// How a bell rung by the terminal is surfaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BellStyle {
+    /// Ask the window system for the user's attention, like bouncing the dock icon.
+    #[default]
+    Attention,
+    /// Briefly flash the terminal.
+    Visual,
+    /// Ignore the bell.
+    None,
+}
+
+#[derive(Debug, Clone)]
+pub struct BellSettings {
+    pub style: BellStyle,
+    /// How long the terminal stays flashed with [`BellStyle::Visual`].
+    pub flash_duration: Duration,
+}
+
+impl Default for BellSettings {
+    fn default() -> Self {
+        Self {
+            style: BellStyle::default(),
+            flash_duration: Duration::from_millis(100),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CursorSettings {
+    /// Time between visibility toggles of a blinking cursor.
+    pub blink_interval: Duration,
+}
+
+impl Default for CursorSettings {
+    fn default() -> Self {
+        Self {
+            blink_interval: Duration::from_millis(500),
+        }
+    }
+}
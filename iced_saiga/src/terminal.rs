@@ -1,4 +1,4 @@
-use iced::widget::canvas::Cache;
+use iced::{keyboard::Modifiers, time::Instant, widget::canvas::Cache, Padding};
 use saiga_backend::event::Event as TermEvent;
 use tokio::sync::mpsc::Sender;
 
@@ -7,7 +7,10 @@ use crate::{
     backend::{Backend, BackendCommand},
     bindings::{Binding, BindingAction, BindingsLayout, InputKind},
     font::TermFont,
-    settings::{BackendSettings, FontSettings, Settings, ThemeSettings},
+    settings::{
+        BackendSettings, BellSettings, BellStyle, CursorSettings, FontSettings, Settings,
+        StrikethroughSettings, ThemeSettings,
+    },
     theme::{ColorPalette, Theme},
 };
 
@@ -32,6 +35,13 @@ pub struct Terminal {
     pub(crate) backend: Option<Backend>,
     pub(crate) cache: Cache,
     pub(crate) bindings: BindingsLayout,
+    pub(crate) cursor_settings: CursorSettings,
+    pub(crate) padding: Padding,
+    pub(crate) strikethrough: StrikethroughSettings,
+    pub(crate) hyperlink_modifiers: Modifiers,
+    pub(crate) bell: BellSettings,
+    /// When the bell last flashed the terminal.
+    bell_flash: Option<Instant>,
     backend_settings: BackendSettings,
 }
 
@@ -43,6 +53,12 @@ impl Terminal {
             theme: Default::default(),
             cache: Default::default(),
             bindings: Default::default(),
+            cursor_settings: settings.cursor,
+            padding: settings.display.padding,
+            strikethrough: settings.display.strikethrough,
+            hyperlink_modifiers: settings.mouse.hyperlink_modifiers,
+            bell: settings.bell,
+            bell_flash: None,
             backend_settings: settings.backend,
             backend: None,
         }
@@ -87,6 +103,16 @@ impl Terminal {
             Command::AddBindings(bindings) => {
                 self.bindings.add_bindings(bindings);
             }
+            Command::ProcessBackendCommand(BackendCommand::ProcessTermEvent(
+                TermEvent::ColorRequest(index, format),
+            )) => {
+                if let Some(ref backend) = self.backend {
+                    let color = backend
+                        .color(index)
+                        .unwrap_or_else(|| self.theme.rgb(index));
+                    backend.write(format(color).into_bytes());
+                }
+            }
             Command::ProcessBackendCommand(c) => {
                 if let Some(ref mut backend) = self.backend {
                     action = backend.process_command(c);
@@ -94,12 +120,39 @@ impl Terminal {
                         self.redraw();
                     }
                 }
+
+                if action == Action::Bell {
+                    action = self.ring_bell();
+                }
             }
         }
 
         action
     }
 
+    /// Surface a bell according to the bell style.
+    fn ring_bell(&mut self) -> Action {
+        match self.bell.style {
+            BellStyle::Attention => Action::Bell,
+            BellStyle::Visual => {
+                self.bell_flash = Some(Instant::now());
+                Action::Redraw
+            }
+            BellStyle::None => Action::Ignore,
+        }
+    }
+
+    /// When the flash of the last visual bell ends.
+    pub(crate) fn bell_flash_end(&self) -> Option<Instant> {
+        self.bell_flash
+            .map(|start| start + self.bell.flash_duration)
+    }
+
+    /// Whether the terminal is flashed by a visual bell at `now`.
+    pub(crate) fn is_bell_flashing(&self, now: Instant) -> bool {
+        self.bell_flash_end().is_some_and(|end| now < end)
+    }
+
     fn sync_and_redraw(&mut self) {
         if let Some(ref mut backend) = self.backend {
             backend.sync();
@@ -111,3 +164,32 @@ impl Terminal {
         self.cache.clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bell_style_action() {
+        for (style, expected) in [
+            (BellStyle::Attention, Action::Bell),
+            (BellStyle::Visual, Action::Redraw),
+            (BellStyle::None, Action::Ignore),
+        ] {
+            let settings = Settings {
+                bell: BellSettings {
+                    style,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let mut term = Terminal::new(0, settings);
+
+            assert_eq!(term.ring_bell(), expected);
+            assert_eq!(
+                term.is_bell_flashing(Instant::now()),
+                style == BellStyle::Visual
+            );
+        }
+    }
+}
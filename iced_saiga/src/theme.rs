@@ -1,7 +1,11 @@
 use std::collections::HashMap;
 
 use iced::{widget::container, Color};
-use saiga_vte::ansi::{self, handler::NamedColor};
+use saiga_backend::term::color::Colors;
+use saiga_vte::ansi::{
+    self,
+    handler::{NamedColor, Rgb},
+};
 
 use crate::settings::ThemeSettings;
 
@@ -102,6 +106,39 @@ impl Theme {
     }
 }
 
+impl Theme {
+    /// Color for `c`, preferring the value an application set through OSC 4, 10, 11 or 12.
+    ///
+    /// Colors set with an alpha channel keep their transparency.
+    pub(crate) fn get_term_color(&self, colors: &Colors, c: ansi::handler::Color) -> Color {
+        let index = match c {
+            ansi::handler::Color::Named(named) => named as usize,
+            ansi::handler::Color::Indexed(index) => index as usize,
+            ansi::handler::Color::Spec(_) => return self.get_color(c),
+        };
+
+        match colors[index] {
+            Some(rgb) => {
+                let alpha = f32::from(colors.alpha(index)) / f32::from(u8::MAX);
+                Color::from_rgba8(rgb.r, rgb.g, rgb.b, alpha)
+            }
+            None => self.get_color(c),
+        }
+    }
+
+    /// Default color for a terminal color index, used to answer color queries.
+    pub(crate) fn rgb(&self, index: usize) -> Rgb {
+        let color = match index {
+            0..=255 => self.get_color(ansi::handler::Color::Indexed(index as u8)),
+            _ if index == NamedColor::Background as usize => self.palette.background,
+            _ => self.palette.foreground,
+        };
+
+        let [r, g, b, _] = color.into_rgba8();
+        Rgb { r, g, b }
+    }
+}
+
 impl TerminalStyle for Theme {
     fn container_style(&self) -> container::Style {
         container::Style {
@@ -1,26 +1,37 @@
 use iced::{
     alignment::{Horizontal, Vertical},
     keyboard::{Key, Modifiers},
+    time::Instant,
     widget::container,
-    Element, Length, Point, Rectangle, Size, Theme,
+    window::{self, RedrawRequest},
+    Color, Element, Length, Padding, Point, Rectangle, Size, Theme,
 };
 use iced_core::{
     clipboard::Kind as ClipboardKind,
+    renderer::Quad,
     text::{LineHeight, Shaping},
     widget::{operation, tree, Tree},
-    Widget,
+    Renderer as _, Widget,
 };
-use iced_graphics::geometry::{Path, Text};
-use saiga_backend::term::{cell, TermMode};
-use saiga_vte::ansi::handler::CursorShape;
+use iced_graphics::geometry::{Path, Stroke, Text};
+use saiga_backend::{
+    grid::Indexed,
+    index::{Column, Point as GridPoint},
+    selection::SelectionType,
+    term::{cell, color::Colors, TermMode},
+};
+use saiga_vte::ansi::handler::{self as ansi, CursorShape, NamedColor};
 
 use crate::{
-    backend::BackendCommand,
+    backend::{BackendCommand, MouseButton},
     bindings::{BindingAction, InputKind},
+    box_drawing::box_drawing_rects,
+    cursor::CursorBlink,
+    settings::StrikethroughSettings,
     terminal::{Command, Event, Terminal},
-    theme::TerminalStyle as _,
+    theme::{TerminalStyle as _, Theme as TermTheme},
 };
-use iced::mouse::Cursor;
+use iced::mouse::{self, Cursor};
 
 pub struct TermView<'a> {
     term: &'a Terminal,
@@ -35,6 +46,10 @@ impl<'a> TermView<'a> {
             .into()
     }
 
+    /// Translates key presses into terminal input.
+    ///
+    /// Input method composition (preedit) is not shown: iced 0.13 does not forward winit's IME
+    /// events to widgets, so text only reaches the terminal through key presses.
     fn handle_keyboard_event(
         &self,
         state: &mut TermViewState,
@@ -45,6 +60,11 @@ impl<'a> TermView<'a> {
             return None;
         };
 
+        if let iced::keyboard::Event::ModifiersChanged(modifiers) = event {
+            state.keyboard_modifiers = modifiers;
+            return None;
+        }
+
         let mut binding_action = BindingAction::Ignore;
         let last_content = backend.renderable_content();
 
@@ -94,36 +114,127 @@ impl<'a> TermView<'a> {
             BindingAction::Esc(seq) => Some(Command::ProcessBackendCommand(BackendCommand::Write(
                 seq.as_bytes().to_vec(),
             ))),
-            BindingAction::Paste => {
-                if let Some(data) = clipboard.read(ClipboardKind::Standard) {
-                    let input: Vec<u8> = data.bytes().collect();
-
-                    Some(Command::ProcessBackendCommand(BackendCommand::Write(input)))
-                } else {
-                    None
-                }
-            }
+            BindingAction::Paste => clipboard
+                .read(ClipboardKind::Standard)
+                .map(|data| Command::ProcessBackendCommand(BackendCommand::Paste(data))),
             BindingAction::Copy => {
-                // clipboard.write(ClipboardKind::Standard, backend.selectable_content());
+                clipboard.write(ClipboardKind::Standard, backend.selectable_content());
                 None
             }
             _ => None,
         }
     }
+
+    fn handle_mouse_event(
+        &self,
+        state: &mut TermViewState,
+        position: Point,
+        event: mouse::Event,
+    ) -> Vec<Command> {
+        let Some(ref backend) = self.term.backend else {
+            return Vec::new();
+        };
+
+        let mode = backend.renderable_content().term_mode;
+        let modifiers = state.keyboard_modifiers;
+        let point = backend.cell_at(position.x, position.y);
+
+        if event == mouse::Event::ButtonPressed(mouse::Button::Left)
+            && modifiers == self.term.hyperlink_modifiers
+            && backend.has_link_at(position.x, position.y)
+        {
+            return vec![Command::ProcessBackendCommand(BackendCommand::OpenLink((
+                position.x, position.y,
+            )))];
+        }
+
+        // Holding shift bypasses mouse reporting so the text can still be selected.
+        if mode.intersects(TermMode::MOUSE_MODE) && !modifiers.shift() {
+            let report = match event {
+                mouse::Event::ButtonPressed(button) => {
+                    state.mouse_button = mouse_button(button);
+                    state.mouse_button.map(|button| (button, true))
+                }
+                mouse::Event::ButtonReleased(button) => {
+                    state.mouse_button = None;
+                    mouse_button(button).map(|button| (button, false))
+                }
+                mouse::Event::CursorMoved { .. } => {
+                    if point == state.mouse_point {
+                        None
+                    } else {
+                        state.mouse_point = point;
+                        mouse_motion(mode, state.mouse_button).map(|button| (button, true))
+                    }
+                }
+                mouse::Event::WheelScrolled { delta } => {
+                    let lines = match delta {
+                        mouse::ScrollDelta::Lines { y, .. } => y,
+                        mouse::ScrollDelta::Pixels { y, .. } => y.signum(),
+                    };
+                    let button = if lines > 0.0 {
+                        MouseButton::ScrollUp
+                    } else {
+                        MouseButton::ScrollDown
+                    };
+                    (lines != 0.0).then_some((button, true))
+                }
+                _ => None,
+            };
+
+            return report
+                .map(|(button, pressed)| {
+                    Command::ProcessBackendCommand(BackendCommand::MouseReport(
+                        button, modifiers, point, pressed,
+                    ))
+                })
+                .into_iter()
+                .collect();
+        }
+
+        let position = (position.x, position.y);
+        match event {
+            mouse::Event::ButtonPressed(mouse::Button::Left) => {
+                state.mouse_button = Some(MouseButton::LeftButton);
+                vec![Command::ProcessBackendCommand(BackendCommand::SelectStart(
+                    SelectionType::Simple,
+                    position,
+                ))]
+            }
+            mouse::Event::ButtonReleased(mouse::Button::Left) => {
+                state.mouse_button = None;
+                Vec::new()
+            }
+            mouse::Event::CursorMoved { .. }
+                if matches!(state.mouse_button, Some(MouseButton::LeftButton)) =>
+            {
+                vec![Command::ProcessBackendCommand(
+                    BackendCommand::SelectUpdate(position),
+                )]
+            }
+            _ => Vec::new(),
+        }
+    }
 }
 
 pub struct TermViewState {
     is_focused: bool,
     keyboard_modifiers: Modifiers,
     size: Size<f32>,
+    cursor_blink: CursorBlink,
+    mouse_button: Option<MouseButton>,
+    mouse_point: GridPoint,
 }
 
-impl Default for TermViewState {
-    fn default() -> Self {
+impl TermViewState {
+    fn new(term: &Terminal) -> Self {
         Self {
             is_focused: true,
             keyboard_modifiers: Modifiers::empty(),
             size: Size::from([0.0, 0.0]),
+            cursor_blink: CursorBlink::new(term.cursor_settings.blink_interval, Instant::now()),
+            mouse_button: None,
+            mouse_point: GridPoint::default(),
         }
     }
 }
@@ -155,7 +266,7 @@ impl Widget<Event, Theme, iced::Renderer> for TermView<'_> {
     }
 
     fn state(&self) -> tree::State {
-        tree::State::new(TermViewState::default())
+        tree::State::new(TermViewState::new(self.term))
     }
 
     fn layout(
@@ -182,7 +293,7 @@ impl Widget<Event, Theme, iced::Renderer> for TermView<'_> {
 
     fn draw(
         &self,
-        _tree: &Tree,
+        tree: &Tree,
         renderer: &mut iced::Renderer,
         _theme: &Theme,
         _style: &iced_core::renderer::Style,
@@ -191,86 +302,121 @@ impl Widget<Event, Theme, iced::Renderer> for TermView<'_> {
         viewport: &iced::Rectangle,
     ) {
         let Some(ref backend) = &self.term.backend else {
+            // Clear to the theme background until the backend is initialized.
+            let colors = Colors::default();
+            let (quad, color) = background_quad(
+                &self.term.theme,
+                &colors,
+                layout.bounds(),
+                TermMode::empty(),
+            );
+            renderer.fill_quad(quad, color);
             return;
         };
 
-        // let _state = tree.state.downcast_ref::<TermViewState>();
+        let state = tree.state.downcast_ref::<TermViewState>();
         let content = backend.renderable_content();
         let term_size = content.term_size;
         let cell_width = term_size.cell_width as f32;
         let cell_height = term_size.cell_height as f32;
         let font_size = self.term.font.size;
         let font_scale_factor = self.term.font.scale_factor;
-        let layout_offset_x = layout.position().x;
-        let layout_offset_y = layout.position().y;
+        let grid_origin = grid_origin(layout.bounds(), self.term.padding);
+
+        let cursor = content.renderable_cursor;
+        let show_cursor = cursor.is_visible(state.cursor_blink.is_visible());
+        let cell_size = Size::new(cell_width, cell_height);
+
+        let cell_origin = |point: GridPoint| {
+            Point::new(
+                grid_origin.x + (point.column.0 as f32 * cell_width),
+                grid_origin.y
+                    + ((point.line.0 as f32 + content.grid.display_offset() as f32) * cell_height),
+            )
+        };
 
-        let show_cursor = content.term_mode.contains(TermMode::SHOW_CURSOR)
-            && content.cursor_style.shape != CursorShape::Hidden;
+        // Fill the padding around the grid
+        let (quad, color) = background_quad(
+            &self.term.theme,
+            &content.colors,
+            layout.bounds(),
+            content.term_mode,
+        );
+        renderer.fill_quad(quad, color);
 
         let geom = self.term.cache.draw(renderer, viewport.size(), |frame| {
-            for indexed in content.grid.display_iter() {
-                let x = layout_offset_x + (indexed.point.column.0 as f32 * cell_width);
-                let y = layout_offset_y
-                    + ((indexed.point.line.0 as f32 + content.grid.display_offset() as f32)
-                        * cell_height);
-
-                let mut fg = self.term.theme.get_color(indexed.fg);
-                let mut bg = self.term.theme.get_color(indexed.bg);
-
-                // Handle dim, inverse, and selected text
-                if indexed
-                    .cell
-                    .flags
-                    .intersects(cell::Flags::DIM | cell::Flags::DIM_BOLD)
-                {
-                    fg.a *= 0.7;
+            let selected =
+                |point: GridPoint| content.selectable_range.is_some_and(|r| r.contains(point));
+            let (theme, colors) = (&self.term.theme, &content.colors);
+            let (mode, bold_is_bright) = (content.term_mode, content.bold_is_bright);
+            let cell_colors = |indexed: &Indexed<&cell::Cell>| {
+                let (attrs, selected) = (indexed.cell.attrs(), selected(indexed.point));
+                cell_colors(theme, colors, attrs, selected, mode, bold_is_bright)
+            };
+
+            // Draw cell backgrounds, one rectangle per run of equally styled cells
+            for (columns, line, attrs) in content.grid.spans() {
+                let mut start = columns.start;
+                while start < columns.end {
+                    // Selected cells have different colors, so the run is split at its edges.
+                    let is_selected = selected(GridPoint::new(line, start));
+                    let end = (start.0 + 1..columns.end.0)
+                        .map(Column)
+                        .find(|&column| selected(GridPoint::new(line, column)) != is_selected)
+                        .unwrap_or(columns.end);
+
+                    let (_, bg) =
+                        cell_colors(theme, colors, attrs, is_selected, mode, bold_is_bright);
+                    let width = (end.0 - start.0) as f32 * cell_width;
+                    let origin = cell_origin(GridPoint::new(line, start));
+                    frame.fill(&Path::rectangle(origin, Size::new(width, cell_height)), bg);
+
+                    start = end;
                 }
-                if indexed.cell.flags.contains(cell::Flags::INVERSE)
-                    || content
-                        .selectable_range
-                        .is_some_and(|r| r.contains(indexed.point))
-                {
-                    std::mem::swap(&mut fg, &mut bg);
+            }
+
+            // Handle cursor rendering
+            if let Some(rect) = cursor.rect(cell_width, cell_height).filter(|_| show_cursor) {
+                let cursor_color = self.term.theme.get_color(content.cursor.fg);
+                let origin = cell_origin(cursor.point);
+                let cursor_path = Path::rectangle(
+                    Point::new(origin.x + rect.x, origin.y + rect.y),
+                    Size::new(rect.width, rect.height),
+                );
+
+                if cursor.shape == CursorShape::HollowBlock {
+                    frame.stroke(&cursor_path, Stroke::default().with_color(cursor_color));
+                } else {
+                    frame.fill(&cursor_path, cursor_color);
                 }
+            }
 
-                let cell_size = Size::new(cell_width, cell_height);
-
-                // Draw cell background
-                let background = Path::rectangle(Point::new(x, y), cell_size);
-                frame.fill(&background, bg);
-
-                // Handle cursor rendering
-                if show_cursor && content.grid.cursor.point == indexed.point {
-                    let cursor_color = self.term.theme.get_color(content.cursor.fg);
-
-                    let cursor_path = match content.cursor_style.shape {
-                        CursorShape::Beam => Path::rectangle(
-                            Point::new(x, y),
-                            Size {
-                                width: cell_size.width * 0.2,
-                                height: cell_size.height,
-                            },
-                        ),
-                        _ => Path::rectangle(Point::new(x, y), cell_size),
-                    };
+            for indexed in content.grid.display_iter() {
+                let origin = cell_origin(indexed.point);
+                let (mut fg, bg) = cell_colors(&indexed);
 
-                    frame.fill(&cursor_path, cursor_color);
+                if show_cursor && cursor.point == indexed.point && cursor.inverts_cell() {
+                    fg = bg;
                 }
 
+                let box_drawing = if self.term.font.builtin_box_drawing {
+                    box_drawing_rects(indexed.c, origin, cell_size)
+                } else {
+                    None
+                };
+
                 // Draw text
-                if indexed.c != ' ' && indexed.c != '\t' {
-                    if content.grid.cursor.point == indexed.point
-                        && content.term_mode.contains(TermMode::APP_CURSOR)
-                        && content.cursor_style.shape == CursorShape::Block
-                    {
-                        fg = bg;
+                if let Some(rects) = box_drawing {
+                    for rect in rects {
+                        frame.fill(&Path::rectangle(rect.position(), rect.size()), fg);
                     }
+                } else if indexed.c != ' '
+                    && indexed.c != '\t'
+                    && !indexed.cell.flags.contains(cell::Flags::WIDE_CHAR_SPACER)
+                {
                     let text = Text {
                         content: indexed.c.to_string(),
-                        position: Point::new(
-                            x + (cell_size.width / 2.0),
-                            y + (cell_size.height / 2.0),
-                        ),
+                        position: glyph_center(indexed.cell.flags, origin, cell_size),
                         font: self.term.font.font_type,
                         size: iced_core::Pixels(font_size),
                         color: fg,
@@ -281,11 +427,29 @@ impl Widget<Event, Theme, iced::Renderer> for TermView<'_> {
                     };
                     frame.fill_text(text);
                 }
+
+                // Draw underline and strikethrough decorations
+                let flags = indexed.cell.flags;
+                for rect in decoration_rects(flags, origin, cell_size, &self.term.strikethrough) {
+                    frame.fill(&Path::rectangle(rect.position(), rect.size()), fg);
+                }
             }
         });
 
         use iced::advanced::graphics::geometry::Renderer as _;
         renderer.draw_geometry(geom);
+
+        if self.term.is_bell_flashing(Instant::now()) {
+            let fg = self
+                .term
+                .theme
+                .get_color(ansi::Color::Named(NamedColor::Foreground));
+            let quad = Quad {
+                bounds: layout.bounds(),
+                ..Default::default()
+            };
+            renderer.fill_quad(quad, Color { a: 0.2, ..fg });
+        }
     }
 
     fn on_event(
@@ -303,25 +467,49 @@ impl Widget<Event, Theme, iced::Renderer> for TermView<'_> {
         let layout_size = layout.bounds().size();
         if state.size != layout_size && self.term.backend.is_some() {
             state.size = layout_size;
-            let cmd =
-                Command::ProcessBackendCommand(BackendCommand::Resize(Some(layout_size), None));
+            let grid_size = grid_size(layout_size, self.term.padding);
+            let cmd = Command::ProcessBackendCommand(BackendCommand::Resize(Some(grid_size), None));
 
             shell.publish(Event::CommandReceived(self.term.id, cmd));
         }
 
+        // Redraw once more to remove the visual bell when it ends, focused or not.
+        if let iced::Event::Window(window::Event::RedrawRequested(now)) = event {
+            if let Some(end) = self.term.bell_flash_end().filter(|&end| now < end) {
+                shell.request_redraw(RedrawRequest::At(end));
+            }
+        }
+
         if !state.is_focused {
             return iced::event::Status::Ignored;
         }
 
+        if let iced::Event::Window(window::Event::RedrawRequested(now)) = event {
+            let blinking = self
+                .term
+                .backend
+                .as_ref()
+                .is_some_and(|backend| backend.renderable_content().renderable_cursor.blinking);
+
+            if blinking {
+                if state.cursor_blink.update(now) {
+                    self.term.cache.clear();
+                }
+                shell.request_redraw(RedrawRequest::At(state.cursor_blink.deadline()));
+            }
+
+            return iced::event::Status::Ignored;
+        }
+
         let commands = match event {
-            // iced::Event::Mouse(mouse_event) if self.is_cursor_in_layout(cursor, layout) => {
-            //     self.handle_mouse_event(
-            //         state,
-            //         layout.position(),
-            //         cursor.position().unwrap(), // Assuming cursor position is always available here.
-            //         mouse_event,
-            //     )
-            // }
+            iced::Event::Mouse(mouse_event) => match cursor.position_in(layout.bounds()) {
+                Some(position) => {
+                    let padding = self.term.padding;
+                    let position = Point::new(position.x - padding.left, position.y - padding.top);
+                    self.handle_mouse_event(state, position, mouse_event)
+                }
+                None => Vec::new(),
+            },
             iced::Event::Keyboard(keyboard_event) => {
                 self.handle_keyboard_event(state, clipboard, keyboard_event)
                     .into_iter() // Convert Option to iterator (0 or 1 element)
@@ -331,6 +519,12 @@ impl Widget<Event, Theme, iced::Renderer> for TermView<'_> {
         };
 
         if !commands.is_empty() {
+            // Keep the cursor visible while typing.
+            if !state.cursor_blink.is_visible() {
+                self.term.cache.clear();
+            }
+            state.cursor_blink.reset(Instant::now());
+
             for cmd in commands {
                 shell.publish(Event::CommandReceived(self.term.id, cmd));
             }
@@ -341,8 +535,340 @@ impl Widget<Event, Theme, iced::Renderer> for TermView<'_> {
     }
 }
 
+/// Quad covering `bounds` with the terminal background color.
+fn background_quad(
+    theme: &TermTheme,
+    colors: &Colors,
+    bounds: Rectangle,
+    mode: TermMode,
+) -> (Quad, Color) {
+    // Reverse video also inverts the clear color.
+    let color = if mode.contains(TermMode::REVERSE_VIDEO) {
+        NamedColor::Foreground
+    } else {
+        NamedColor::Background
+    };
+    let color = theme.get_term_color(colors, ansi::Color::Named(color));
+    let quad = Quad {
+        bounds,
+        ..Quad::default()
+    };
+
+    (quad, color)
+}
+
+/// Foreground and background colors of a cell with the given attributes.
+///
+/// Inverse and selected cells swap their colors. Reverse video mode (DECSCNM) swaps the colors of
+/// every cell on top of that. With `bold_is_bright`, bold cells use the bright foreground color.
+/// Colors set by the application in `colors` take precedence over the theme.
+fn cell_colors(
+    theme: &TermTheme,
+    colors: &Colors,
+    attrs: cell::Attrs,
+    selected: bool,
+    mode: TermMode,
+    bold_is_bright: bool,
+) -> (Color, Color) {
+    let mut fg = theme.get_term_color(colors, attrs.foreground(bold_is_bright));
+    let mut bg = theme.get_term_color(colors, attrs.bg);
+
+    // Handle dim, inverse, and selected text
+    if attrs
+        .flags
+        .intersects(cell::Flags::DIM | cell::Flags::DIM_BOLD)
+    {
+        fg.a *= 0.7;
+    }
+
+    let inverse = attrs.flags.contains(cell::Flags::INVERSE) || selected;
+    if inverse != mode.contains(TermMode::REVERSE_VIDEO) {
+        std::mem::swap(&mut fg, &mut bg);
+    }
+
+    (fg, bg)
+}
+
+/// Top left corner of the grid inside the widget `bounds`.
+fn grid_origin(bounds: Rectangle, padding: Padding) -> Point {
+    Point::new(bounds.x + padding.left, bounds.y + padding.top)
+}
+
+/// Space available to the grid inside a widget of the given size.
+fn grid_size(size: Size, padding: Padding) -> Size {
+    Size::new(
+        (size.width - padding.horizontal()).max(0.0),
+        (size.height - padding.vertical()).max(0.0),
+    )
+}
+
+/// Report code for a mouse button, if it can be reported.
+fn mouse_button(button: mouse::Button) -> Option<MouseButton> {
+    match button {
+        mouse::Button::Left => Some(MouseButton::LeftButton),
+        mouse::Button::Middle => Some(MouseButton::MiddleButton),
+        mouse::Button::Right => Some(MouseButton::RightButton),
+        _ => None,
+    }
+}
+
+/// Report code for cursor motion, if the terminal mode asks for it.
+fn mouse_motion(mode: TermMode, button: Option<MouseButton>) -> Option<MouseButton> {
+    match button {
+        Some(MouseButton::LeftButton) => Some(MouseButton::LeftMove),
+        Some(MouseButton::MiddleButton) => Some(MouseButton::MiddleMove),
+        Some(MouseButton::RightButton) => Some(MouseButton::RightMove),
+        Some(_) => None,
+        None if mode.contains(TermMode::MOUSE_MOTION) => Some(MouseButton::NoneMove),
+        None => None,
+    }
+    .filter(|_| mode.intersects(TermMode::MOUSE_DRAG | TermMode::MOUSE_MOTION))
+}
+
+/// Computes the rectangles for the line decorations of a cell located at `origin`.
+///
+/// Dotted, dashed and curly underlines are drawn as a single straight underline.
+fn decoration_rects(
+    flags: cell::Flags,
+    origin: Point,
+    cell_size: Size,
+    strikethrough: &StrikethroughSettings,
+) -> Vec<Rectangle> {
+    let thickness = (cell_size.height / 16.0).round().max(1.0);
+    let line = |y: f32, thickness: f32| {
+        Rectangle::new(
+            Point::new(origin.x, y),
+            Size::new(cell_size.width, thickness),
+        )
+    };
+    let bottom = origin.y + cell_size.height - thickness;
+
+    let mut rects = Vec::new();
+    if flags.contains(cell::Flags::DOUBLE_UNDERLINE) {
+        rects.push(line(bottom, thickness));
+        rects.push(line(bottom - 2.0 * thickness, thickness));
+    } else if flags.intersects(cell::Flags::ALL_UNDERLINES) {
+        rects.push(line(bottom, thickness));
+    }
+
+    if flags.contains(cell::Flags::STRIKEOUT) {
+        let thickness = strikethrough.thickness.unwrap_or(thickness);
+        let center = origin.y + cell_size.height * strikethrough.position;
+        rects.push(line(center - thickness / 2.0, thickness));
+    }
+
+    rects
+}
+
+/// Center of the glyph drawn for a cell located at `origin`.
+///
+/// Wide characters are centered across their own cell and the spacer cell following it.
+fn glyph_center(flags: cell::Flags, origin: Point, cell_size: Size) -> Point {
+    let columns = if flags.contains(cell::Flags::WIDE_CHAR) {
+        2.0
+    } else {
+        1.0
+    };
+
+    Point::new(
+        origin.x + (columns * cell_size.width / 2.0),
+        origin.y + (cell_size.height / 2.0),
+    )
+}
+
 impl<'a> From<TermView<'a>> for Element<'a, Event, Theme, iced::Renderer> {
     fn from(widget: TermView<'a>) -> Self {
         Self::new(widget)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{settings::Settings, theme::ColorPalette};
+    use saiga_vte::ansi::handler::Rgb;
+
+    const ORIGIN: Point = Point::new(10.0, 32.0);
+    const CELL: Size = Size::new(8.0, 16.0);
+
+    fn offsets(flags: cell::Flags) -> Vec<f32> {
+        decoration_rects(flags, ORIGIN, CELL, &StrikethroughSettings::default())
+            .iter()
+            .map(|rect| rect.y - ORIGIN.y)
+            .collect()
+    }
+
+    #[test]
+    fn background_without_backend() {
+        let term = Terminal::new(0, Settings::default());
+        assert!(term.backend.is_none());
+
+        let bounds = Rectangle::new(Point::new(5.0, 10.0), Size::new(640.0, 480.0));
+        let colors = Colors::default();
+        let (quad, color) = background_quad(&term.theme, &colors, bounds, TermMode::empty());
+
+        assert_eq!(quad.bounds, bounds);
+        assert_eq!(color, ColorPalette::default().background);
+
+        let (_, color) = background_quad(&term.theme, &colors, bounds, TermMode::REVERSE_VIDEO);
+        assert_eq!(color, ColorPalette::default().foreground);
+    }
+
+    #[test]
+    fn reverse_video_swaps_cell_colors() {
+        let (theme, colors) = (TermTheme::default(), Colors::default());
+        let palette = ColorPalette::default();
+        let attrs = cell::Cell::default().attrs();
+
+        let normal = cell_colors(&theme, &colors, attrs, false, TermMode::empty(), false);
+        assert_eq!(normal, (palette.foreground, palette.background));
+
+        let mode = TermMode::REVERSE_VIDEO;
+        let reverse = cell_colors(&theme, &colors, attrs, false, mode, false);
+        assert_eq!(reverse, (palette.background, palette.foreground));
+
+        // Inverse cells are swapped back.
+        let inverse = cell::Cell {
+            flags: cell::Flags::INVERSE,
+            ..cell::Cell::default()
+        };
+        let reverse = cell_colors(&theme, &colors, inverse.attrs(), false, mode, false);
+        assert_eq!(reverse, normal);
+    }
+
+    #[test]
+    fn bold_is_bright_cell_colors() {
+        let (theme, colors) = (TermTheme::default(), Colors::default());
+        let bold_red = cell::Cell {
+            fg: ansi::Color::Named(NamedColor::Red),
+            flags: cell::Flags::BOLD,
+            ..cell::Cell::default()
+        };
+
+        let mode = TermMode::empty();
+        let (fg, _) = cell_colors(&theme, &colors, bold_red.attrs(), false, mode, false);
+        assert_eq!(fg, theme.get_color(ansi::Color::Named(NamedColor::Red)));
+
+        let (fg, _) = cell_colors(&theme, &colors, bold_red.attrs(), false, mode, true);
+        assert_eq!(
+            fg,
+            theme.get_color(ansi::Color::Named(NamedColor::BrightRed))
+        );
+    }
+
+    #[test]
+    fn application_colors_keep_alpha() {
+        let (theme, mut colors) = (TermTheme::default(), Colors::default());
+        let background = NamedColor::Background as usize;
+        colors[background] = Some(Rgb::new(0x10, 0x20, 0x30));
+        colors.set_alpha(background, 0x80);
+
+        let (_, bg) = cell_colors(
+            &theme,
+            &colors,
+            cell::Cell::default().attrs(),
+            false,
+            TermMode::empty(),
+            false,
+        );
+        assert_eq!(bg, Color::from_rgba8(0x10, 0x20, 0x30, 128.0 / 255.0));
+
+        let bounds = Rectangle::new(Point::ORIGIN, Size::new(640.0, 480.0));
+        let (_, color) = background_quad(&theme, &colors, bounds, TermMode::empty());
+        assert_eq!(color, bg);
+    }
+
+    #[test]
+    fn padding_offsets_grid() {
+        let bounds = Rectangle::new(Point::new(5.0, 10.0), Size::new(640.0, 480.0));
+        let padding = Padding {
+            top: 4.0,
+            right: 6.0,
+            bottom: 8.0,
+            left: 2.0,
+        };
+
+        assert_eq!(grid_origin(bounds, padding), Point::new(7.0, 14.0));
+        assert_eq!(grid_origin(bounds, Padding::ZERO), bounds.position());
+    }
+
+    #[test]
+    fn padding_reduces_grid_size() {
+        let size = Size::new(640.0, 480.0);
+
+        assert_eq!(grid_size(size, Padding::new(10.0)), Size::new(620.0, 460.0));
+        assert_eq!(grid_size(size, Padding::ZERO), size);
+
+        // Padding larger than the widget leaves no room for the grid.
+        assert_eq!(grid_size(size, Padding::new(400.0)), Size::ZERO);
+    }
+
+    #[test]
+    fn no_decorations() {
+        assert!(offsets(cell::Flags::empty()).is_empty());
+        assert!(offsets(cell::Flags::BOLD | cell::Flags::ITALIC).is_empty());
+    }
+
+    #[test]
+    fn underline() {
+        assert_eq!(offsets(cell::Flags::UNDERLINE), vec![15.0]);
+    }
+
+    #[test]
+    fn double_underline() {
+        assert_eq!(offsets(cell::Flags::DOUBLE_UNDERLINE), vec![15.0, 13.0]);
+    }
+
+    #[test]
+    fn undercurl_falls_back_to_underline() {
+        assert_eq!(offsets(cell::Flags::UNDERCURL), vec![15.0]);
+    }
+
+    #[test]
+    fn strikeout() {
+        assert_eq!(offsets(cell::Flags::STRIKEOUT), vec![7.5]);
+        assert_eq!(
+            offsets(cell::Flags::UNDERLINE | cell::Flags::STRIKEOUT),
+            vec![15.0, 7.5]
+        );
+    }
+
+    #[test]
+    fn strikeout_placement() {
+        let strikethrough = StrikethroughSettings {
+            position: 0.25,
+            thickness: Some(2.0),
+        };
+        let rects = decoration_rects(cell::Flags::STRIKEOUT, ORIGIN, CELL, &strikethrough);
+
+        assert_eq!(rects.len(), 1);
+        assert_eq!(rects[0].height, 2.0);
+        assert_eq!(rects[0].center_y(), ORIGIN.y + 4.0);
+    }
+
+    #[test]
+    fn decorations_span_cell_width() {
+        for rect in decoration_rects(
+            cell::Flags::UNDERLINE | cell::Flags::STRIKEOUT,
+            ORIGIN,
+            CELL,
+            &StrikethroughSettings::default(),
+        ) {
+            assert_eq!(rect.x, ORIGIN.x);
+            assert_eq!(rect.width, CELL.width);
+            assert_eq!(rect.height, 1.0);
+        }
+    }
+
+    #[test]
+    fn wide_glyph_spans_two_cells() {
+        assert_eq!(
+            glyph_center(cell::Flags::empty(), ORIGIN, CELL),
+            Point::new(14.0, 40.0)
+        );
+        assert_eq!(
+            glyph_center(cell::Flags::WIDE_CHAR, ORIGIN, CELL),
+            Point::new(18.0, 40.0)
+        );
+    }
+}
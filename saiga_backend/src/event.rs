@@ -45,6 +45,17 @@ pub enum Event {
     /// Request to write the text area size.
     TextAreaSizeRequest(Arc<dyn Fn(WindowSize) -> String + Sync + Send + 'static>),
 
+    /// Request to write whether the window is iconified.
+    ///
+    /// The attached function formats the iconified state as the expected escape sequence.
+    WindowStateRequest(Arc<dyn Fn(bool) -> String + Sync + Send + 'static>),
+
+    /// Request to write the position of the window on the screen.
+    ///
+    /// The attached function formats the `x` and `y` pixel coordinates as the expected escape
+    /// sequence.
+    WindowPositionRequest(Arc<dyn Fn(i32, i32) -> String + Sync + Send + 'static>),
+
     /// Cursor blinking state has changed.
     CursorBlinkingChange,
 
@@ -67,6 +78,8 @@ impl Debug for Event {
             Event::ClipboardStore(ty, text) => write!(f, "ClipboardStore({ty:?}, {text})"),
             Event::ClipboardLoad(ty, _) => write!(f, "ClipboardLoad({ty:?})"),
             Event::TextAreaSizeRequest(_) => write!(f, "TextAreaSizeRequest"),
+            Event::WindowStateRequest(_) => write!(f, "WindowStateRequest"),
+            Event::WindowPositionRequest(_) => write!(f, "WindowPositionRequest"),
             Event::ColorRequest(index, _) => write!(f, "ColorRequest({index})"),
             Event::PtyWrite(text) => write!(f, "PtyWrite({text})"),
             Event::Title(title) => write!(f, "Title({title})"),
@@ -1,10 +1,11 @@
 //! A specialized 2D grid implementation optimized for use in a terminal.
 
 use std::cmp::{max, min};
+use std::iter;
 use std::ops::{Bound, Deref, Index, IndexMut, Range, RangeBounds};
 
 use crate::index::{Column, Line, Point};
-use crate::term::cell::{Flags, ResetDiscriminant};
+use crate::term::cell::{Attrs, Cell, Flags, ResetDiscriminant};
 use saiga_vte::ansi::handler::{Charset, CharsetIndex};
 
 pub mod resize;
@@ -38,6 +39,11 @@ pub struct Cursor<T> {
     /// Currently configured graphic character sets.
     pub charsets: Charsets,
 
+    /// Whether origin mode was active when this cursor was saved.
+    ///
+    /// Only meaningful for saved cursors, restoring one also restores the mode.
+    pub origin: bool,
+
     /// Tracks if the next call to input will need to first handle wrapping.
     ///
     /// This is true after the last column is set with the input function. Any function that
@@ -191,6 +197,10 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         T: ResetDiscriminant<D>,
         D: PartialEq,
     {
+        if region.start >= region.end {
+            return;
+        }
+
         // When rotating the entire region, just reset everything.
         if region.end - region.start <= positions {
             for i in (region.start.0..region.end.0).map(Line::from) {
@@ -252,6 +262,10 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         T: ResetDiscriminant<D>,
         D: PartialEq,
     {
+        if region.start >= region.end {
+            return;
+        }
+
         // When rotating the entire region with fixed lines at the top, just reset everything.
         if region.end - region.start <= positions && region.start != 0 {
             for i in (region.start.0..region.end.0).map(Line::from) {
@@ -309,6 +323,10 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         T: ResetDiscriminant<D>,
         D: PartialEq,
     {
+        if self.is_empty() {
+            return;
+        }
+
         // Determine how many lines to scroll up by.
         let end = Point::new(Line(self.lines as i32 - 1), Column(self.columns()));
         let mut iter = self.iter_from(end);
@@ -369,7 +387,7 @@ impl<T> Grid<T> {
             Bound::Unbounded => Line(self.screen_lines() as i32),
         };
 
-        debug_assert!(start < self.screen_lines() as i32);
+        debug_assert!(start < self.screen_lines() as i32 || start >= end);
         debug_assert!(end <= self.screen_lines() as i32);
 
         for line in (start.0..end.0).map(Line::from) {
@@ -377,6 +395,24 @@ impl<T> Grid<T> {
         }
     }
 
+    /// Fill a rectangular region within the visible area with copies of `template`.
+    ///
+    /// Unlike a reset, the template's content is kept, so the region can be filled with an
+    /// arbitrary character instead of blanks.
+    pub fn fill_region(&mut self, lines: Range<Line>, columns: Range<Column>, template: &T)
+    where
+        T: Clone,
+    {
+        debug_assert!(lines.end <= self.screen_lines() as i32);
+        debug_assert!(columns.end <= self.columns());
+
+        for line in (lines.start.0..lines.end.0).map(Line::from) {
+            for cell in &mut self.raw[line][columns.clone()] {
+                *cell = template.clone();
+            }
+        }
+    }
+
     #[inline]
     pub fn clear_history(&mut self) {
         // Explicitly purge all lines from history.
@@ -435,6 +471,12 @@ impl<T> Grid<T> {
         }
     }
 
+    /// Check if the grid has no visible cells.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.lines == 0 || self.columns == 0
+    }
+
     #[inline]
     pub fn display_offset(&self) -> usize {
         self.display_offset
@@ -447,6 +489,38 @@ impl<T> Grid<T> {
     }
 }
 
+impl Grid<Cell> {
+    /// Iterate over runs of adjacent visible cells sharing the same attributes.
+    ///
+    /// Runs never continue across lines.
+    pub fn spans(&self) -> impl Iterator<Item = (Range<Column>, Line, Attrs)> + '_ {
+        let top = -(self.display_offset as i32);
+        let columns = self.columns;
+
+        (top..top + self.lines as i32)
+            .map(Line)
+            .flat_map(move |line| {
+                let row = &self[line];
+                let mut start = 0;
+
+                iter::from_fn(move || {
+                    if start >= columns {
+                        return None;
+                    }
+
+                    let attrs = row[Column(start)].attrs();
+                    let end = (start + 1..columns)
+                        .find(|&column| row[Column(column)].attrs() != attrs)
+                        .unwrap_or(columns);
+
+                    let span = (Column(start)..Column(end), line, attrs);
+                    start = end;
+                    Some(span)
+                })
+            })
+    }
+}
+
 impl<T: PartialEq> PartialEq for Grid<T> {
     fn eq(&self, other: &Self) -> bool {
         // Compare struct fields and check result of grid comparison.
@@ -503,7 +577,7 @@ pub trait Dimensions {
     /// Index for the last column.
     #[inline]
     fn last_column(&self) -> Column {
-        Column(self.columns() - 1)
+        Column(self.columns().saturating_sub(1))
     }
 
     /// Line farthest up in the grid history.
@@ -601,7 +675,7 @@ impl<'a, T> Iterator for GridIterator<'a, T> {
 
     fn next(&mut self) -> Option<Self::Item> {
         // Stop once we've reached the end of the grid.
-        if self.point >= self.end {
+        if self.point >= self.end || self.grid.columns() == 0 {
             return None;
         }
 
@@ -631,7 +705,7 @@ impl<T> BidirectionalIterator for GridIterator<'_, T> {
         let last_column = self.grid.last_column();
 
         // Stop once we've reached the end of the grid.
-        if self.point == Point::new(topmost_line, Column(0)) {
+        if self.point == Point::new(topmost_line, Column(0)) || self.grid.columns() == 0 {
             return None;
         }
 
@@ -18,6 +18,9 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         T: ResetDiscriminant<D>,
         D: PartialEq,
     {
+        // Reflow is meaningless while either the old or the new grid has no cells.
+        let reflow = reflow && !self.is_empty() && lines != 0 && columns != 0;
+
         // Use empty template cell for resetting cells due to resize.
         let template = mem::take(&mut self.cursor.template);
 
@@ -50,7 +53,7 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         let lines_added = target - self.lines;
 
         // Need to resize before updating buffer.
-        self.raw.grow_visible_lines(target);
+        self.raw.grow_visible_lines(target, self.columns);
         self.lines = target;
 
         let history_size = self.history_size();
@@ -82,17 +85,20 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         T: ResetDiscriminant<D>,
         D: PartialEq,
     {
+        // Keep the cursor on the first line when no lines are left.
+        let last_line = Line(target.saturating_sub(1) as i32);
+
         // Scroll up to keep content inside the window.
         let required_scrolling = (self.cursor.point.line.0 as usize + 1).saturating_sub(target);
         if required_scrolling > 0 {
             self.scroll_up(&(Line(0)..Line(self.lines as i32)), required_scrolling);
 
             // Clamp cursors to the new viewport size.
-            self.cursor.point.line = min(self.cursor.point.line, Line(target as i32 - 1));
+            self.cursor.point.line = min(self.cursor.point.line, last_line);
         }
 
         // Clamp saved cursor, since only primary cursor is scrolled into viewport.
-        self.saved_cursor.point.line = min(self.saved_cursor.point.line, Line(target as i32 - 1));
+        self.saved_cursor.point.line = min(self.saved_cursor.point.line, last_line);
 
         self.raw.rotate((self.lines - target) as isize);
         self.raw.shrink_visible_lines(target);
@@ -276,9 +282,13 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
                 // Remove all cells which require reflowing.
                 let mut wrapped = match row.shrink(columns) {
                     Some(wrapped) if reflow => wrapped,
+                    _ if !reflow => {
+                        new_raw.push(row);
+                        break;
+                    }
                     _ => {
                         let cursor_buffer_line = self.lines - self.cursor.point.line.0 as usize - 1;
-                        if reflow && i == cursor_buffer_line && self.cursor.point.column > columns {
+                        if i == cursor_buffer_line && self.cursor.point.column > columns {
                             // If there are empty cells before the cursor, we assume it is explicit
                             // whitespace and need to wrap it like normal content.
                             Vec::new()
@@ -381,7 +391,7 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
 
         // Reflow the primary cursor, or clamp it if reflow is disabled.
         if !reflow {
-            self.cursor.point.column = min(self.cursor.point.column, Column(columns - 1));
+            self.cursor.point.column = min(self.cursor.point.column, self.last_column());
         } else if self.cursor.point.column == columns
             && !self[self.cursor.point.line][Column(columns - 1)]
                 .flags()
@@ -394,6 +404,6 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         }
 
         // Clamp the saved cursor to the grid.
-        self.saved_cursor.point.column = min(self.saved_cursor.point.column, Column(columns - 1));
+        self.saved_cursor.point.column = min(self.saved_cursor.point.column, self.last_column());
     }
 }
@@ -31,9 +31,10 @@ impl<T: Clone + Default> Row<T> {
     ///
     /// Ideally the `template` should be `Copy` in all performance sensitive scenarios.
     pub fn new(columns: usize) -> Row<T> {
-        debug_assert!(columns >= 1);
-
         let mut inner: Vec<T> = Vec::with_capacity(columns);
+        if columns == 0 {
+            return Row { inner, occ: 0 };
+        }
 
         // This is a slightly optimized version of `std::vec::Vec::resize`.
         unsafe {
@@ -93,7 +94,9 @@ impl<T: Clone + Default> Row<T> {
         T: ResetDiscriminant<D> + GridCell,
         D: PartialEq,
     {
-        debug_assert!(!self.inner.is_empty());
+        if self.inner.is_empty() {
+            return;
+        }
 
         // Mark all cells as dirty if template cell changed.
         let len = self.inner.len();
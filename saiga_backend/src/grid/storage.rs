@@ -74,14 +74,13 @@ impl<T> Storage<T> {
 
     /// Increase the number of lines in the buffer.
     #[inline]
-    pub fn grow_visible_lines(&mut self, next: usize)
+    pub fn grow_visible_lines(&mut self, next: usize, columns: usize)
     where
         T: Clone + Default,
     {
         // Number of lines the buffer needs to grow.
         let additional_lines = next - self.visible_lines;
 
-        let columns = self[Line(0)].len();
         self.initialize(additional_lines, columns);
 
         // Update visible lines.
@@ -178,6 +177,10 @@ impl<T> Storage<T> {
         debug_assert!(count.unsigned_abs() <= self.inner.len());
 
         let len = self.inner.len();
+        if len == 0 {
+            return;
+        }
+
         self.zero = (self.zero as isize + count + len as isize) as usize % len;
     }
 
@@ -188,6 +191,10 @@ impl<T> Storage<T> {
     /// [`rotate_left`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.rotate_left
     #[inline]
     pub fn rotate_down(&mut self, count: usize) {
+        if self.inner.is_empty() {
+            return;
+        }
+
         self.zero = (self.zero + count) % self.inner.len();
     }
 
@@ -358,7 +365,7 @@ mod tests {
         };
 
         // Grow buffer.
-        storage.grow_visible_lines(4);
+        storage.grow_visible_lines(4, 1);
 
         // Make sure the result is correct.
         let mut expected = Storage {
@@ -399,7 +406,7 @@ mod tests {
         };
 
         // Grow buffer.
-        storage.grow_visible_lines(4);
+        storage.grow_visible_lines(4, 1);
 
         // Make sure the result is correct.
         let mut expected = Storage {
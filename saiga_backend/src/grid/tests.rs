@@ -3,6 +3,7 @@
 use super::*;
 
 use crate::term::cell::Cell;
+use saiga_vte::ansi::handler::{Color, NamedColor};
 
 impl GridCell for usize {
     fn is_empty(&self) -> bool {
@@ -22,6 +23,25 @@ impl GridCell for usize {
     }
 }
 
+#[test]
+fn spans_merge_equal_attributes() {
+    let mut grid = Grid::<Cell>::new(1, 5, 0);
+    let red = Color::Named(NamedColor::Red);
+    for column in 0..2 {
+        grid[Line(0)][Column(column)].fg = red;
+    }
+    grid[Line(0)][Column(1)].flags.insert(Flags::WRAPLINE);
+
+    let spans: Vec<_> = grid.spans().collect();
+
+    assert_eq!(spans.len(), 2);
+    assert_eq!(spans[0].0, Column(0)..Column(2));
+    assert_eq!(spans[0].1, Line(0));
+    assert_eq!(spans[0].2.fg, red);
+    assert_eq!(spans[1].0, Column(2)..Column(5));
+    assert_eq!(spans[1].2, Cell::default().attrs());
+}
+
 // Scroll up moves lines upward.
 #[test]
 fn scroll_up() {
@@ -348,6 +368,57 @@ fn shrink_reflow_disabled() {
     assert_eq!(grid[Line(0)][Column(1)], cell('2'));
 }
 
+#[test]
+fn empty_grid_operations() {
+    for (lines, columns) in [(0, 0), (0, 80), (24, 0)] {
+        let mut grid = Grid::<Cell>::new(lines, columns, 10);
+        let region = Line(0)..Line(lines as i32);
+
+        grid.scroll_up(&region, 1);
+        grid.scroll_down(&region, 1);
+        grid.reset_region(..);
+        grid.clear_viewport();
+        grid.scroll_display(Scroll::PageUp);
+        grid.scroll_display(Scroll::Top);
+        assert_eq!(grid.display_iter().count(), 0);
+        grid.clear_history();
+        grid.reset();
+
+        assert_eq!(grid.screen_lines(), lines);
+        assert_eq!(grid.columns(), columns);
+    }
+}
+
+#[test]
+fn resize_from_empty_grid() {
+    let mut grid = Grid::<Cell>::new(0, 0, 10);
+
+    grid.resize(true, 0, 80);
+    assert_eq!(grid.columns(), 80);
+    assert_eq!(grid.screen_lines(), 0);
+
+    grid.resize(true, 2, 80);
+    assert_eq!(grid.screen_lines(), 2);
+    assert_eq!(grid[Line(1)].len(), 80);
+    assert_eq!(grid[Line(1)][Column(79)], Cell::default());
+}
+
+#[test]
+fn resize_to_empty_grid() {
+    let mut grid = Grid::<Cell>::new(3, 80, 10);
+    grid[Line(0)][Column(0)] = cell('a');
+
+    grid.resize(true, 0, 80);
+    assert_eq!(grid.screen_lines(), 0);
+
+    grid.resize(false, 0, 0);
+    assert_eq!(grid.columns(), 0);
+
+    grid.resize(true, 3, 80);
+    assert_eq!(grid.screen_lines(), 3);
+    assert_eq!(grid.columns(), 80);
+}
+
 // https://github.com/rust-lang/rust-clippy/pull/6375
 #[allow(clippy::all)]
 fn cell(c: char) -> Cell {
@@ -97,6 +97,8 @@ impl SelectionRange {
 pub enum SelectionType {
     Simple,
     Block,
+    Semantic,
+    Lines,
 }
 
 /// Describes a region of a 2-dimensional area.
@@ -225,6 +227,7 @@ impl Selection {
                         && start.side == Side::Left
                         && end.side == Side::Right)
             }
+            SelectionType::Semantic | SelectionType::Lines => false,
         }
     }
 
@@ -294,6 +297,30 @@ impl Selection {
         match self.ty {
             SelectionType::Simple => self.range_simple(start, end, columns),
             SelectionType::Block => self.range_block(start, end),
+            SelectionType::Semantic => Some(Self::range_semantic(term, start.point, end.point)),
+            SelectionType::Lines => Some(Self::range_lines(term, start.point, end.point)),
+        }
+    }
+
+    fn range_semantic<T>(term: &Term<T>, start: Point, end: Point) -> SelectionRange {
+        let start = term.semantic_search_left(start);
+        let end = term.semantic_search_right(end);
+
+        SelectionRange {
+            start,
+            end,
+            is_block: false,
+        }
+    }
+
+    fn range_lines<T>(term: &Term<T>, start: Point, end: Point) -> SelectionRange {
+        let start = term.line_search_left(start);
+        let end = term.line_search_right(end);
+
+        SelectionRange {
+            start,
+            end,
+            is_block: false,
         }
     }
 
@@ -139,6 +139,35 @@ pub struct Cell {
     pub extra: Option<Arc<CellExtra>>,
 }
 
+/// Attributes of a cell which affect its appearance.
+///
+/// Layout flags like line wrapping and wide character markers are not included, so runs of
+/// equally styled cells can be merged.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Attrs {
+    pub fg: Color,
+    pub bg: Color,
+    pub flags: Flags,
+}
+
+impl Attrs {
+    /// Foreground color to draw with.
+    ///
+    /// With `bold_is_bright`, bold text uses the bright variant of its named or basic indexed
+    /// color.
+    pub fn foreground(&self, bold_is_bright: bool) -> Color {
+        if !bold_is_bright || !self.flags.contains(Flags::BOLD) {
+            return self.fg;
+        }
+
+        match self.fg {
+            Color::Named(color) => Color::Named(color.to_bright()),
+            Color::Indexed(index @ 0..=7) => Color::Indexed(index + 8),
+            color => color,
+        }
+    }
+}
+
 impl Default for Cell {
     #[inline]
     fn default() -> Cell {
@@ -153,6 +182,21 @@ impl Default for Cell {
 }
 
 impl Cell {
+    /// Attributes for drawing this cell.
+    #[inline]
+    pub fn attrs(&self) -> Attrs {
+        let layout = Flags::WRAPLINE
+            | Flags::WIDE_CHAR
+            | Flags::WIDE_CHAR_SPACER
+            | Flags::LEADING_WIDE_CHAR_SPACER;
+
+        Attrs {
+            fg: self.fg,
+            bg: self.bg,
+            flags: self.flags - layout,
+        }
+    }
+
     /// Zerowidth characters stored in this cell.
     #[inline]
     pub fn zerowidth(&self) -> Option<&[char]> {
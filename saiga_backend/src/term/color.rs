@@ -18,12 +18,34 @@ pub const COUNT: usize = 269;
 /// | 259..267 | Dim colors        |
 /// | 267      | Bright foreground |
 /// | 268      | Dim background    |
+///
+/// Each color also carries an alpha channel, which is opaque unless an application set it.
 #[derive(Copy, Clone)]
-pub struct Colors([Option<Rgb>; COUNT]);
+pub struct Colors {
+    rgb: [Option<Rgb>; COUNT],
+    alpha: [u8; COUNT],
+}
+
+impl Colors {
+    /// Opacity of the color at `index`.
+    #[inline]
+    pub fn alpha(&self, index: usize) -> u8 {
+        self.alpha[index]
+    }
+
+    /// Set the opacity of the color at `index`.
+    #[inline]
+    pub fn set_alpha(&mut self, index: usize, alpha: u8) {
+        self.alpha[index] = alpha;
+    }
+}
 
 impl Default for Colors {
     fn default() -> Self {
-        Self([None; COUNT])
+        Self {
+            rgb: [None; COUNT],
+            alpha: [u8::MAX; COUNT],
+        }
     }
 }
 
@@ -32,14 +54,14 @@ impl Index<usize> for Colors {
 
     #[inline]
     fn index(&self, index: usize) -> &Self::Output {
-        &self.0[index]
+        &self.rgb[index]
     }
 }
 
 impl IndexMut<usize> for Colors {
     #[inline]
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        &mut self.0[index]
+        &mut self.rgb[index]
     }
 }
 
@@ -48,13 +70,13 @@ impl Index<NamedColor> for Colors {
 
     #[inline]
     fn index(&self, index: NamedColor) -> &Self::Output {
-        &self.0[index as usize]
+        &self.rgb[index as usize]
     }
 }
 
 impl IndexMut<NamedColor> for Colors {
     #[inline]
     fn index_mut(&mut self, index: NamedColor) -> &mut Self::Output {
-        &mut self.0[index as usize]
+        &mut self.rgb[index as usize]
     }
 }
@@ -1,6 +1,8 @@
 //! Exports the `Term` type which is a high-level API for the Grid.
 
+use std::fmt::Write;
 use std::ops::{Index, IndexMut, Range};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::{cmp, mem, ptr, slice, str};
 
@@ -11,15 +13,15 @@ use log::{debug, trace};
 use unicode_width::UnicodeWidthChar;
 
 use crate::event::{Event, EventListener};
-use crate::grid::{Dimensions, Grid, GridIterator, Scroll};
+use crate::grid::{BidirectionalIterator, Dimensions, Grid, GridIterator, Scroll};
 use crate::index::{self, Boundary, Column, Direction, Line, Point};
 use crate::selection::{Selection, SelectionRange, SelectionType};
 use crate::term::cell::{Cell, Flags, LineLength};
 use crate::term::color::Colors;
 use saiga_vte::ansi::handler::{
-    self, Attribute, Charset, CharsetIndex, Color, CursorShape, CursorStyle, Handler, Hyperlink,
-    KeyboardModes, KeyboardModesApplyBehavior, NamedColor, NamedMode, NamedPrivateMode,
-    PrivateMode, Rgb,
+    self, sgr_color, Attribute, Charset, CharsetIndex, Color, CursorShape, CursorStyle,
+    DeviceAttributes, Handler, Hyperlink, KeyboardModes, KeyboardModesApplyBehavior, NamedColor,
+    NamedMode, NamedPrivateMode, PrivateMode, Rectangle, Rgb, Rgba, StatusStringRequest,
 };
 
 pub mod cell;
@@ -66,6 +68,7 @@ bitflags! {
         const MOUSE_MODE              = 0b0000_0000_0010_0000_0100_1000;
         const UTF8_MOUSE              = 0b0000_0000_0100_0000_0000_0000;
         const ALTERNATE_SCROLL        = 0b0000_0000_1000_0000_0000_0000;
+        const REVERSE_VIDEO           = 0b0000_0001_0000_0000_0000_0000;
         const URGENCY_HINTS           = 0b0000_0010_0000_0000_0000_0000;
         const DISAMBIGUATE_ESC_CODES  = 0b0000_0100_0000_0000_0000_0000;
         const REPORT_EVENT_TYPES      = 0b0000_1000_0000_0000_0000_0000;
@@ -321,6 +324,9 @@ pub struct Term<T> {
     /// term is set.
     title_stack: Vec<Option<String>>,
 
+    /// Working directory last reported with OSC 7.
+    current_directory: Option<PathBuf>,
+
     /// The stack for the keyboard modes.
     keyboard_mode_stack: Vec<KeyboardModes>,
 
@@ -332,8 +338,14 @@ pub struct Term<T> {
 
     /// Config directly for the terminal.
     config: Config,
+
+    /// Callback observing every response written back to the pty.
+    response_tap: Option<ResponseTap>,
 }
 
+/// Callback invoked with each response the terminal writes back to the pty.
+pub type ResponseTap = Box<dyn FnMut(&str) + Send>;
+
 /// Configuration options for the [`Term`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Config {
@@ -353,6 +365,21 @@ pub struct Config {
 
     /// OSC52 support mode.
     pub osc52: Osc52,
+
+    /// Whether applications may switch to the alternate screen.
+    ///
+    /// When disabled, full-screen applications are rendered on the primary screen and
+    /// their output is kept in the scrollback history.
+    pub alternate_screen: bool,
+
+    /// Whether a full reset (RIS) discards colors set through OSC 4, 10, 11 and 12.
+    ///
+    /// Enabled by default to match xterm.
+    pub reset_colors_on_ris: bool,
+
+    /// Whether bold text is drawn with the bright variant of its named or basic indexed
+    /// foreground color.
+    pub bold_is_bright: bool,
 }
 
 impl Default for Config {
@@ -363,6 +390,9 @@ impl Default for Config {
             default_cursor_style: Default::default(),
             kitty_keyboard: Default::default(),
             osc52: Default::default(),
+            alternate_screen: true,
+            reset_colors_on_ris: true,
+            bold_is_bright: false,
         }
     }
 }
@@ -390,6 +420,11 @@ impl<T> Term<T> {
     where
         T: EventListener,
     {
+        // The alternate screen has no scrollback to show.
+        if self.mode.contains(TermMode::ALT_SCREEN) {
+            return;
+        }
+
         let old_display_offset = self.grid.display_offset();
         self.grid.scroll_display(scroll);
         self.event_proxy.send_event(Event::MouseCursorDirty);
@@ -401,8 +436,8 @@ impl<T> Term<T> {
     }
 
     pub fn new<D: Dimensions>(config: Config, dimensions: &D, event_proxy: T) -> Term<T> {
-        let num_cols = dimensions.columns();
-        let num_lines = dimensions.screen_lines();
+        let num_cols = cmp::max(dimensions.columns(), MIN_COLUMNS);
+        let num_lines = cmp::max(dimensions.screen_lines(), MIN_SCREEN_LINES);
 
         let history_size = config.scrolling_history;
         let grid = Grid::new(num_lines, num_cols, history_size);
@@ -429,8 +464,10 @@ impl<T> Term<T> {
             cursor_style: Default::default(),
             colors: color::Colors::default(),
             title_stack: Default::default(),
+            current_directory: None,
             is_focused: Default::default(),
             selection: Default::default(),
+            response_tap: None,
             title: Default::default(),
             mode: Default::default(),
         }
@@ -482,6 +519,18 @@ impl<T> Term<T> {
         self.damage.reset(self.columns());
     }
 
+    /// Check whether any output was processed since the last [`Term::reset_damage`] call.
+    ///
+    /// Frontends can use this to skip redrawing idle frames. Bytes buffered by a synchronized
+    /// update are not visible to the terminal yet, see [`Processor::sync_bytes_count`] for those.
+    ///
+    /// [`Processor::sync_bytes_count`]: saiga_vte::ansi::processor::Processor::sync_bytes_count
+    pub fn has_pending_output(&self) -> bool {
+        self.damage.full
+            || self.damage.last_cursor != self.grid.cursor.point
+            || self.damage.lines.iter().any(LineDamageBounds::is_damaged)
+    }
+
     #[inline]
     fn mark_fully_damaged(&mut self) {
         self.damage.full = true;
@@ -669,8 +718,9 @@ impl<T> Term<T> {
         let old_cols = self.columns();
         let old_lines = self.screen_lines();
 
-        let num_cols = size.columns();
-        let num_lines = size.screen_lines();
+        // Tiny or minimized windows can report zero cells, which the grid can't hold a cursor in.
+        let num_cols = cmp::max(size.columns(), MIN_COLUMNS);
+        let num_lines = cmp::max(size.screen_lines(), MIN_SCREEN_LINES);
 
         if old_cols == num_cols && old_lines == num_lines {
             debug!("Term::resize dimensions unchanged");
@@ -717,6 +767,10 @@ impl<T> Term<T> {
     }
 
     /// Swap primary and alternate screen buffer.
+    ///
+    /// Like xterm's mode 1049, entering the alternate screen carries the cursor over with its
+    /// position, SGR template and charsets. The primary screen keeps its own cursor, so leaving
+    /// the alternate screen restores the cursor state from before entering it.
     pub fn swap_alt(&mut self) {
         if !self.mode.contains(TermMode::ALT_SCREEN) {
             // Set alt screen cursor to the current primary screen cursor.
@@ -724,11 +778,29 @@ impl<T> Term<T> {
 
             // Drop information about the primary screens saved cursor.
             self.grid.saved_cursor = self.grid.cursor.clone();
+            self.grid.saved_cursor.origin = self.mode.contains(TermMode::ORIGIN);
 
             // Reset alternate screen contents.
             self.inactive_grid.reset_region(..);
         }
 
+        self.swap_grids();
+    }
+
+    /// Swap screen buffers the way modes 47 and 1047 do.
+    ///
+    /// The cursor is shared between both screens and the alternate screen keeps its content,
+    /// unless `clear` is set when leaving it.
+    fn swap_alt_shared_cursor(&mut self, clear: bool) {
+        if self.mode.contains(TermMode::ALT_SCREEN) && clear {
+            self.grid.reset_region(..);
+        }
+
+        self.inactive_grid.cursor = self.grid.cursor.clone();
+        self.swap_grids();
+    }
+
+    fn swap_grids(&mut self) {
         mem::swap(
             &mut self.keyboard_mode_stack,
             &mut self.inactive_keyboard_mode_stack,
@@ -825,6 +897,28 @@ impl<T> Term<T> {
         self.event_proxy.send_event(Event::Exit);
     }
 
+    /// Set a callback observing the responses written to the pty, e.g. for device attributes
+    /// or status reports.
+    ///
+    /// Responses which are formatted by the event listener, like color or clipboard queries,
+    /// are not observed.
+    pub fn set_response_tap(&mut self, tap: Option<ResponseTap>) {
+        self.response_tap = tap;
+    }
+
+    /// Write a response to the pty.
+    #[inline]
+    fn write_response(&mut self, text: String)
+    where
+        T: EventListener,
+    {
+        if let Some(tap) = &mut self.response_tap {
+            tap(&text);
+        }
+
+        self.event_proxy.send_event(Event::PtyWrite(text));
+    }
+
     /// Scroll display to point if it is outside of viewport.
     pub fn scroll_to_point(&mut self, point: Point)
     where
@@ -878,6 +972,124 @@ impl<T> Term<T> {
         &self.config.semantic_escape_chars
     }
 
+    /// Find the left end of the semantic block containing `point`.
+    #[must_use]
+    pub fn semantic_search_left(&self, point: Point) -> Point {
+        match self.inline_search_left(point, self.semantic_escape_chars()) {
+            // Step back over the escape character, skipping wide char spacers.
+            Ok(point) => {
+                let wide_spacer = Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER;
+                self.grid
+                    .iter_from(point)
+                    .find(|cell| !cell.flags.intersects(wide_spacer))
+                    .map_or(point, |cell| cell.point)
+            }
+            Err(point) => point,
+        }
+    }
+
+    /// Find the right end of the semantic block containing `point`.
+    #[must_use]
+    pub fn semantic_search_right(&self, point: Point) -> Point {
+        match self.inline_search_right(point, self.semantic_escape_chars()) {
+            Ok(point) => self
+                .grid
+                .iter_from(point)
+                .prev()
+                .map_or(point, |cell| cell.point),
+            Err(point) => point,
+        }
+    }
+
+    /// Search the next character from `needles` to the left, without crossing line breaks.
+    ///
+    /// Returns the last searched point if no character matched.
+    pub fn inline_search_left(&self, mut point: Point, needles: &str) -> Result<Point, Point> {
+        point.line = cmp::max(point.line, self.topmost_line());
+
+        let mut iter = self.grid.iter_from(point);
+        let last_column = self.last_column();
+
+        let wide_spacer = Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER;
+        while let Some(cell) = iter.prev() {
+            if cell.point.column == last_column && !cell.flags.contains(Flags::WRAPLINE) {
+                break;
+            }
+
+            point = cell.point;
+
+            if !cell.flags.intersects(wide_spacer) && needles.contains(cell.c) {
+                return Ok(point);
+            }
+        }
+
+        Err(point)
+    }
+
+    /// Search the next character from `needles` to the right, without crossing line breaks.
+    ///
+    /// Returns the last searched point if no character matched.
+    pub fn inline_search_right(&self, mut point: Point, needles: &str) -> Result<Point, Point> {
+        point.line = cmp::max(point.line, self.topmost_line());
+
+        let wide_spacer = Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER;
+        let last_column = self.last_column();
+
+        // Immediately stop if the start point is on a line break.
+        if point.column == last_column && !self.grid[point].flags.contains(Flags::WRAPLINE) {
+            return Err(point);
+        }
+
+        for cell in self.grid.iter_from(point) {
+            point = cell.point;
+
+            if !cell.flags.intersects(wide_spacer) && needles.contains(cell.c) {
+                return Ok(point);
+            }
+
+            if point.column == last_column && !cell.flags.contains(Flags::WRAPLINE) {
+                break;
+            }
+        }
+
+        Err(point)
+    }
+
+    /// Find the beginning of the line containing `point`, following line wraps.
+    pub fn line_search_left(&self, mut point: Point) -> Point {
+        while point.line > self.topmost_line()
+            && self.grid[point.line - 1i32][self.last_column()]
+                .flags
+                .contains(Flags::WRAPLINE)
+        {
+            point.line -= 1;
+        }
+
+        point.column = Column(0);
+
+        point
+    }
+
+    /// Find the end of the line containing `point`, following line wraps.
+    pub fn line_search_right(&self, mut point: Point) -> Point {
+        while point.line + 1 < self.screen_lines() as i32
+            && self.grid[point.line][self.last_column()]
+                .flags
+                .contains(Flags::WRAPLINE)
+        {
+            point.line += 1;
+        }
+
+        point.column = self.last_column();
+
+        point
+    }
+
+    /// Cursor rendering information for the current terminal state.
+    pub fn renderable_cursor(&self) -> RenderableCursor {
+        RenderableCursor::new(self)
+    }
+
     /// Active terminal cursor style.
     ///
     /// While vi mode is active, this will automatically return the vi mode cursor style.
@@ -887,10 +1099,22 @@ impl<T> Term<T> {
             .unwrap_or(self.config.default_cursor_style)
     }
 
+    /// Whether bold text is drawn with bright colors, see [`Config::bold_is_bright`].
+    pub fn bold_is_bright(&self) -> bool {
+        self.config.bold_is_bright
+    }
+
     pub fn colors(&self) -> &Colors {
         &self.colors
     }
 
+    /// Working directory last reported by the shell with OSC 7, for new tabs to start in.
+    ///
+    /// Only `file://` URIs pointing at this machine are accepted, with their path decoded.
+    pub fn current_directory(&self) -> Option<&Path> {
+        self.current_directory.as_deref()
+    }
+
     /// Insert a linebreak at the current cursor position.
     #[inline]
     fn wrapline(&mut self)
@@ -926,6 +1150,18 @@ impl<T> Term<T> {
         let flags = self.grid.cursor.template.flags;
         let extra = self.grid.cursor.template.extra.clone();
 
+        // Rewriting identical content, like a progress bar redrawing itself, adds no damage.
+        let point = self.grid.cursor.point;
+        let cell = &self.grid[point.line][point.column];
+        let unchanged = cell.c == c
+            && cell.fg == fg
+            && cell.bg == bg
+            && cell.flags == flags
+            && cell.extra == extra;
+        if unchanged {
+            return;
+        }
+
         let mut cursor_cell = self.grid.cursor_cell();
 
         // Clear all related cells when overwriting a fullwidth cell.
@@ -960,6 +1196,32 @@ impl<T> Term<T> {
         cursor_cell.bg = bg;
         cursor_cell.flags = flags;
         cursor_cell.extra = extra;
+
+        self.damage_cursor();
+    }
+
+    /// Visible lines and columns covered by a rectangular area operation.
+    ///
+    /// The area is relative to the scrolling region in origin mode and clamped to the screen.
+    /// Returns `None` when the area is empty.
+    fn rectangle_bounds(&self, area: Rectangle) -> Option<(Range<Line>, Range<Column>)> {
+        let (offset, end_line) = if self.mode.contains(TermMode::ORIGIN) {
+            (self.scroll_region.start, self.scroll_region.end)
+        } else {
+            (Line(0), Line(self.screen_lines() as i32))
+        };
+
+        let top = cmp::min(offset + (area.top.max(1) - 1), end_line);
+        let bottom = match area.bottom {
+            Some(bottom) => cmp::min(offset + bottom, end_line),
+            None => end_line,
+        };
+
+        let columns = self.columns();
+        let left = cmp::min(area.left.max(1) - 1, columns);
+        let right = area.right.map_or(columns, |right| cmp::min(right, columns));
+
+        (top < bottom && left < right).then_some((top..bottom, Column(left)..Column(right)))
     }
 
     #[inline]
@@ -1103,6 +1365,23 @@ impl<T: EventListener> Handler for Term<T> {
         }
     }
 
+    #[inline]
+    fn repeat(&mut self, c: char, count: usize) {
+        trace!("Repeating {:?} {} times", c, count);
+
+        // Without autowrap, repetitions past the margin keep overwriting the last cell, so a line
+        // worth of them is all that can change the screen.
+        let count = if self.mode.contains(TermMode::LINE_WRAP) {
+            count
+        } else {
+            cmp::min(count, self.columns())
+        };
+
+        for _ in 0..count {
+            self.input(c);
+        }
+    }
+
     #[inline]
     fn decaln(&mut self) {
         trace!("Decalnning");
@@ -1115,6 +1394,11 @@ impl<T: EventListener> Handler for Term<T> {
             }
         }
 
+        // Reset the margins and home the cursor, ignoring origin mode.
+        self.scroll_region = Line(0)..Line(self.screen_lines() as i32);
+        self.grid.cursor.point = Point::new(Line(0), Column(0));
+        self.grid.cursor.input_needs_wrap = false;
+
         self.mark_fully_damaged();
     }
 
@@ -1176,6 +1460,7 @@ impl<T: EventListener> Handler for Term<T> {
         for cell in &mut row[source.0..destination] {
             *cell = bg.into();
         }
+        self.grid.cursor.input_needs_wrap = false;
     }
 
     #[inline]
@@ -1199,7 +1484,8 @@ impl<T: EventListener> Handler for Term<T> {
     #[inline]
     fn move_forward(&mut self, cols: usize) {
         trace!("Moving forward: {}", cols);
-        let last_column = cmp::min(self.grid.cursor.point.column + cols, self.last_column());
+        let column = Column(self.grid.cursor.point.column.saturating_add(cols));
+        let last_column = cmp::min(column, self.last_column());
 
         let cursor_line = self.grid.cursor.point.line.0 as usize;
         self.damage
@@ -1223,25 +1509,33 @@ impl<T: EventListener> Handler for Term<T> {
     }
 
     #[inline]
-    fn identify_terminal(&mut self, intermediate: Option<char>) {
-        match intermediate {
-            None => {
+    fn identify_terminal(&mut self, attributes: DeviceAttributes) {
+        match attributes {
+            DeviceAttributes::Primary => {
                 trace!("Reporting primary device attributes");
 
-                let text = "\x1b[?6c".to_string();
-                self.event_proxy.send_event(Event::PtyWrite(text));
+                // VT220 conformance level (62) with ANSI color (22).
+                let text = "\x1b[?62;22c".to_string();
+                self.write_response(text);
             }
-            Some('>') => {
+            DeviceAttributes::Secondary => {
                 trace!("Reporting secondary device attributes");
 
                 let version = version_number(env!("CARGO_PKG_VERSION"));
-                let text = format!("\x1b[>0;{version};1c");
-                self.event_proxy.send_event(Event::PtyWrite(text));
+                let text = format!("\x1b[>0;{version};0c");
+                self.write_response(text);
             }
-            _ => debug!("Unsupported device attributes intermediate"),
         }
     }
 
+    #[inline]
+    fn identify_terminal_tertiary(&mut self) {
+        trace!("Reporting tertiary device attributes");
+
+        // DECRPTUI with an all-zero unit ID, like xterm.
+        self.write_response(String::from("\x1bP!|00000000\x1b\\"));
+    }
+
     #[inline]
     fn report_keyboard_mode(&mut self) {
         if !self.config.kitty_keyboard {
@@ -1255,7 +1549,7 @@ impl<T: EventListener> Handler for Term<T> {
             .unwrap_or(&KeyboardModes::NO_MODE)
             .bits();
         let text = format!("\x1b[?{current_mode}u");
-        self.event_proxy.send_event(Event::PtyWrite(text));
+        self.write_response(text);
     }
 
     #[inline]
@@ -1267,7 +1561,7 @@ impl<T: EventListener> Handler for Term<T> {
         trace!("Pushing `{mode:?}` keyboard mode into the stack");
 
         if self.keyboard_mode_stack.len() >= KEYBOARD_MODE_STACK_MAX_DEPTH {
-            let removed = self.title_stack.remove(0);
+            let removed = self.keyboard_mode_stack.remove(0);
             trace!(
                 "Removing '{:?}' from bottom of keyboard mode stack that exceeds its maximum depth",
                 removed
@@ -1315,17 +1609,31 @@ impl<T: EventListener> Handler for Term<T> {
         match arg {
             5 => {
                 let text = String::from("\x1b[0n");
-                self.event_proxy.send_event(Event::PtyWrite(text));
-            }
-            6 => {
-                let pos = self.grid.cursor.point;
-                let text = format!("\x1b[{};{}R", pos.line + 1, pos.column + 1);
-                self.event_proxy.send_event(Event::PtyWrite(text));
+                self.write_response(text);
             }
             _ => debug!("unknown device status query: {}", arg),
         };
     }
 
+    #[inline]
+    fn report_cursor_position(&mut self, extended: bool) {
+        trace!("Reporting cursor position, extended: {}", extended);
+
+        // In origin mode the position is relative to the scrolling region.
+        let mut line = self.grid.cursor.point.line;
+        if self.mode.contains(TermMode::ORIGIN) {
+            line -= self.scroll_region.start;
+        }
+        let column = self.grid.cursor.point.column;
+
+        let text = if extended {
+            format!("\x1b[?{};{};1R", line + 1, column + 1)
+        } else {
+            format!("\x1b[{};{}R", line + 1, column + 1)
+        };
+        self.write_response(text);
+    }
+
     #[inline]
     fn move_down_and_cr(&mut self, lines: usize) {
         trace!("Moving down and cr: {}", lines);
@@ -1392,11 +1700,8 @@ impl<T: EventListener> Handler for Term<T> {
     #[inline]
     fn carriage_return(&mut self) {
         trace!("Carriage return");
-        let new_col = 0;
-        let line = self.grid.cursor.point.line.0 as usize;
-        self.damage
-            .damage_line(line, new_col, self.grid.cursor.point.column.0);
-        self.grid.cursor.point.column = Column(new_col);
+        // The cells in between are unchanged, and `Term::damage` covers the old and new cursor.
+        self.grid.cursor.point.column = Column(0);
         self.grid.cursor.input_needs_wrap = false;
     }
 
@@ -1467,12 +1772,14 @@ impl<T: EventListener> Handler for Term<T> {
     fn scroll_up(&mut self, lines: usize) {
         let origin = self.scroll_region.start;
         self.scroll_up_relative(origin, lines);
+        self.grid.cursor.input_needs_wrap = false;
     }
 
     #[inline]
     fn scroll_down(&mut self, lines: usize) {
         let origin = self.scroll_region.start;
         self.scroll_down_relative(origin, lines);
+        self.grid.cursor.input_needs_wrap = false;
     }
 
     #[inline]
@@ -1483,6 +1790,7 @@ impl<T: EventListener> Handler for Term<T> {
         if self.scroll_region.contains(&origin) {
             self.scroll_down_relative(origin, lines);
         }
+        self.grid.cursor.input_needs_wrap = false;
     }
 
     #[inline]
@@ -1495,6 +1803,7 @@ impl<T: EventListener> Handler for Term<T> {
         if lines > 0 && self.scroll_region.contains(&origin) {
             self.scroll_up_relative(origin, lines);
         }
+        self.grid.cursor.input_needs_wrap = false;
     }
 
     #[inline]
@@ -1518,6 +1827,61 @@ impl<T: EventListener> Handler for Term<T> {
         for cell in &mut row[start..end] {
             *cell = bg.into();
         }
+        self.grid.cursor.input_needs_wrap = false;
+    }
+
+    #[inline]
+    fn fill_rectangle(&mut self, c: char, area: Rectangle) {
+        trace!("Filling rectangle with {c:?}: {area:?}");
+
+        let Some((lines, columns)) = self.rectangle_bounds(area) else {
+            return;
+        };
+
+        let mut template = self.grid.cursor.template.clone();
+        template.c = c;
+
+        for line in lines.start.0..lines.end.0 {
+            let line = Line(line);
+
+            // Clear fullwidth characters cut in half by the edges of the area.
+            if columns.start > 0 {
+                let cell = &mut self.grid[line][columns.start - 1];
+                if cell.flags.contains(Flags::WIDE_CHAR) {
+                    cell.clear_wide();
+                }
+            }
+            if columns.end < self.columns() {
+                let cell = &mut self.grid[line][columns.end];
+                if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    cell.flags.remove(Flags::WIDE_CHAR_SPACER);
+                    cell.c = ' ';
+                }
+            }
+
+            self.damage
+                .damage_line(line.0 as usize, 0, self.columns() - 1);
+        }
+
+        self.grid.fill_region(lines, columns, &template);
+    }
+
+    #[inline]
+    fn report_rectangle_checksum(&mut self, id: usize, area: Rectangle) {
+        trace!("Reporting checksum {id} of rectangle {area:?}");
+
+        // DEC terminals sum up the characters and some of their attributes, then report the
+        // negated 16-bit sum.
+        let mut checksum = 0u16;
+        if let Some((lines, columns)) = self.rectangle_bounds(area) {
+            for line in lines.start.0..lines.end.0 {
+                for cell in &self.grid[Line(line)][columns.clone()] {
+                    checksum = checksum.wrapping_add(cell_checksum(cell));
+                }
+            }
+        }
+
+        self.write_response(format!("\x1bP{id}!~{:04X}\x1b\\", checksum.wrapping_neg()));
     }
 
     #[inline]
@@ -1548,6 +1912,7 @@ impl<T: EventListener> Handler for Term<T> {
         for cell in &mut row[end..] {
             *cell = bg.into();
         }
+        self.grid.cursor.input_needs_wrap = false;
     }
 
     #[inline]
@@ -1557,15 +1922,12 @@ impl<T: EventListener> Handler for Term<T> {
 
         let old_col = self.grid.cursor.point.column.0;
         for _ in 0..count {
-            let mut col = self.grid.cursor.point.column;
-            for i in (0..(col.0)).rev() {
-                if self.tabs[index::Column(i)] {
-                    col = index::Column(i);
-                    break;
-                }
-            }
-            self.grid.cursor.point.column = col;
+            // Without a stop to the left the cursor stops at the first column.
+            let col = self.grid.cursor.point.column.0;
+            let stop = (0..col).rev().find(|&i| self.tabs[index::Column(i)]);
+            self.grid.cursor.point.column = index::Column(stop.unwrap_or(0));
         }
+        self.grid.cursor.input_needs_wrap = false;
 
         let line = self.grid.cursor.point.line.0 as usize;
         self.damage
@@ -1574,7 +1936,25 @@ impl<T: EventListener> Handler for Term<T> {
 
     #[inline]
     fn move_forward_tabs(&mut self, count: u16) {
-        trace!("[unimplemented] Moving forward {} tabs", count);
+        trace!("Moving forward {} tabs", count);
+        self.damage_cursor();
+
+        let old_col = self.grid.cursor.point.column.0;
+        for _ in 0..count {
+            let mut col = self.grid.cursor.point.column;
+            for i in (col.0 + 1)..self.columns() {
+                col = index::Column(i);
+                if self.tabs[col] {
+                    break;
+                }
+            }
+            self.grid.cursor.point.column = col;
+        }
+        self.grid.cursor.input_needs_wrap = false;
+
+        let line = self.grid.cursor.point.line.0 as usize;
+        self.damage
+            .damage_line(line, old_col, self.grid.cursor.point.column.0);
     }
 
     #[inline]
@@ -1582,6 +1962,7 @@ impl<T: EventListener> Handler for Term<T> {
         trace!("Saving cursor position");
 
         self.grid.saved_cursor = self.grid.cursor.clone();
+        self.grid.saved_cursor.origin = self.mode.contains(TermMode::ORIGIN);
     }
 
     #[inline]
@@ -1590,6 +1971,7 @@ impl<T: EventListener> Handler for Term<T> {
 
         self.damage_cursor();
         self.grid.cursor = self.grid.saved_cursor.clone();
+        self.mode.set(TermMode::ORIGIN, self.grid.cursor.origin);
         self.damage_cursor();
     }
 
@@ -1602,6 +1984,7 @@ impl<T: EventListener> Handler for Term<T> {
         let point = cursor.point;
 
         let (left, right) = match mode {
+            // Keep the last character and the pending wrap, so the next print still wraps.
             handler::LineClearMode::Right if cursor.input_needs_wrap => return,
             handler::LineClearMode::Right => (point.column, Column(self.columns())),
             handler::LineClearMode::Left => (Column(0), point.column + 1),
@@ -1615,6 +1998,7 @@ impl<T: EventListener> Handler for Term<T> {
         for cell in &mut row[left..right] {
             *cell = bg.into();
         }
+        self.grid.cursor.input_needs_wrap = false;
 
         let range = self.grid.cursor.point.line..=self.grid.cursor.point.line;
         self.selection = self.selection.take().filter(|s| !s.intersects_range(range));
@@ -1623,14 +2007,23 @@ impl<T: EventListener> Handler for Term<T> {
     /// Set the indexed color value.
     #[inline]
     fn set_color(&mut self, index: usize, color: Rgb) {
+        self.set_color_rgba(index, color.into());
+    }
+
+    /// Set the indexed color value with transparency.
+    #[inline]
+    fn set_color_rgba(&mut self, index: usize, color: Rgba) {
         trace!("Setting color[{}] = {:?}", index, color);
 
         // Damage terminal if the color changed and it's not the cursor.
-        if index != NamedColor::Cursor as usize && self.colors[index] != Some(color) {
+        let changed =
+            self.colors[index] != Some(color.rgb()) || self.colors.alpha(index) != color.a;
+        if index != NamedColor::Cursor as usize && changed {
             self.mark_fully_damaged();
         }
 
-        self.colors[index] = Some(color);
+        self.colors[index] = Some(color.rgb());
+        self.colors.set_alpha(index, color.a);
     }
 
     /// Respond to a color query escape sequence.
@@ -1645,12 +2038,7 @@ impl<T: EventListener> Handler for Term<T> {
         let terminator = terminator.to_owned();
         self.event_proxy.send_event(Event::ColorRequest(
             index,
-            Arc::new(move |color| {
-                format!(
-                    "\x1b]{};rgb:{1:02x}{1:02x}/{2:02x}{2:02x}/{3:02x}{3:02x}{4}",
-                    prefix, color.r, color.g, color.b, terminator
-                )
-            }),
+            Arc::new(move |color| color_response(&prefix, color, &terminator)),
         ));
     }
 
@@ -1665,6 +2053,7 @@ impl<T: EventListener> Handler for Term<T> {
         }
 
         self.colors[index] = None;
+        self.colors.set_alpha(index, u8::MAX);
     }
 
     /// Store data into clipboard.
@@ -1774,6 +2163,10 @@ impl<T: EventListener> Handler for Term<T> {
             handler::ScreenClearMode::Saved => (),
         }
 
+        if mode != handler::ScreenClearMode::Saved {
+            self.grid.cursor.input_needs_wrap = false;
+        }
+
         self.mark_fully_damaged();
     }
 
@@ -1790,7 +2183,7 @@ impl<T: EventListener> Handler for Term<T> {
         }
     }
 
-    /// Reset all important fields in the term struct.
+    /// Reset the terminal to its power-on state (RIS), keeping its dimensions.
     #[inline]
     fn reset_state(&mut self) {
         if self.mode.contains(TermMode::ALT_SCREEN) {
@@ -1808,8 +2201,15 @@ impl<T: EventListener> Handler for Term<T> {
         self.keyboard_mode_stack = Default::default();
         self.inactive_keyboard_mode_stack = Default::default();
 
-        self.mode.insert(TermMode::default());
+        if self.config.reset_colors_on_ris {
+            self.colors = Default::default();
+        }
+
+        // Drop every mode set by the application, not just restore the default ones.
+        self.mode = TermMode::default();
 
+        self.event_proxy.send_event(Event::ResetTitle);
+        self.event_proxy.send_event(Event::MouseCursorDirty);
         self.event_proxy.send_event(Event::CursorBlinkingChange);
         self.mark_fully_damaged();
     }
@@ -1904,10 +2304,15 @@ impl<T: EventListener> Handler for Term<T> {
         match mode {
             NamedPrivateMode::UrgencyHints => self.mode.insert(TermMode::URGENCY_HINTS),
             NamedPrivateMode::SwapScreenAndSetRestoreCursor => {
-                if !self.mode.contains(TermMode::ALT_SCREEN) {
+                if self.config.alternate_screen && !self.mode.contains(TermMode::ALT_SCREEN) {
                     self.swap_alt();
                 }
             }
+            NamedPrivateMode::SwapScreen | NamedPrivateMode::SwapScreenAndClear => {
+                if self.config.alternate_screen && !self.mode.contains(TermMode::ALT_SCREEN) {
+                    self.swap_alt_shared_cursor(false);
+                }
+            }
             NamedPrivateMode::ShowCursor => self.mode.insert(TermMode::SHOW_CURSOR),
             NamedPrivateMode::CursorKeys => self.mode.insert(TermMode::APP_CURSOR),
             // Mouse protocols are mutually exclusive.
@@ -1940,6 +2345,10 @@ impl<T: EventListener> Handler for Term<T> {
             NamedPrivateMode::AlternateScroll => self.mode.insert(TermMode::ALTERNATE_SCROLL),
             NamedPrivateMode::LineWrap => self.mode.insert(TermMode::LINE_WRAP),
             NamedPrivateMode::Origin => self.mode.insert(TermMode::ORIGIN),
+            NamedPrivateMode::ReverseVideo => {
+                self.mode.insert(TermMode::REVERSE_VIDEO);
+                self.mark_fully_damaged();
+            }
             NamedPrivateMode::ColumnMode => self.deccolm(),
             NamedPrivateMode::BlinkingCursor => {
                 let style = self
@@ -1966,10 +2375,16 @@ impl<T: EventListener> Handler for Term<T> {
         match mode {
             NamedPrivateMode::UrgencyHints => self.mode.remove(TermMode::URGENCY_HINTS),
             NamedPrivateMode::SwapScreenAndSetRestoreCursor => {
-                if self.mode.contains(TermMode::ALT_SCREEN) {
+                if self.config.alternate_screen && self.mode.contains(TermMode::ALT_SCREEN) {
                     self.swap_alt();
                 }
             }
+            NamedPrivateMode::SwapScreen | NamedPrivateMode::SwapScreenAndClear => {
+                if self.config.alternate_screen && self.mode.contains(TermMode::ALT_SCREEN) {
+                    let clear = mode == NamedPrivateMode::SwapScreenAndClear;
+                    self.swap_alt_shared_cursor(clear);
+                }
+            }
             NamedPrivateMode::ShowCursor => self.mode.remove(TermMode::SHOW_CURSOR),
             NamedPrivateMode::CursorKeys => self.mode.remove(TermMode::APP_CURSOR),
             NamedPrivateMode::ReportMouseClicks => {
@@ -1991,6 +2406,10 @@ impl<T: EventListener> Handler for Term<T> {
             NamedPrivateMode::AlternateScroll => self.mode.remove(TermMode::ALTERNATE_SCROLL),
             NamedPrivateMode::LineWrap => self.mode.remove(TermMode::LINE_WRAP),
             NamedPrivateMode::Origin => self.mode.remove(TermMode::ORIGIN),
+            NamedPrivateMode::ReverseVideo => {
+                self.mode.remove(TermMode::REVERSE_VIDEO);
+                self.mark_fully_damaged();
+            }
             NamedPrivateMode::ColumnMode => self.deccolm(),
             NamedPrivateMode::BlinkingCursor => {
                 let style = self
@@ -2010,6 +2429,9 @@ impl<T: EventListener> Handler for Term<T> {
             PrivateMode::Named(mode) => match mode {
                 NamedPrivateMode::CursorKeys => self.mode.contains(TermMode::APP_CURSOR).into(),
                 NamedPrivateMode::Origin => self.mode.contains(TermMode::ORIGIN).into(),
+                NamedPrivateMode::ReverseVideo => {
+                    self.mode.contains(TermMode::REVERSE_VIDEO).into()
+                }
                 NamedPrivateMode::LineWrap => self.mode.contains(TermMode::LINE_WRAP).into(),
                 NamedPrivateMode::BlinkingCursor => {
                     let style = self
@@ -2038,7 +2460,16 @@ impl<T: EventListener> Handler for Term<T> {
                 NamedPrivateMode::UrgencyHints => {
                     self.mode.contains(TermMode::URGENCY_HINTS).into()
                 }
-                NamedPrivateMode::SwapScreenAndSetRestoreCursor => {
+                NamedPrivateMode::SwapScreenAndSetRestoreCursor
+                | NamedPrivateMode::SwapScreen
+                | NamedPrivateMode::SwapScreenAndClear
+                    if !self.config.alternate_screen =>
+                {
+                    ModeState::NotSupported
+                }
+                NamedPrivateMode::SwapScreenAndSetRestoreCursor
+                | NamedPrivateMode::SwapScreen
+                | NamedPrivateMode::SwapScreenAndClear => {
                     self.mode.contains(TermMode::ALT_SCREEN).into()
                 }
                 NamedPrivateMode::BracketedPaste => {
@@ -2050,11 +2481,7 @@ impl<T: EventListener> Handler for Term<T> {
             PrivateMode::Unknown(_) => ModeState::NotSupported,
         };
 
-        self.event_proxy.send_event(Event::PtyWrite(format!(
-            "\x1b[?{};{}$y",
-            mode.raw(),
-            state as u8,
-        )));
+        self.write_response(format!("\x1b[?{};{}$y", mode.raw(), state as u8,));
     }
 
     #[inline]
@@ -2107,17 +2534,59 @@ impl<T: EventListener> Handler for Term<T> {
             handler::Mode::Unknown(_) => ModeState::NotSupported,
         };
 
-        self.event_proxy.send_event(Event::PtyWrite(format!(
-            "\x1b[{};{}$y",
-            mode.raw(),
-            state as u8,
-        )));
+        self.write_response(format!("\x1b[{};{}$y", mode.raw(), state as u8,));
+    }
+
+    #[inline]
+    fn report_status_string(&mut self, request: StatusStringRequest) {
+        trace!("Reporting status string {request:?}");
+        let setting = match request {
+            StatusStringRequest::Sgr => format!("{}m", sgr_parameters(&self.grid.cursor.template)),
+            StatusStringRequest::ScrollingRegion => {
+                let region = &self.scroll_region;
+                format!("{};{}r", region.start + 1, region.end)
+            }
+            StatusStringRequest::CursorStyle => {
+                let style = self.cursor_style();
+                let shape = match style.shape {
+                    CursorShape::Underline => 3,
+                    CursorShape::Beam => 5,
+                    CursorShape::Block | CursorShape::HollowBlock | CursorShape::Hidden => 1,
+                };
+                format!("{} q", shape + !style.blinking as u8)
+            }
+            StatusStringRequest::Unknown(request) => {
+                debug!(
+                    "Unknown status string request: {:?}",
+                    String::from_utf8_lossy(&request)
+                );
+                self.write_response(String::from("\x1bP0$r\x1b\\"));
+                return;
+            }
+        };
+
+        self.write_response(format!("\x1bP1$r{setting}\x1b\\"));
+    }
+
+    #[inline]
+    fn report_termcap(&mut self, names: &[String]) {
+        for name in names {
+            trace!("Reporting termcap {name:?}");
+            let text = match termcap(name) {
+                Some(Some(value)) => format!("\x1bP1+r{}={}\x1b\\", hex(name), hex(value)),
+                Some(None) => format!("\x1bP1+r{}\x1b\\", hex(name)),
+                None => String::from("\x1bP0+r\x1b\\"),
+            };
+            self.write_response(text);
+        }
     }
 
     #[inline]
     fn set_scrolling_region(&mut self, top: usize, bottom: Option<usize>) {
-        // Fallback to the last line as default.
-        let bottom = bottom.unwrap_or_else(|| self.screen_lines());
+        // Fallback to the last line as default and clamp the margins to the screen.
+        let screen_lines = self.screen_lines();
+        let bottom = cmp::min(bottom.unwrap_or(screen_lines), screen_lines);
+        let top = cmp::max(top, 1);
 
         if top >= bottom {
             debug!("Invalid scrolling region: ({};{})", top, bottom);
@@ -2133,9 +2602,7 @@ impl<T: EventListener> Handler for Term<T> {
 
         trace!("Setting scrolling region: ({};{})", start, end);
 
-        let screen_lines = Line(self.screen_lines() as i32);
-        self.scroll_region.start = cmp::min(start, screen_lines);
-        self.scroll_region.end = cmp::min(end, screen_lines);
+        self.scroll_region = start..end;
         self.goto(0, 0);
     }
 
@@ -2196,6 +2663,12 @@ impl<T: EventListener> Handler for Term<T> {
         self.event_proxy.send_event(title_event);
     }
 
+    #[inline]
+    fn set_current_directory(&mut self, path: Option<PathBuf>) {
+        trace!("Setting current directory to {:?}", path);
+        self.current_directory = path;
+    }
+
     #[inline]
     fn push_title(&mut self) {
         trace!("Pushing '{:?}' onto title stack", self.title);
@@ -2221,6 +2694,23 @@ impl<T: EventListener> Handler for Term<T> {
         }
     }
 
+    #[inline]
+    fn report_window_state(&mut self) {
+        self.event_proxy
+            .send_event(Event::WindowStateRequest(Arc::new(|iconified| {
+                let state = if iconified { 2 } else { 1 };
+                format!("\x1b[{state}t")
+            })));
+    }
+
+    #[inline]
+    fn report_window_position(&mut self) {
+        self.event_proxy
+            .send_event(Event::WindowPositionRequest(Arc::new(|x, y| {
+                format!("\x1b[3;{x};{y}t")
+            })));
+    }
+
     #[inline]
     fn text_area_size_pixels(&mut self) {
         self.event_proxy
@@ -2234,28 +2724,118 @@ impl<T: EventListener> Handler for Term<T> {
     #[inline]
     fn text_area_size_chars(&mut self) {
         let text = format!("\x1b[8;{};{}t", self.screen_lines(), self.columns());
-        self.event_proxy.send_event(Event::PtyWrite(text));
+        self.write_response(text);
     }
 }
 
-/// The state of the [`Mode`] and [`PrivateMode`].
-#[repr(u8)]
-#[derive(Debug, Clone, Copy)]
-enum ModeState {
-    /// The mode is not supported.
-    NotSupported = 0,
-    /// The mode is currently set.
-    Set = 1,
-    /// The mode is currently not set.
-    Reset = 2,
+/// Format the reply to an OSC color query, e.g. `OSC 4 ; index ; rgb:RRRR/GGGG/BBBB ST`.
+fn color_response(prefix: &str, color: Rgb, terminator: &str) -> String {
+    format!(
+        "\x1b]{};rgb:{1:02x}{1:02x}/{2:02x}{2:02x}/{3:02x}{3:02x}{4}",
+        prefix, color.r, color.g, color.b, terminator
+    )
 }
 
-impl From<bool> for ModeState {
-    fn from(value: bool) -> Self {
-        if value {
-            Self::Set
-        } else {
-            Self::Reset
+/// Terminfo capabilities reported through XTGETTCAP.
+///
+/// Boolean capabilities have no value.
+fn termcap(name: &str) -> Option<Option<&'static str>> {
+    let value = match name {
+        "Tc" => None,
+        "RGB" => Some("8/8/8"),
+        "Co" | "colors" => Some("256"),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Hex encode a string, as used by XTGETTCAP.
+fn hex(text: &str) -> String {
+    text.bytes().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02X}");
+        hex
+    })
+}
+
+/// Contribution of a cell to a DECRQCRA checksum.
+fn cell_checksum(cell: &Cell) -> u16 {
+    // The spacer is covered by the wide character in front of it.
+    if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+        return 0;
+    }
+
+    let mut checksum = cell.c as u32 as u16;
+    if cell.flags.intersects(Flags::ALL_UNDERLINES) {
+        checksum = checksum.wrapping_add(0x10);
+    }
+    if cell.flags.contains(Flags::INVERSE) {
+        checksum = checksum.wrapping_add(0x20);
+    }
+    if cell.flags.contains(Flags::BOLD) {
+        checksum = checksum.wrapping_add(0x80);
+    }
+
+    checksum
+}
+
+/// SGR parameters reproducing the attributes of a cell.
+fn sgr_parameters(cell: &Cell) -> String {
+    let mut sgr = String::from("0");
+
+    let flags = [
+        (Flags::BOLD, "1"),
+        (Flags::DIM, "2"),
+        (Flags::ITALIC, "3"),
+        (Flags::UNDERLINE, "4"),
+        (Flags::DOUBLE_UNDERLINE, "4:2"),
+        (Flags::UNDERCURL, "4:3"),
+        (Flags::DOTTED_UNDERLINE, "4:4"),
+        (Flags::DASHED_UNDERLINE, "4:5"),
+        (Flags::INVERSE, "7"),
+        (Flags::HIDDEN, "8"),
+        (Flags::STRIKEOUT, "9"),
+    ];
+    for (flag, param) in flags {
+        if cell.flags.contains(flag) {
+            sgr.push(';');
+            sgr.push_str(param);
+        }
+    }
+
+    // Default colors are covered by the reset.
+    let colors = [
+        sgr_color(cell.fg, Some(30), 38),
+        sgr_color(cell.bg, Some(40), 48),
+        cell.underline_color()
+            .and_then(|color| sgr_color(color, None, 58)),
+    ];
+    for param in colors.into_iter().flatten() {
+        sgr.push(';');
+        sgr.push_str(&param);
+    }
+
+    sgr
+}
+
+/// The state of the [`Mode`] and [`PrivateMode`].
+#[repr(u8)]
+#[derive(Debug, Clone, Copy)]
+enum ModeState {
+    /// The mode is not supported.
+    NotSupported = 0,
+    /// The mode is currently set.
+    Set = 1,
+    /// The mode is currently not set.
+    Reset = 2,
+}
+
+impl From<bool> for ModeState {
+    fn from(value: bool) -> Self {
+        if value {
+            Self::Set
+        } else {
+            Self::Reset
         }
     }
 }
@@ -2338,6 +2918,19 @@ impl IndexMut<Column> for TabStops {
 pub struct RenderableCursor {
     pub shape: CursorShape,
     pub point: Point,
+    pub blinking: bool,
+
+    /// Whether the cursor is on top of a wide character.
+    pub is_wide: bool,
+}
+
+/// Area covered by the cursor, relative to the origin of its cell.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CursorRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
 }
 
 impl RenderableCursor {
@@ -2347,15 +2940,68 @@ impl RenderableCursor {
         if term.grid[point].flags.contains(Flags::WIDE_CHAR_SPACER) {
             point.column -= 1;
         }
+        let is_wide = term.grid[point].flags.contains(Flags::WIDE_CHAR);
 
         // Cursor shape.
+        let style = term.cursor_style();
         let shape = if term.mode().contains(TermMode::SHOW_CURSOR) {
+            style.shape
+        } else {
             CursorShape::Hidden
+        };
+
+        Self {
+            shape,
+            point,
+            blinking: style.blinking,
+            is_wide,
+        }
+    }
+
+    /// Whether the cursor should be drawn in the current blink phase.
+    pub fn is_visible(&self, blink_phase: bool) -> bool {
+        self.shape != CursorShape::Hidden && (!self.blinking || blink_phase)
+    }
+
+    /// Whether the cell under the cursor should be drawn with its colors swapped.
+    pub fn inverts_cell(&self) -> bool {
+        self.shape == CursorShape::Block
+    }
+
+    /// Area to fill with the cursor color for the given cell size.
+    ///
+    /// Hollow blocks return the outer bounds of the cursor, which should be stroked instead.
+    pub fn rect(&self, cell_width: f32, cell_height: f32) -> Option<CursorRect> {
+        let width = if self.is_wide {
+            cell_width * 2.
         } else {
-            term.cursor_style().shape
+            cell_width
+        };
+        let thickness = (cell_height * 0.1).round().max(1.);
+
+        let rect = match self.shape {
+            CursorShape::Block | CursorShape::HollowBlock => CursorRect {
+                x: 0.,
+                y: 0.,
+                width,
+                height: cell_height,
+            },
+            CursorShape::Underline => CursorRect {
+                x: 0.,
+                y: cell_height - thickness,
+                width,
+                height: thickness,
+            },
+            CursorShape::Beam => CursorRect {
+                x: 0.,
+                y: 0.,
+                width: cell_width * 0.2,
+                height: cell_height,
+            },
+            CursorShape::Hidden => return None,
         };
 
-        Self { shape, point }
+        Some(rect)
     }
 }
 
@@ -2491,12 +3137,14 @@ mod tests {
     use super::*;
 
     use crate::event::VoidListener;
-    use crate::grid::Scroll;
+    use crate::grid::{Cursor, Scroll};
     use crate::index::{Column, Point, Side};
     use crate::selection::{Selection, SelectionType};
     use crate::term::cell::Flags;
     use crate::term::test::TermSize;
     use saiga_vte::ansi::handler::{self as ansi, Charset, CharsetIndex, Handler};
+    use saiga_vte::ansi::processor::Processor;
+    use std::sync::Mutex;
 
     #[test]
     fn scroll_display_page_up() {
@@ -2687,6 +3335,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn semantic_selection_works() {
+        let size = TermSize::new(10, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"ls (foo)\r\nabcdefghijkl bar");
+
+        // A word inside brackets.
+        term.selection = Some(Selection::new(
+            SelectionType::Semantic,
+            Point::new(Line(0), Column(5)),
+            Side::Left,
+        ));
+        assert_eq!(term.selection_to_string(), Some(String::from("foo")));
+
+        // Extending the selection expands the end to the next word boundary.
+        if let Some(s) = term.selection.as_mut() {
+            s.update(Point::new(Line(1), Column(1)), Side::Right);
+        }
+        assert_eq!(
+            term.selection_to_string(),
+            Some(String::from("foo)\nabcdefghijkl"))
+        );
+
+        // Words continue across wrapped lines.
+        term.selection = Some(Selection::new(
+            SelectionType::Semantic,
+            Point::new(Line(2), Column(0)),
+            Side::Left,
+        ));
+        assert_eq!(
+            term.selection_to_string(),
+            Some(String::from("abcdefghijkl"))
+        );
+    }
+
+    #[test]
+    fn line_selection_works() {
+        let size = TermSize::new(5, 4);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"abc\r\ndefghij\r\nk");
+
+        term.selection = Some(Selection::new(
+            SelectionType::Lines,
+            Point::new(Line(0), Column(1)),
+            Side::Left,
+        ));
+        assert_eq!(term.selection_to_string(), Some(String::from("abc")));
+
+        // Wrapped lines are selected as a whole, without a line break.
+        term.selection = Some(Selection::new(
+            SelectionType::Lines,
+            Point::new(Line(2), Column(0)),
+            Side::Left,
+        ));
+        assert_eq!(term.selection_to_string(), Some(String::from("defghij")));
+
+        if let Some(s) = term.selection.as_mut() {
+            s.update(Point::new(Line(3), Column(0)), Side::Left);
+        }
+        assert_eq!(term.selection_to_string(), Some(String::from("defghij\nk")));
+    }
+
     #[test]
     fn input_line_drawing_character() {
         let size = TermSize::new(7, 17);
@@ -2782,6 +3496,33 @@ mod tests {
         assert_eq!(term.grid.cursor.point, Point::new(Line(19), Column(0)));
     }
 
+    #[test]
+    fn alt_screen_keeps_cursor_template() {
+        let size = TermSize::new(10, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b[1m\x1b(0a\x1b[?1049hq");
+
+        // The alternate screen starts with the SGR template and charsets of the primary one.
+        let cell = &term.grid[Line(0)][Column(1)];
+        assert_eq!(cell.c, '─');
+        assert!(cell.flags.contains(Flags::BOLD));
+
+        parser.advance(&mut term, b"\x1b[0;31m\x1b(Bx\x1b[?1049l");
+
+        let cursor = &term.grid.cursor;
+        assert_eq!(cursor.point, Point::new(Line(0), Column(1)));
+        assert!(cursor.template.flags.contains(Flags::BOLD));
+        assert_eq!(cursor.template.fg, Color::Named(NamedColor::Foreground));
+        assert_eq!(
+            cursor.charsets[CharsetIndex::G0],
+            Charset::SpecialCharacterAndLineDrawing
+        );
+        assert_eq!(term.grid[Line(0)][Column(0)].c, '▒');
+        assert_eq!(term.grid[Line(0)][Column(1)].c, ' ');
+    }
+
     #[test]
     fn grow_lines_updates_inactive_cursor_pos() {
         let mut size = TermSize::new(100, 10);
@@ -3097,15 +3838,9 @@ mod tests {
         );
         term.damage.reset(num_cols);
 
+        // Returning the cursor doesn't change any cells.
         term.carriage_return();
-        assert_eq!(
-            term.damage.lines[7],
-            LineDamageBounds {
-                line: 7,
-                left: 0,
-                right: 3
-            }
-        );
+        assert!(!term.damage.lines[7].is_damaged());
         term.damage.reset(num_cols);
 
         term.erase_chars(5);
@@ -3216,6 +3951,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn damage_identical_cell_writes() {
+        let size = TermSize::new(10, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"[##  ] 5%");
+        let _ = term.damage();
+        term.reset_damage();
+
+        // Redrawing the same progress bar changes no cells, only the cursor is redrawn.
+        parser.advance(&mut term, b"\r[##  ] 5%");
+        let mut damaged_lines = match term.damage() {
+            TermDamage::Full => panic!("Expected partial damage, however got Full"),
+            TermDamage::Partial(damaged_lines) => damaged_lines,
+        };
+        assert_eq!(damaged_lines.next(), Some(LineDamageBounds::new(0, 9, 9)));
+        assert_eq!(damaged_lines.next(), None);
+        term.reset_damage();
+
+        // Changed cells are damaged.
+        parser.advance(&mut term, b"\r[### ] 7%");
+        let mut damaged_lines = match term.damage() {
+            TermDamage::Full => panic!("Expected partial damage, however got Full"),
+            TermDamage::Partial(damaged_lines) => damaged_lines,
+        };
+        assert_eq!(damaged_lines.next(), Some(LineDamageBounds::new(0, 3, 9)));
+        assert_eq!(damaged_lines.next(), None);
+    }
+
     #[test]
     fn full_damage() {
         let size = TermSize::new(100, 10);
@@ -3302,6 +4067,183 @@ mod tests {
         assert!(term.damage.full);
     }
 
+    #[test]
+    fn pending_output() {
+        let size = TermSize::new(10, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        // A new terminal needs to be drawn once.
+        assert!(term.has_pending_output());
+        let _ = term.damage();
+        term.reset_damage();
+        assert!(!term.has_pending_output());
+
+        parser.advance(&mut term, b"abc");
+        assert!(term.has_pending_output());
+
+        let _ = term.damage();
+        term.reset_damage();
+        assert!(!term.has_pending_output());
+
+        // Cursor movement alone needs a redraw too.
+        parser.advance(&mut term, b"\x1b[3;3H");
+        assert!(term.has_pending_output());
+    }
+
+    #[test]
+    fn reverse_video_mode() {
+        let size = TermSize::new(10, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let responses = tap_responses(&mut term);
+        let mut parser = Processor::new();
+
+        term.reset_damage();
+        parser.advance(&mut term, b"\x1b[?5h\x1b[?5$p");
+        assert!(term.mode().contains(TermMode::REVERSE_VIDEO));
+        assert!(matches!(term.damage(), TermDamage::Full));
+
+        term.reset_damage();
+        parser.advance(&mut term, b"\x1b[?5l\x1b[?5$p");
+        assert!(!term.mode().contains(TermMode::REVERSE_VIDEO));
+        assert!(matches!(term.damage(), TermDamage::Full));
+
+        assert_eq!(
+            *responses.lock().unwrap(),
+            vec![String::from("\x1b[?5;1$y"), String::from("\x1b[?5;2$y")]
+        );
+    }
+
+    /// Event listener answering window requests like a frontend would.
+    struct WindowListener(Arc<Mutex<Vec<String>>>);
+
+    impl EventListener for WindowListener {
+        fn send_event(&self, event: Event) {
+            let response = match event {
+                Event::WindowStateRequest(format) => format(false),
+                Event::WindowPositionRequest(format) => format(20, 40),
+                _ => return,
+            };
+            self.0.lock().unwrap().push(response);
+        }
+    }
+
+    #[test]
+    fn window_state_and_position_reports() {
+        let responses = Arc::new(Mutex::new(Vec::new()));
+        let size = TermSize::new(10, 5);
+        let mut term = Term::new(Config::default(), &size, WindowListener(responses.clone()));
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b[11t\x1b[13t");
+
+        assert_eq!(
+            *responses.lock().unwrap(),
+            vec![String::from("\x1b[1t"), String::from("\x1b[3;20;40t")]
+        );
+    }
+
+    /// Event listener counting bell rings.
+    struct BellListener(Arc<Mutex<usize>>);
+
+    impl EventListener for BellListener {
+        fn send_event(&self, event: Event) {
+            if let Event::Bell = event {
+                *self.0.lock().unwrap() += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn bell_event() {
+        let rings = Arc::new(Mutex::new(0));
+        let size = TermSize::new(10, 2);
+        let mut term = Term::new(Config::default(), &size, BellListener(rings.clone()));
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"a\x07b");
+        assert_eq!(*rings.lock().unwrap(), 1);
+
+        // BEL terminating an OSC does not ring.
+        parser.advance(&mut term, b"\x1b]2;title\x07");
+        assert_eq!(*rings.lock().unwrap(), 1);
+    }
+
+    /// Event listener recording title changes.
+    struct TitleListener(Arc<Mutex<Vec<Option<String>>>>);
+
+    impl EventListener for TitleListener {
+        fn send_event(&self, event: Event) {
+            let title = match event {
+                Event::Title(title) => Some(title),
+                Event::ResetTitle => None,
+                _ => return,
+            };
+            self.0.lock().unwrap().push(title);
+        }
+    }
+
+    #[test]
+    fn osc7_current_directory() {
+        let size = TermSize::new(10, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        assert_eq!(term.current_directory(), None);
+
+        parser.advance(&mut term, b"\x1b]7;file://localhost/tmp/my%20dir\x07");
+        assert_eq!(term.current_directory(), Some(Path::new("/tmp/my dir")));
+
+        // Directories on other hosts are not inherited.
+        parser.advance(&mut term, b"\x1b]7;file://remote.invalid/home\x07");
+        assert_eq!(term.current_directory(), Some(Path::new("/tmp/my dir")));
+
+        parser.advance(&mut term, b"\x1b]7;\x07");
+        assert_eq!(term.current_directory(), None);
+    }
+
+    #[test]
+    fn title_stack_events() {
+        let titles = Arc::new(Mutex::new(Vec::new()));
+        let size = TermSize::new(10, 5);
+        let mut term = Term::new(Config::default(), &size, TitleListener(titles.clone()));
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b]2;one\x07\x1b[22t\x1b]2;two\x07");
+        parser.advance(&mut term, b"\x1b[23t\x1b[23t");
+
+        // Popping the empty stack leaves the title alone.
+        assert_eq!(
+            *titles.lock().unwrap(),
+            vec![
+                Some(String::from("one")),
+                Some(String::from("two")),
+                Some(String::from("one")),
+            ]
+        );
+    }
+
+    #[test]
+    fn keyboard_mode_overflow_keeps_titles() {
+        let config = Config {
+            kitty_keyboard: true,
+            ..Config::default()
+        };
+        let size = TermSize::new(10, 5);
+        let mut term = Term::new(config, &size, VoidListener);
+
+        term.push_title();
+        for _ in 0..=KEYBOARD_MODE_STACK_MAX_DEPTH {
+            term.push_keyboard_mode(KeyboardModes::DISAMBIGUATE_ESC_CODES);
+        }
+
+        assert_eq!(term.title_stack.len(), 1);
+        assert_eq!(
+            term.keyboard_mode_stack.len(),
+            KEYBOARD_MODE_STACK_MAX_DEPTH
+        );
+    }
+
     #[test]
     fn window_title() {
         let size = TermSize::new(7, 17);
@@ -3350,6 +4292,1207 @@ mod tests {
         assert_eq!(term.title, None);
     }
 
+    #[test]
+    fn cursor_forward_clamps_to_last_column() {
+        let size = TermSize::new(10, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b[1000C");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(9)));
+        assert!(!term.grid.cursor.input_needs_wrap);
+
+        // Moving past the margin must not wrap onto the next line.
+        parser.advance(&mut term, b"\x1b[C");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(9)));
+
+        // Huge counts passed to the handler directly must not overflow.
+        term.move_forward(usize::MAX);
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(9)));
+    }
+
+    #[test]
+    fn cursor_backward_clamps_to_first_column() {
+        let size = TermSize::new(10, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b[2;5H\x1b[1000D");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(1), Column(0)));
+        assert!(!term.grid.cursor.input_needs_wrap);
+
+        parser.advance(&mut term, b"\x1b[D");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(1), Column(0)));
+    }
+
+    #[test]
+    fn cursor_forward_clears_pending_wrap() {
+        let size = TermSize::new(3, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"abc");
+        assert!(term.grid.cursor.input_needs_wrap);
+
+        parser.advance(&mut term, b"\x1b[1000C");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(2)));
+        assert!(!term.grid.cursor.input_needs_wrap);
+    }
+
+    #[test]
+    fn linefeed_at_bottom_scrolls_into_history() {
+        let size = TermSize::new(5, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"a\r\nb\r\nc\n");
+
+        assert_eq!(term.grid.cursor.point.line, Line(2));
+        assert_eq!(term.grid.history_size(), 1);
+        assert_eq!(term.grid[Line(-1)][Column(0)].c, 'a');
+        assert_eq!(term.grid[Line(0)][Column(0)].c, 'b');
+        assert_eq!(term.grid[Line(1)][Column(0)].c, 'c');
+        assert_eq!(term.grid[Line(2)][Column(0)].c, ' ');
+    }
+
+    #[test]
+    fn linefeed_at_region_bottom_discards_line() {
+        let size = TermSize::new(5, 4);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"a\r\nb\r\nc\r\nd");
+
+        // Restrict scrolling to lines 2-3 and linefeed at the region's bottom.
+        parser.advance(&mut term, b"\x1b[2;3r\x1b[3;1H\n");
+
+        assert_eq!(term.grid.cursor.point.line, Line(2));
+        assert_eq!(term.grid.history_size(), 0);
+        assert_eq!(term.grid[Line(0)][Column(0)].c, 'a');
+        assert_eq!(term.grid[Line(1)][Column(0)].c, 'c');
+        assert_eq!(term.grid[Line(2)][Column(0)].c, ' ');
+        assert_eq!(term.grid[Line(3)][Column(0)].c, 'd');
+    }
+
+    #[test]
+    fn scroll_region_keeps_outer_lines() {
+        let size = TermSize::new(2, 6);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        let screen = |term: &Term<VoidListener>| -> String {
+            (0..6)
+                .map(|line| term.grid[Line(line)][Column(0)].c)
+                .collect()
+        };
+
+        parser.advance(&mut term, b"a\r\nb\r\nc\r\nd\r\ne\r\nf");
+        parser.advance(&mut term, b"\x1b[2;4r");
+        assert_eq!(term.scroll_region, Line(1)..Line(4));
+
+        // Explicit scrolling only moves lines within the region.
+        parser.advance(&mut term, b"\x1b[S");
+        assert_eq!(screen(&term), "acd ef");
+        parser.advance(&mut term, b"\x1b[2T");
+        assert_eq!(screen(&term), "a  cef");
+
+        // Index at the region bottom and reverse index at its top scroll the region.
+        parser.advance(&mut term, b"\x1b[2;1Hx\x1b[4;1H\n");
+        assert_eq!(screen(&term), "a c ef");
+        parser.advance(&mut term, b"\x1b[2;1H\x1bM");
+        assert_eq!(screen(&term), "a  cef");
+        assert_eq!(term.grid.history_size(), 0);
+
+        // A full reset restores full screen margins.
+        parser.advance(&mut term, b"\x1bc");
+        assert_eq!(term.scroll_region, Line(0)..Line(6));
+    }
+
+    #[test]
+    fn scroll_counts_clamp_to_region() {
+        let size = TermSize::new(2, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        let screen = |term: &Term<VoidListener>| -> String {
+            (0..5)
+                .map(|line| term.grid[Line(line)][Column(0)].c)
+                .collect()
+        };
+
+        parser.advance(&mut term, b"a\r\nb\r\nc\r\nd\r\ne\x1b[2;4r\x1b[5;2H");
+
+        // Counts past the region height only blank the region, the cursor stays put.
+        parser.advance(&mut term, b"\x1b[100S");
+        assert_eq!(screen(&term), "a   e");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(4), Column(1)));
+        assert_eq!(term.grid.history_size(), 0);
+
+        parser.advance(&mut term, b"\x1b[2;1Hx\x1b[100T");
+        assert_eq!(screen(&term), "a   e");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(1), Column(1)));
+    }
+
+    #[test]
+    fn scroll_region_clamps_margins() {
+        let size = TermSize::new(5, 6);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        // The bottom margin is clamped to the screen.
+        parser.advance(&mut term, b"\x1b[3;100r");
+        assert_eq!(term.scroll_region, Line(2)..Line(6));
+
+        // Regions without at least two lines are ignored.
+        parser.advance(&mut term, b"\x1b[4;4r\x1b[7;100r");
+        assert_eq!(term.scroll_region, Line(2)..Line(6));
+
+        parser.advance(&mut term, b"\x1b[r");
+        assert_eq!(term.scroll_region, Line(0)..Line(6));
+    }
+
+    fn line_text<T>(term: &Term<T>, line: Line) -> String {
+        (0..term.columns())
+            .map(|col| term.grid[line][Column(col)].c)
+            .collect()
+    }
+
+    #[test]
+    fn insert_mode_shifts_cells_right() {
+        let size = TermSize::new(8, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"abcdef\x1b[1;3H\x1b[4hXY");
+
+        assert_eq!(line_text(&term, Line(0)), "abXYcdef");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(4)));
+
+        // Cells shifted past the right margin are discarded.
+        parser.advance(&mut term, b"Z");
+        assert_eq!(line_text(&term, Line(0)), "abXYZcde");
+        assert_eq!(line_text(&term, Line(1)), "        ");
+    }
+
+    #[test]
+    fn replace_mode_overwrites_cells() {
+        let size = TermSize::new(8, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"abcdef\x1b[4h\x1b[4l\x1b[1;3HXY");
+
+        assert_eq!(line_text(&term, Line(0)), "abXYef  ");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(4)));
+    }
+
+    #[test]
+    fn wide_char_occupies_two_cells() {
+        let size = TermSize::new(6, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, "a\u{6f22}b".as_bytes());
+
+        assert_eq!(line_text(&term, Line(0)), "a\u{6f22} b  ");
+        assert!(term.grid[Line(0)][Column(1)]
+            .flags
+            .contains(Flags::WIDE_CHAR));
+        assert!(term.grid[Line(0)][Column(2)]
+            .flags
+            .contains(Flags::WIDE_CHAR_SPACER));
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(4)));
+    }
+
+    #[test]
+    fn wide_char_at_right_margin_wraps() {
+        let size = TermSize::new(4, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, "abc\u{6f22}".as_bytes());
+
+        // The glyph does not fit in the last column, so it moves to the next line.
+        let last = &term.grid[Line(0)][Column(3)];
+        assert_eq!(last.c, ' ');
+        assert!(last.flags.contains(Flags::LEADING_WIDE_CHAR_SPACER));
+        assert!(last.flags.contains(Flags::WRAPLINE));
+
+        assert_eq!(term.grid[Line(1)][Column(0)].c, '\u{6f22}');
+        assert!(term.grid[Line(1)][Column(1)]
+            .flags
+            .contains(Flags::WIDE_CHAR_SPACER));
+        assert_eq!(term.grid.cursor.point, Point::new(Line(1), Column(2)));
+    }
+
+    #[test]
+    fn insert_mode_wide_char() {
+        let size = TermSize::new(6, 1);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, "abcd\x1b[1;2H\x1b[4h字".as_bytes());
+
+        assert_eq!(term.grid[Line(0)][Column(1)].c, '字');
+        assert!(term.grid[Line(0)][Column(1)]
+            .flags
+            .contains(Flags::WIDE_CHAR));
+        assert!(term.grid[Line(0)][Column(2)]
+            .flags
+            .contains(Flags::WIDE_CHAR_SPACER));
+        assert_eq!(term.grid[Line(0)][Column(3)].c, 'b');
+        assert_eq!(term.grid[Line(0)][Column(5)].c, 'd');
+    }
+
+    #[test]
+    fn repeat_wraps_and_handles_wide_chars() {
+        let size = TermSize::new(4, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"a\x1b[5b");
+        assert_eq!(line_text(&term, Line(0)), "aaaa");
+        assert_eq!(line_text(&term, Line(1)), "aa  ");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(1), Column(2)));
+
+        // Wide repetitions take two cells each, the last one wraps and scrolls the screen.
+        parser.advance(&mut term, "\r\n字\x1b[2b".as_bytes());
+        assert_eq!(line_text(&term, Line(0)), "aa  ");
+        assert_eq!(line_text(&term, Line(1)), "字 字 ");
+        assert_eq!(line_text(&term, Line(2)), "字   ");
+        assert!(term.grid[Line(1)][Column(2)]
+            .flags
+            .contains(Flags::WIDE_CHAR));
+        assert_eq!(term.grid.cursor.point, Point::new(Line(2), Column(2)));
+    }
+
+    #[test]
+    fn repeat_without_autowrap() {
+        let size = TermSize::new(4, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b[?7lab\x1b[65535b");
+        assert_eq!(line_text(&term, Line(0)), "abbb");
+        assert_eq!(line_text(&term, Line(1)), "    ");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(3)));
+    }
+
+    #[test]
+    fn cursor_motion_across_wide_chars() {
+        let size = TermSize::new(10, 1);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, "a字b漢c".as_bytes());
+        assert_eq!(term.grid.cursor.point.column, Column(7));
+
+        // Motion counts columns, so it can land on the spacer of a wide char.
+        parser.advance(&mut term, b"\x1b[2D");
+        assert_eq!(term.grid.cursor.point.column, Column(5));
+        assert!(term.grid[Line(0)][Column(5)]
+            .flags
+            .contains(Flags::WIDE_CHAR_SPACER));
+
+        parser.advance(&mut term, b"\x1b[4D");
+        assert_eq!(term.grid.cursor.point.column, Column(1));
+        assert_eq!(term.grid[Line(0)][Column(1)].c, '字');
+
+        parser.advance(&mut term, b"\x1b[2C");
+        assert_eq!(term.grid.cursor.point.column, Column(3));
+        assert_eq!(term.grid[Line(0)][Column(3)].c, 'b');
+
+        // Writing over a spacer clears the wide char it belongs to.
+        parser.advance(&mut term, b"\x1b[2Cx");
+        assert_eq!(line_text(&term, Line(0)), "a字 b xc   ");
+        assert_eq!(term.grid.cursor.point.column, Column(6));
+    }
+
+    #[test]
+    fn renderable_cursor_block() {
+        let size = TermSize::new(5, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+
+        let cursor = term.renderable_cursor();
+        assert_eq!(cursor.shape, CursorShape::Block);
+        assert!(cursor.inverts_cell());
+        assert_eq!(
+            cursor.rect(8., 16.),
+            Some(CursorRect {
+                x: 0.,
+                y: 0.,
+                width: 8.,
+                height: 16.
+            })
+        );
+
+        // Block covers both cells of a wide character.
+        term.input('字');
+        term.goto(0, 0);
+        let cursor = term.renderable_cursor();
+        assert!(cursor.is_wide);
+        assert_eq!(cursor.rect(8., 16.).map(|rect| rect.width), Some(16.));
+    }
+
+    #[test]
+    fn renderable_cursor_beam() {
+        let size = TermSize::new(5, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+
+        term.set_cursor_style(Some(CursorStyle {
+            shape: CursorShape::Beam,
+            blinking: true,
+        }));
+
+        let cursor = term.renderable_cursor();
+        assert!(!cursor.inverts_cell());
+        assert_eq!(
+            cursor.rect(10., 20.),
+            Some(CursorRect {
+                x: 0.,
+                y: 0.,
+                width: 2.,
+                height: 20.
+            })
+        );
+
+        assert!(cursor.is_visible(true));
+        assert!(!cursor.is_visible(false));
+    }
+
+    #[test]
+    fn renderable_cursor_hidden() {
+        let size = TermSize::new(5, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+
+        term.unset_private_mode(NamedPrivateMode::ShowCursor.into());
+
+        let cursor = term.renderable_cursor();
+        assert_eq!(cursor.shape, CursorShape::Hidden);
+        assert_eq!(cursor.rect(8., 16.), None);
+        assert!(!cursor.is_visible(true));
+    }
+
+    #[test]
+    fn bold_is_bright() {
+        let size = TermSize::new(5, 3);
+        let mut parser = Processor::new();
+
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        parser.advance(&mut term, b"\x1b[1;31mx");
+        let fg = term.grid[Line(0)][Column(0)]
+            .attrs()
+            .foreground(term.bold_is_bright());
+        assert_eq!(fg, Color::Named(NamedColor::Red));
+
+        let config = Config {
+            bold_is_bright: true,
+            ..Config::default()
+        };
+        let mut term = Term::new(config, &size, VoidListener);
+        parser.advance(&mut term, b"\x1b[1;31mx\x1b[22my");
+        let foreground = |column| {
+            term.grid[Line(0)][Column(column)]
+                .attrs()
+                .foreground(term.bold_is_bright())
+        };
+        assert_eq!(foreground(0), Color::Named(NamedColor::BrightRed));
+        assert_eq!(foreground(1), Color::Named(NamedColor::Red));
+    }
+
+    #[test]
+    fn alternate_screen_disabled() {
+        let size = TermSize::new(5, 3);
+        let config = Config {
+            alternate_screen: false,
+            ..Config::default()
+        };
+        let mut term = Term::new(config, &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"a\x1b[?1049hb");
+
+        assert!(!term.mode().contains(TermMode::ALT_SCREEN));
+        assert_eq!(term.grid[Line(0)][Column(0)].c, 'a');
+        assert_eq!(term.grid[Line(0)][Column(1)].c, 'b');
+
+        parser.advance(&mut term, b"\x1b[?1049lc");
+
+        assert!(!term.mode().contains(TermMode::ALT_SCREEN));
+        assert_eq!(term.grid[Line(0)][Column(2)].c, 'c');
+    }
+
+    #[test]
+    fn full_reset_restores_defaults() {
+        let size = TermSize::new(10, 4);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        // Scrollback, a saved cursor, custom tab stops and charsets on the primary screen.
+        parser.advance(&mut term, b"1\r\n2\r\n3\r\n4\r\n5\r\n6");
+        parser.advance(&mut term, b"\x1b[1;3H\x1bH\x1b[3g\x1b[2;2H\x1b7\x1b(0");
+        parser.advance(
+            &mut term,
+            b"\x1b[2;3r\x1b[?6h\x1b[4h\x1b[?7l\x1b[?5h\x1b[?1000h",
+        );
+        parser.advance(&mut term, b"\x1b]2;title\x07\x1b[22t\x1b[5 q\x1b[1;31m");
+
+        // Content on the alternate screen, which stays active.
+        parser.advance(&mut term, b"\x1b[?1049halt");
+        assert!(term.inactive_grid.history_size() > 0);
+
+        parser.advance(&mut term, b"\x1bc");
+
+        assert_eq!(term.mode, TermMode::default());
+        assert_eq!(term.grid.history_size(), 0);
+        assert_eq!(term.grid.display_offset(), 0);
+        assert_eq!(term.screen_lines(), 4);
+        assert_eq!(term.columns(), 10);
+        assert_eq!(term.scroll_region, Line(0)..Line(4));
+        assert_eq!(term.grid.cursor, Cursor::default());
+        assert_eq!(term.grid.saved_cursor, Cursor::default());
+        assert_eq!(term.active_charset, CharsetIndex::G0);
+        assert_eq!(term.cursor_style, None);
+        assert_eq!(term.title, None);
+        assert!(term.title_stack.is_empty());
+
+        // Both screens are blank.
+        for grid in [&term.grid, &term.inactive_grid] {
+            for line in 0..4 {
+                for column in 0..10 {
+                    assert_eq!(grid[Line(line)][Column(column)], Cell::default());
+                }
+            }
+        }
+
+        // Default tab stops are back.
+        parser.advance(&mut term, b"\t");
+        assert_eq!(term.grid.cursor.point.column, Column(8));
+    }
+
+    #[test]
+    fn full_reset_clears_colors() {
+        let size = TermSize::new(5, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b]4;1;rgb:ab/01/ff\x07\x1b]11;#102030\x07");
+        assert_eq!(term.colors()[1], Some(Rgb::new(0xab, 0x01, 0xff)));
+
+        parser.advance(&mut term, b"\x1bc");
+
+        assert_eq!(term.colors()[1], None);
+        assert_eq!(term.colors()[NamedColor::Background], None);
+    }
+
+    #[test]
+    fn color_alpha() {
+        let size = TermSize::new(5, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+        let background = NamedColor::Background as usize;
+
+        assert_eq!(term.colors().alpha(background), 0xff);
+
+        parser.advance(&mut term, b"\x1b]11;rgba:10/20/30/80\x07");
+        assert_eq!(term.colors()[background], Some(Rgb::new(0x10, 0x20, 0x30)));
+        assert_eq!(term.colors().alpha(background), 0x80);
+
+        // Colors without an alpha channel are opaque again.
+        parser.advance(&mut term, b"\x1b]11;#102030\x07");
+        assert_eq!(term.colors().alpha(background), 0xff);
+
+        parser.advance(&mut term, b"\x1b]4;1;#11223344\x07");
+        assert_eq!(term.colors().alpha(1), 0x44);
+
+        parser.advance(&mut term, b"\x1b]104;1\x07");
+        assert_eq!(term.colors()[1], None);
+        assert_eq!(term.colors().alpha(1), 0xff);
+    }
+
+    #[test]
+    fn full_reset_keeps_colors() {
+        let size = TermSize::new(5, 3);
+        let config = Config {
+            reset_colors_on_ris: false,
+            ..Config::default()
+        };
+        let mut term = Term::new(config, &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b]4;1;rgb:ab/01/ff\x07\x1b]11;#102030\x07");
+        parser.advance(&mut term, b"\x1bc");
+
+        assert_eq!(term.colors()[1], Some(Rgb::new(0xab, 0x01, 0xff)));
+        assert_eq!(
+            term.colors()[NamedColor::Background],
+            Some(Rgb::new(0x10, 0x20, 0x30))
+        );
+    }
+
+    #[test]
+    fn alternate_screen_disables_scrollback() {
+        let size = TermSize::new(5, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"1\r\n2\r\n3\r\n4\r\n5\r\n6");
+        term.scroll_display(Scroll::Delta(2));
+        assert_eq!(term.grid().display_offset(), 2);
+        term.scroll_display(Scroll::Bottom);
+
+        parser.advance(&mut term, b"\x1b[?1049h");
+        term.scroll_display(Scroll::Delta(2));
+        assert_eq!(term.grid().display_offset(), 0);
+        term.scroll_display(Scroll::Top);
+        assert_eq!(term.grid().display_offset(), 0);
+
+        parser.advance(&mut term, b"\x1b[?1049l");
+        term.scroll_display(Scroll::Delta(2));
+        assert_eq!(term.grid().display_offset(), 2);
+    }
+
+    #[test]
+    fn alternate_screen_enabled() {
+        let size = TermSize::new(5, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"a\x1b[?1049hb");
+
+        assert!(term.mode().contains(TermMode::ALT_SCREEN));
+        assert_eq!(term.grid[Line(0)][Column(1)].c, 'b');
+        assert_eq!(term.inactive_grid[Line(0)][Column(0)].c, 'a');
+    }
+
+    #[test]
+    fn alternate_screen_keeps_primary_content() {
+        for mode in [47, 1047, 1049] {
+            let size = TermSize::new(5, 3);
+            let mut term = Term::new(Config::default(), &size, VoidListener);
+            let mut parser = Processor::new();
+
+            parser.advance(&mut term, b"ab\r\ncd");
+            let cursor = term.grid.cursor.point;
+
+            parser.advance(&mut term, format!("\x1b[?{mode}h").as_bytes());
+            assert!(term.mode().contains(TermMode::ALT_SCREEN), "{mode}");
+
+            // The alternate screen starts out empty.
+            assert_eq!(line_text(&term, Line(0)).trim_end(), "", "{mode}");
+
+            parser.advance(&mut term, b"\x1b[3;1Hxyz");
+            assert_eq!(line_text(&term, Line(2)).trim_end(), "xyz", "{mode}");
+
+            parser.advance(&mut term, format!("\x1b[?{mode}l").as_bytes());
+            assert!(!term.mode().contains(TermMode::ALT_SCREEN), "{mode}");
+
+            assert_eq!(line_text(&term, Line(0)).trim_end(), "ab", "{mode}");
+            assert_eq!(line_text(&term, Line(1)).trim_end(), "cd", "{mode}");
+            assert_eq!(line_text(&term, Line(2)).trim_end(), "", "{mode}");
+
+            if mode == 1049 {
+                assert_eq!(term.grid.cursor.point, cursor);
+            }
+
+            // Entering again shows the previous alternate screen content only for mode 47.
+            parser.advance(&mut term, format!("\x1b[?{mode}h").as_bytes());
+            let expected = if mode == 47 { "xyz" } else { "" };
+            assert_eq!(line_text(&term, Line(2)).trim_end(), expected, "{mode}");
+        }
+    }
+
+    #[test]
+    fn restore_cursor_charsets_and_origin() {
+        let size = TermSize::new(10, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b(0\x1b[2;4r\x1b[?6h\x1b7");
+        parser.advance(&mut term, b"\x1b(B\x1b[?6l\x1b[5;1H\x1b8q");
+
+        assert!(term.mode().contains(TermMode::ORIGIN));
+        assert_eq!(
+            term.grid.cursor.charsets[CharsetIndex::G0],
+            Charset::SpecialCharacterAndLineDrawing
+        );
+        assert_eq!(term.grid[Line(1)][Column(0)].c, '─');
+
+        // Restoring without a saved cursor falls back to the defaults.
+        parser.advance(&mut term, b"\x1bc\x1b[?6h\x1b8");
+        assert!(!term.mode().contains(TermMode::ORIGIN));
+    }
+
+    #[test]
+    fn alternate_screen_cursor_restore() {
+        let size = TermSize::new(5, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b[2;2H");
+
+        // Modes 47 and 1047 share the cursor with the primary screen.
+        parser.advance(&mut term, b"\x1b[?1047h\x1b[3;4H\x1b[?1047l");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(2), Column(3)));
+
+        // Mode 1049 restores the cursor from before entering the alternate screen.
+        parser.advance(&mut term, b"\x1b[?1049h\x1b[1;1H\x1b[?1049l");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(2), Column(3)));
+    }
+
+    #[test]
+    fn legacy_alternate_screen_keeps_saved_cursor() {
+        let size = TermSize::new(5, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        // Save the cursor at the origin, then move away from it.
+        parser.advance(&mut term, b"\x1b7\x1b[2;3H");
+
+        parser.advance(&mut term, b"\x1b[?1047h\x1b[?1047l\x1b8");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(0)));
+
+        parser.advance(&mut term, b"\x1b[2;3H\x1b[?1049h\x1b[?1049l\x1b8");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(1), Column(2)));
+    }
+
+    #[test]
+    fn decaln_ignores_scroll_region() {
+        let size = TermSize::new(4, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        // Set a scroll region with origin mode and move inside it.
+        parser.advance(&mut term, b"\x1b[2;4r\x1b[?6h\x1b[2;3H\x1b#8");
+
+        for line in 0..5 {
+            assert_eq!(line_text(&term, Line(line)), "EEEE");
+        }
+        assert_eq!(term.scroll_region, Line(0)..Line(5));
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(0)));
+
+        // Linefeed at the former region bottom no longer scrolls.
+        parser.advance(&mut term, b"\x1b[4;1H\n");
+        assert_eq!(term.grid.cursor.point.line, Line(4));
+    }
+
+    #[test]
+    fn decaln_uses_default_template() {
+        let size = TermSize::new(3, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        // Current attributes and existing wide characters are discarded.
+        parser.advance(&mut term, "\x1b[1;31;44m\u{4e2d}\x1b#8".as_bytes());
+
+        let expected = Cell {
+            c: 'E',
+            ..Cell::default()
+        };
+        for line in 0..2 {
+            for column in 0..3 {
+                assert_eq!(term.grid[Line(line)][Column(column)], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn fill_rectangle_with_character() {
+        let size = TermSize::new(6, 4);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        // Fill lines 2-3 and columns 2-4 with bold `#`.
+        parser.advance(&mut term, b"\x1b[1m\x1b[35;2;2;3;4$x");
+
+        assert_eq!(line_text(&term, Line(0)), "      ");
+        assert_eq!(line_text(&term, Line(1)), " ###  ");
+        assert_eq!(line_text(&term, Line(2)), " ###  ");
+        assert_eq!(line_text(&term, Line(3)), "      ");
+
+        let cell = &term.grid[Line(1)][Column(1)];
+        assert_eq!(cell.c, '#');
+        assert!(cell.flags.contains(Flags::BOLD));
+
+        // Missing edges extend the area to the end of the screen.
+        parser.advance(&mut term, b"\x1b[0m\x1b[46;4;5$x");
+        assert_eq!(line_text(&term, Line(3)), "    ..");
+        assert_eq!(line_text(&term, Line(2)), " ###  ");
+
+        // Non-printable fill characters are ignored.
+        parser.advance(&mut term, b"\x1b[10;1;1;4;6$x");
+        assert_eq!(term.grid[Line(0)][Column(0)].c, ' ');
+    }
+
+    #[test]
+    fn rectangle_checksum() {
+        let size = TermSize::new(6, 4);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let responses = tap_responses(&mut term);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b[2;2HAB\x1b[3;1H\x1b[1;4mC");
+        parser.advance(
+            &mut term,
+            b"\x1b[1;1;2;2;2;4*y\x1b[2;1;3;1;3;1*y\x1b[3;1;3;3;2;1*y",
+        );
+
+        // 'A' + 'B' + ' ', then 'C' with bold and underline.
+        let row = 0x41u16 + 0x42 + 0x20;
+        let bold = 0x43u16 + 0x80 + 0x10;
+        assert_eq!(
+            *responses.lock().unwrap(),
+            vec![
+                format!("\x1bP1!~{:04X}\x1b\\", row.wrapping_neg()),
+                format!("\x1bP2!~{:04X}\x1b\\", bold.wrapping_neg()),
+                String::from("\x1bP3!~0000\x1b\\"),
+            ]
+        );
+    }
+
+    #[test]
+    fn response_tap_observes_device_status() {
+        let size = TermSize::new(10, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        let responses = Arc::new(Mutex::new(Vec::new()));
+        let tap_responses = responses.clone();
+        term.set_response_tap(Some(Box::new(move |response| {
+            tap_responses.lock().unwrap().push(response.to_owned());
+        })));
+
+        parser.advance(&mut term, b"\x1b[3;5H\x1b[6n\x1b[5n");
+
+        assert_eq!(
+            *responses.lock().unwrap(),
+            vec![String::from("\x1b[3;5R"), String::from("\x1b[0n")]
+        );
+
+        term.set_response_tap(None);
+        parser.advance(&mut term, b"\x1b[6n");
+        assert_eq!(responses.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn device_attributes_responses() {
+        let size = TermSize::new(10, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        let responses = Arc::new(Mutex::new(Vec::new()));
+        let tap_responses = responses.clone();
+        term.set_response_tap(Some(Box::new(move |response| {
+            tap_responses.lock().unwrap().push(response.to_owned());
+        })));
+
+        parser.advance(&mut term, b"\x1b[c\x1b[>c\x1b[=c");
+
+        let version = version_number(env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            *responses.lock().unwrap(),
+            vec![
+                String::from("\x1b[?62;22c"),
+                format!("\x1b[>0;{version};0c"),
+                String::from("\x1bP!|00000000\x1b\\"),
+            ]
+        );
+    }
+
+    #[test]
+    fn color_response_indexed() {
+        let color = Rgb {
+            r: 0xab,
+            g: 0x01,
+            b: 0xff,
+        };
+
+        assert_eq!(
+            color_response("4;1", color, "\x07"),
+            "\x1b]4;1;rgb:abab/0101/ffff\x07"
+        );
+    }
+
+    #[test]
+    fn color_response_foreground() {
+        let color = Rgb {
+            r: 0xee,
+            g: 0xdd,
+            b: 0x00,
+        };
+
+        assert_eq!(
+            color_response("10", color, "\x1b\\"),
+            "\x1b]10;rgb:eeee/dddd/0000\x1b\\"
+        );
+    }
+
+    #[test]
+    fn resize_clamps_to_minimum_size() {
+        let size = TermSize::new(80, 24);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+
+        term.resize(TermSize::new(80, 0));
+        assert_eq!(term.screen_lines(), MIN_SCREEN_LINES);
+        assert_eq!(term.columns(), 80);
+
+        term.resize(TermSize::new(0, 0));
+        assert_eq!(term.screen_lines(), MIN_SCREEN_LINES);
+        assert_eq!(term.columns(), MIN_COLUMNS);
+
+        // The terminal keeps working at the minimum size.
+        let mut parser = Processor::new();
+        parser.advance(&mut term, b"abc\r\n");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(0)));
+
+        term.resize(TermSize::new(80, 24));
+        assert_eq!(term.screen_lines(), 24);
+        assert_eq!(term.columns(), 80);
+    }
+
+    #[test]
+    fn new_clamps_to_minimum_size() {
+        let size = TermSize::new(0, 0);
+        let term = Term::new(Config::default(), &size, VoidListener);
+
+        assert_eq!(term.screen_lines(), MIN_SCREEN_LINES);
+        assert_eq!(term.columns(), MIN_COLUMNS);
+    }
+
+    /// Collect all responses written by the terminal.
+    fn tap_responses(term: &mut Term<VoidListener>) -> Arc<Mutex<Vec<String>>> {
+        let responses = Arc::new(Mutex::new(Vec::new()));
+        let tap_responses = responses.clone();
+        term.set_response_tap(Some(Box::new(move |response| {
+            tap_responses.lock().unwrap().push(response.to_owned());
+        })));
+
+        responses
+    }
+
+    #[test]
+    fn decrqss_sgr() {
+        let size = TermSize::new(20, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let responses = tap_responses(&mut term);
+
+        let mut parser = Processor::new();
+        parser.advance(&mut term, b"\x1bP$qm\x1b\\");
+        parser.advance(
+            &mut term,
+            b"\x1b[1;4:3;31;48;5;100;58;2;1;2;3m\x1bP$qm\x1b\\",
+        );
+        parser.advance(&mut term, b"\x1b[0;94;58;5;9m\x1bP$qm\x1b\\");
+
+        assert_eq!(
+            *responses.lock().unwrap(),
+            vec![
+                String::from("\x1bP1$r0m\x1b\\"),
+                String::from("\x1bP1$r0;1;4:3;31;48;5;100;58;2;1;2;3m\x1b\\"),
+                String::from("\x1bP1$r0;94;58;5;9m\x1b\\"),
+            ]
+        );
+    }
+
+    #[test]
+    fn decrqss_scrolling_region_and_cursor_style() {
+        let size = TermSize::new(20, 10);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let responses = tap_responses(&mut term);
+
+        let mut parser = Processor::new();
+        parser.advance(&mut term, b"\x1bP$qr\x1b\\\x1b[2;8r\x1bP$qr\x1b\\");
+        parser.advance(&mut term, b"\x1b[6 q\x1bP$q q\x1b\\\x1bP$qx\x1b\\");
+
+        assert_eq!(
+            *responses.lock().unwrap(),
+            vec![
+                String::from("\x1bP1$r1;10r\x1b\\"),
+                String::from("\x1bP1$r2;8r\x1b\\"),
+                String::from("\x1bP1$r6 q\x1b\\"),
+                String::from("\x1bP0$r\x1b\\"),
+            ]
+        );
+    }
+
+    #[test]
+    fn decrqss_cursor_shapes_and_sgr_reset() {
+        let size = TermSize::new(20, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let responses = tap_responses(&mut term);
+
+        let mut parser = Processor::new();
+        for style in 1..=6 {
+            let request = format!("\x1b[{style} q\x1bP$q q\x1b\\");
+            parser.advance(&mut term, request.as_bytes());
+        }
+        parser.advance(&mut term, b"\x1b[0 q\x1bP$q q\x1b\\");
+        parser.advance(&mut term, b"\x1b[3;7m\x1b[0m\x1bP$qm\x1b\\");
+
+        let expected: Vec<String> = (1..=6)
+            .map(|style| format!("\x1bP1$r{style} q\x1b\\"))
+            .chain([
+                String::from("\x1bP1$r2 q\x1b\\"),
+                String::from("\x1bP1$r0m\x1b\\"),
+            ])
+            .collect();
+        assert_eq!(*responses.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn xtgettcap() {
+        let size = TermSize::new(20, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let responses = tap_responses(&mut term);
+
+        // "Tc", "colors;xx".
+        let mut parser = Processor::new();
+        parser.advance(
+            &mut term,
+            b"\x1bP+q5463\x1b\\\x1bP+q636f6c6f7273;7878\x1b\\",
+        );
+
+        assert_eq!(
+            *responses.lock().unwrap(),
+            vec![
+                String::from("\x1bP1+r5463\x1b\\"),
+                String::from("\x1bP1+r636F6C6F7273=323536\x1b\\"),
+                String::from("\x1bP0+r\x1b\\"),
+            ]
+        );
+    }
+
+    #[test]
+    fn extended_cursor_position_report() {
+        let size = TermSize::new(10, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let responses = tap_responses(&mut term);
+
+        let mut parser = Processor::new();
+        parser.advance(&mut term, b"\x1b[3;5H\x1b[?6n\x1b[6n");
+
+        assert_eq!(
+            *responses.lock().unwrap(),
+            vec![String::from("\x1b[?3;5;1R"), String::from("\x1b[3;5R")]
+        );
+    }
+
+    #[test]
+    fn cursor_position_report_in_origin_mode() {
+        let size = TermSize::new(10, 8);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let responses = tap_responses(&mut term);
+
+        let mut parser = Processor::new();
+        parser.advance(&mut term, b"\x1b[3;6r\x1b[?6h\x1b[2;4H\x1b[6n\x1b[?6n");
+        parser.advance(&mut term, b"\x1b[?6l\x1b[8;10H\x1b[6n");
+
+        assert_eq!(
+            *responses.lock().unwrap(),
+            vec![
+                String::from("\x1b[2;4R"),
+                String::from("\x1b[?2;4;1R"),
+                String::from("\x1b[8;10R"),
+            ]
+        );
+    }
+
+    #[test]
+    fn next_line_control() {
+        let size = TermSize::new(8, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"abc\x85d\x1bEe");
+
+        assert_eq!(line_text(&term, Line(0)).trim_end(), "abc");
+        assert_eq!(line_text(&term, Line(1)).trim_end(), "d");
+        assert_eq!(line_text(&term, Line(2)).trim_end(), "e");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(2), Column(1)));
+    }
+
+    #[test]
+    fn c1_tabulation_set() {
+        let size = TermSize::new(20, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b[3g\x1b[1;4H\x88\r\t");
+
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(3)));
+    }
+
+    #[test]
+    fn c1_index_and_reverse_index() {
+        let size = TermSize::new(8, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"ab\x84");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(1), Column(2)));
+
+        parser.advance(&mut term, b"\x8d\x8d");
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(2)));
+
+        // Reverse index at the top scrolls the content down.
+        assert_eq!(line_text(&term, Line(0)).trim_end(), "");
+        assert_eq!(line_text(&term, Line(1)).trim_end(), "ab");
+    }
+
+    #[test]
+    fn tab_advances_to_custom_stop() {
+        let size = TermSize::new(20, 1);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        // Set a stop at column 3, before the default one at column 8.
+        parser.advance(&mut term, b"\x1b[1;4H\x1bH\r\t");
+        assert_eq!(term.grid.cursor.point.column, Column(3));
+
+        parser.advance(&mut term, b"\t");
+        assert_eq!(term.grid.cursor.point.column, Column(8));
+
+        // Clearing the current stop only affects that column.
+        parser.advance(&mut term, b"\x1b[g\r\t");
+        assert_eq!(term.grid.cursor.point.column, Column(3));
+    }
+
+    #[test]
+    fn tab_clear_all_stops() {
+        let size = TermSize::new(20, 1);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b[3g\t");
+        assert_eq!(term.grid.cursor.point.column, Column(19));
+
+        parser.advance(&mut term, b"\r\x1b[2I");
+        assert_eq!(term.grid.cursor.point.column, Column(19));
+    }
+
+    #[test]
+    fn tab_forward_and_backward() {
+        let size = TermSize::new(30, 1);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"\x1b[2I");
+        assert_eq!(term.grid.cursor.point.column, Column(16));
+
+        // Clamped to the last column without a stop.
+        parser.advance(&mut term, b"\x1b[5I");
+        assert_eq!(term.grid.cursor.point.column, Column(29));
+
+        parser.advance(&mut term, b"\x1b[Z");
+        assert_eq!(term.grid.cursor.point.column, Column(24));
+    }
+
+    #[test]
+    fn tab_forward_and_backward_custom_stops() {
+        let size = TermSize::new(30, 1);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        // Replace the default stops with stops at columns 5, 12 and 20.
+        parser.advance(
+            &mut term,
+            b"\x1b[3g\x1b[1;6H\x1bH\x1b[1;13H\x1bH\x1b[1;21H\x1bH\r",
+        );
+
+        parser.advance(&mut term, b"\x1b[2I");
+        assert_eq!(term.grid.cursor.point.column, Column(12));
+
+        parser.advance(&mut term, b"\x1b[Z");
+        assert_eq!(term.grid.cursor.point.column, Column(5));
+
+        // The count defaults to 1 and backward tabs stop at the first column.
+        parser.advance(&mut term, b"\x1b[I\x1b[I\x1b[3Z");
+        assert_eq!(term.grid.cursor.point.column, Column(0));
+    }
+
+    #[test]
+    fn grid_edits_clear_pending_wrap() {
+        for sequence in [
+            &b"\x1b[S"[..],
+            b"\x1b[T",
+            b"\x1b[L",
+            b"\x1b[M",
+            b"\x1b[X",
+            b"\x1b[@",
+            b"\x1b[P",
+            b"\x1b[1K",
+            b"\x1b[2K",
+            b"\x1b[J",
+            b"\x1b[1J",
+            b"\x1b[2J",
+        ] {
+            let size = TermSize::new(3, 2);
+            let mut term = Term::new(Config::default(), &size, VoidListener);
+            let mut parser = Processor::new();
+
+            parser.advance(&mut term, b"abc");
+            assert!(term.grid.cursor.input_needs_wrap);
+
+            parser.advance(&mut term, sequence);
+            parser.advance(&mut term, b"d");
+
+            // The print lands in the last column instead of wrapping.
+            assert_eq!(term.grid[Line(0)][Column(2)].c, 'd', "{sequence:?}");
+            assert_eq!(term.grid.cursor.point.line, Line(0), "{sequence:?}");
+        }
+    }
+
+    #[test]
+    fn tab_stops_resize() {
+        let size = TermSize::new(10, 1);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+
+        term.resize(TermSize::new(20, 1));
+
+        let stops: Vec<_> = (0..20).filter(|&i| term.tabs[Column(i)]).collect();
+        assert_eq!(stops, [0, 8, 16]);
+    }
+
+    #[test]
+    fn pending_wrap_cursor_renders_at_last_column() {
+        let size = TermSize::new(5, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, b"abcde");
+
+        assert!(term.grid.cursor.input_needs_wrap);
+        assert_eq!(
+            term.renderable_cursor().point,
+            Point::new(Line(0), Column(4))
+        );
+
+        // The next character wraps instead of overwriting the last column.
+        parser.advance(&mut term, b"f");
+
+        assert_eq!(line_text(&term, Line(0)), "abcde");
+        assert_eq!(line_text(&term, Line(1)).trim_end(), "f");
+        assert_eq!(
+            term.renderable_cursor().point,
+            Point::new(Line(1), Column(1))
+        );
+    }
+
+    #[test]
+    fn pending_wrap_cursor_on_wide_char() {
+        let size = TermSize::new(4, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser = Processor::new();
+
+        parser.advance(&mut term, "ab😀".as_bytes());
+
+        assert!(term.grid.cursor.input_needs_wrap);
+
+        let cursor = term.renderable_cursor();
+        assert_eq!(cursor.point, Point::new(Line(0), Column(2)));
+        assert!(cursor.is_wide);
+    }
+
     #[test]
     fn parse_cargo_version() {
         assert_eq!(version_number("0.0.1-dev"), 1);
@@ -1,19 +1,16 @@
+use std::collections::BTreeMap;
 use std::ops::Add as _;
 
 use iced::advanced::graphics::core::Element;
 use iced::font::{Family, Stretch, Weight};
 use iced::keyboard::Modifiers;
 use iced::widget::container;
-use iced::{Font, Length, Size, Subscription, Task, Theme, window};
+use iced::{Font, Length, Padding, Size, Subscription, Task, Theme, window};
 use iced_saiga::{Command, TermMode, TermView};
 
 pub fn run() -> iced::Result {
-    iced::application(App::title, App::update, App::view)
+    iced::daemon(App::title, App::update, App::view)
         .antialiasing(false)
-        .window_size(Size {
-            width: 1280.0,
-            height: 720.0,
-        })
         .subscription(App::subscription)
         .run_with(App::new)
 }
@@ -22,11 +19,21 @@ pub fn run() -> iced::Result {
 pub enum Event {
     Terminal(iced_saiga::Event),
     FontSize(f32),
+    NewWindow,
+    WindowOpened(window::Id),
+    WindowClosed(window::Id),
 }
 
-struct App {
+/// Terminal shown in a window.
+struct Window {
     title: String,
     term: iced_saiga::Terminal,
+}
+
+struct App {
+    windows: BTreeMap<window::Id, Window>,
+    next_term_id: u64,
+    shell: String,
     font_settings: iced_saiga::settings::FontSettings,
 }
 
@@ -36,9 +43,18 @@ impl App {
             .expect("SHELL variable is not defined")
             .to_string();
 
-        let term_id = 0;
-        let term_settings = iced_saiga::settings::Settings {
-            font: iced_saiga::settings::FontSettings {
+        let app = Self::with_shell(system_shell);
+        let task = app.open_window();
+
+        (app, task)
+    }
+
+    fn with_shell(shell: String) -> Self {
+        Self {
+            windows: BTreeMap::new(),
+            next_term_id: 0,
+            shell,
+            font_settings: iced_saiga::settings::FontSettings {
                 size: 17.0,
                 font_type: Font {
                     weight: Weight::Normal,
@@ -48,26 +64,64 @@ impl App {
                 },
                 ..Default::default()
             },
+        }
+    }
+
+    fn open_window(&self) -> Task<Event> {
+        let (_, open) = window::open(window::Settings {
+            size: Size {
+                width: 1280.0,
+                height: 720.0,
+            },
+            ..Default::default()
+        });
+
+        open.map(Event::WindowOpened)
+    }
+
+    /// Create the terminal of a newly opened window.
+    fn insert_window(&mut self, id: window::Id) {
+        let term_settings = iced_saiga::settings::Settings {
+            font: self.font_settings.clone(),
             theme: iced_saiga::settings::ThemeSettings::default(),
+            cursor: iced_saiga::settings::CursorSettings::default(),
+            display: iced_saiga::settings::DisplaySettings {
+                padding: Padding::new(8.0),
+                ..Default::default()
+            },
+            mouse: iced_saiga::settings::MouseSettings::default(),
+            bell: iced_saiga::settings::BellSettings::default(),
             backend: iced_saiga::settings::BackendSettings {
-                shell: system_shell.to_string(),
+                shell: self.shell.clone(),
+                ..Default::default()
             },
         };
 
-        let font_settings = term_settings.font.clone();
+        let term_id = self.next_term_id;
+        self.next_term_id += 1;
 
-        (
-            Self {
+        self.windows.insert(
+            id,
+            Window {
                 title: String::from("Saiga"),
                 term: iced_saiga::Terminal::new(term_id, term_settings),
-                font_settings,
             },
-            Task::none(),
-        )
+        );
+    }
+
+    /// Window showing the terminal with `term_id`.
+    fn window_of(&self, term_id: u64) -> Option<window::Id> {
+        self.windows
+            .iter()
+            .find(|(_, window)| window.term.id == term_id)
+            .map(|(&id, _)| id)
     }
 
-    fn title(&self) -> String {
-        self.title.clone()
+    fn title(&self, id: window::Id) -> String {
+        self.windows
+            .get(&id)
+            .map(|window| window.title.clone())
+            .unwrap_or_default()
     }
 
     fn subscription(&self) -> Subscription<Event> {
@@ -77,29 +131,50 @@ impl App {
             {
                 "=" => Some(Event::FontSize(2.0)),
                 "-" => Some(Event::FontSize(-2.0)),
+                "n" => Some(Event::NewWindow),
                 _ => None,
             },
             _ => None,
         });
 
-        let term_subscription = iced_saiga::Subscription::new(self.term.id);
-        let term_event_stream = term_subscription.event_stream();
+        let term_subscriptions = self.windows.values().map(|window| {
+            let term_subscription = iced_saiga::Subscription::new(window.term.id);
+            let term_event_stream = term_subscription.event_stream();
 
-        Subscription::batch(vec![
-            key_subscription,
-            Subscription::run_with_id(self.term.id, term_event_stream).map(Event::Terminal),
-        ])
+            Subscription::run_with_id(window.term.id, term_event_stream).map(Event::Terminal)
+        });
+
+        Subscription::batch(
+            [
+                key_subscription,
+                window::close_events().map(Event::WindowClosed),
+            ]
+            .into_iter()
+            .chain(term_subscriptions),
+        )
     }
 
     fn update(&mut self, event: Event) -> Task<Event> {
         match event {
-            Event::Terminal(iced_saiga::Event::CommandReceived(_, cmd)) => {
-                match self.term.update(cmd) {
-                    iced_saiga::actions::Action::Shutdown => {
-                        window::get_latest().and_then(window::close)
-                    }
+            Event::Terminal(iced_saiga::Event::CommandReceived(term_id, cmd)) => {
+                let Some(id) = self.window_of(term_id) else {
+                    return Task::none();
+                };
+                let window = self.windows.get_mut(&id).expect("window of the terminal");
+
+                match window.term.update(cmd) {
+                    iced_saiga::actions::Action::Shutdown => window::close(id),
                     iced_saiga::actions::Action::ChangeTitle(title) => {
-                        self.title = title;
+                        window.title = title;
+
+                        Task::none()
+                    }
+                    iced_saiga::actions::Action::Bell => window::request_user_attention(
+                        id,
+                        Some(window::UserAttention::Informational),
+                    ),
+                    iced_saiga::actions::Action::OpenLink(uri) => {
+                        open_link(&uri);
 
                         Task::none()
                     }
@@ -108,18 +183,118 @@ impl App {
             }
             Event::FontSize(delta) => {
                 self.font_settings.size = self.font_settings.size.add(delta).max(5.0);
-                self.term
-                    .update(iced_saiga::Command::ChangeFont(self.font_settings.clone()));
+                for window in self.windows.values_mut() {
+                    window
+                        .term
+                        .update(iced_saiga::Command::ChangeFont(self.font_settings.clone()));
+                }
+
+                Task::none()
+            }
+            Event::NewWindow => self.open_window(),
+            Event::WindowOpened(id) => {
+                self.insert_window(id);
 
                 Task::none()
             }
+            Event::WindowClosed(id) => {
+                self.windows.remove(&id);
+
+                if self.windows.is_empty() {
+                    iced::exit()
+                } else {
+                    Task::none()
+                }
+            }
         }
     }
 
-    fn view(&self) -> Element<Event, Theme, iced::Renderer> {
-        container(TermView::show(&self.term).map(Event::Terminal))
+    fn view(&self, id: window::Id) -> Element<Event, Theme, iced::Renderer> {
+        let Some(window) = self.windows.get(&id) else {
+            return container("").into();
+        };
+
+        container(TermView::show(&window.term).map(Event::Terminal))
             .width(Length::Fill)
             .height(Length::Fill)
             .into()
     }
 }
+
+/// URI schemes that hyperlinks from terminal output are allowed to open.
+///
+/// `file` is left out, since it would let any program open arbitrary local paths, executables
+/// included.
+const LINK_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+fn is_allowed_link(uri: &str) -> bool {
+    uri.split_once(':').is_some_and(|(scheme, _)| {
+        LINK_SCHEMES
+            .iter()
+            .any(|allowed| scheme.eq_ignore_ascii_case(allowed))
+    })
+}
+
+/// Open `uri` with the platform's default handler.
+fn open_link(uri: &str) {
+    if !is_allowed_link(uri) {
+        log::warn!("Refusing to open link with unsupported scheme: {uri}");
+        return;
+    }
+
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = std::process::Command::new("explorer");
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    if let Err(err) = command.arg(uri).spawn() {
+        log::warn!("Failed to open {uri}: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_have_independent_terminals() {
+        let mut app = App::with_shell(String::from("/bin/sh"));
+        let first = window::Id::unique();
+        let second = window::Id::unique();
+
+        let _ = app.update(Event::WindowOpened(first));
+        let _ = app.update(Event::WindowOpened(second));
+
+        let first_term = app.windows[&first].term.id;
+        let second_term = app.windows[&second].term.id;
+        assert_ne!(first_term, second_term);
+        assert_eq!(app.window_of(first_term), Some(first));
+        assert_eq!(app.window_of(second_term), Some(second));
+
+        let _ = app.update(Event::WindowClosed(first));
+        assert_eq!(app.window_of(first_term), None);
+        assert_eq!(app.window_of(second_term), Some(second));
+        assert_eq!(app.title(second), "Saiga");
+
+        // Events of a closed window's terminal are dropped.
+        let _ = app.update(Event::Terminal(iced_saiga::Event::CommandReceived(
+            first_term,
+            iced_saiga::Command::AddBindings(Vec::new()),
+        )));
+        assert_eq!(app.windows.len(), 1);
+    }
+
+    #[test]
+    fn only_allowed_link_schemes_are_opened() {
+        assert!(is_allowed_link("https://example.com"));
+        assert!(is_allowed_link("HTTP://example.com"));
+        assert!(is_allowed_link("mailto:someone@example.com"));
+
+        assert!(!is_allowed_link("javascript:alert(1)"));
+        assert!(!is_allowed_link("ssh://example.com"));
+        assert!(!is_allowed_link("file:///usr/bin/xterm"));
+        assert!(!is_allowed_link("example.com & calc.exe"));
+    }
+}
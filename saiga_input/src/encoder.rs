@@ -1,4 +1,9 @@
+use std::fmt::Write as _;
+
+use saiga_vte::ansi::handler::KeyboardModes;
+
 use crate::key::{Action, Key, KeyEvent, Mods};
+use crate::kitty;
 
 pub enum MacosOptionAsAlt {
     None,
@@ -8,13 +13,149 @@ pub enum MacosOptionAsAlt {
 }
 
 pub struct KeyEncoder {
-    event: KeyEvent,
-    macos_option_as_alt: MacosOptionAsAlt,
+    pub event: KeyEvent,
+    pub macos_option_as_alt: MacosOptionAsAlt,
+
+    /// Application cursor keys mode (DECCKM).
+    pub cursor_key_application: bool,
+
+    /// Application keypad mode (DECKPAM).
+    pub keypad_key_application: bool,
+    pub ignore_keypad_with_numlock: bool,
+
+    /// True if xterm's modifyOtherKeys is in state 2.
+    pub modify_other_keys_state_2: bool,
+
+    /// Active kitty keyboard protocol flags of the terminal.
+    pub keyboard_modes: KeyboardModes,
 }
 
 impl KeyEncoder {
-    fn encode_legacy(&self, buf: &[u8]) -> Option<String> {
-        let all_mods = self.event.mods;
+    pub fn new(event: KeyEvent) -> Self {
+        Self {
+            event,
+            macos_option_as_alt: MacosOptionAsAlt::None,
+            cursor_key_application: false,
+            keypad_key_application: false,
+            ignore_keypad_with_numlock: false,
+            modify_other_keys_state_2: false,
+            keyboard_modes: KeyboardModes::NO_MODE,
+        }
+    }
+
+    /// Encodes the key event into the bytes that should be written to the pty.
+    pub fn encode(&self) -> Option<Vec<u8>> {
+        if !self.keyboard_modes.is_empty() {
+            return self.encode_kitty(self.keyboard_modes);
+        }
+
+        self.encode_legacy()
+    }
+
+    /// Encodes the key event using the [kitty keyboard protocol].
+    ///
+    /// [kitty keyboard protocol]: https://sw.kovidgoyal.net/kitty/keyboard-protocol
+    pub fn encode_kitty(&self, modes: KeyboardModes) -> Option<Vec<u8>> {
+        let event = &self.event;
+
+        if event.composing {
+            return None;
+        }
+
+        let release = event.action == Action::Release;
+        if release && !modes.contains(KeyboardModes::REPORT_EVENT_TYPES) {
+            return None;
+        }
+
+        let text = (!release && !event.utf8.chars().any(char::is_control))
+            .then_some(event.utf8.as_str())
+            .filter(|text| !text.is_empty());
+
+        if !modes.contains(KeyboardModes::REPORT_ALL_KEYS_AS_ESC) && !release {
+            // Text without ctrl, alt or meta is sent as is.
+            let effective_mods = self.event.effective_mods();
+            let plain = !effective_mods.intersects(Mods::CTRL | Mods::ALT | Mods::META);
+            if let Some(text) = text.filter(|_| plain) {
+                return Some(text.as_bytes().to_vec());
+            }
+
+            // Enter, tab and backspace keep their legacy encoding.
+            if event.mods.is_empty() {
+                match event.key {
+                    Key::Enter => return Some(b"\r".to_vec()),
+                    Key::Tab => return Some(b"\t".to_vec()),
+                    Key::Backspace => return Some(b"\x7f".to_vec()),
+                    _ => (),
+                }
+            }
+        }
+
+        let entry = match kitty::functional_key(event.key) {
+            Some(entry) => entry,
+            None => {
+                let c = event.unshifted_char.or_else(|| event.key.codepoint())?;
+                kitty::Entry::new(c as u32, 'u')
+            }
+        };
+
+        let mut alternates = String::new();
+        if modes.contains(KeyboardModes::REPORT_ALTERNATE_KEYS) && entry.final_byte == 'u' {
+            let mut chars = event.utf8.chars();
+            let shifted = match (chars.next(), chars.next()) {
+                (Some(c), None) if event.mods.contains(Mods::SHIFT) && c as u32 != entry.code => {
+                    Some(c as u32)
+                }
+                _ => None,
+            };
+            let base = event
+                .physical_key
+                .codepoint()
+                .map(|c| c as u32)
+                .filter(|&c| c != entry.code);
+
+            match (shifted, base) {
+                (Some(shifted), Some(base)) => _ = write!(alternates, ":{shifted}:{base}"),
+                (Some(shifted), None) => _ = write!(alternates, ":{shifted}"),
+                (None, Some(base)) => _ = write!(alternates, "::{base}"),
+                (None, None) => (),
+            }
+        }
+
+        let event_type = match event.action {
+            _ if !modes.contains(KeyboardModes::REPORT_EVENT_TYPES) => None,
+            Action::Press => None,
+            Action::Repeat => Some(2),
+            Action::Release => Some(3),
+        };
+
+        let text = text.filter(|_| modes.contains(KeyboardModes::REPORT_ASSOCIATED_TEXT));
+
+        let modifier = modifier_param(event.mods).unwrap_or(1);
+        let has_modifier = modifier != 1 || event_type.is_some() || text.is_some();
+
+        let mut seq = String::from("\x1b[");
+        if entry.code != 1 || has_modifier || !alternates.is_empty() {
+            _ = write!(seq, "{}{alternates}", entry.code);
+        }
+
+        if has_modifier {
+            _ = write!(seq, ";{modifier}");
+            if let Some(event_type) = event_type {
+                _ = write!(seq, ":{event_type}");
+            }
+        }
+
+        if let Some(text) = text {
+            let codepoints: Vec<String> = text.chars().map(|c| (c as u32).to_string()).collect();
+            _ = write!(seq, ";{}", codepoints.join(":"));
+        }
+
+        seq.push(entry.final_byte);
+
+        Some(seq.into_bytes())
+    }
+
+    fn encode_legacy(&self) -> Option<Vec<u8>> {
         let effective_mods = self.event.effective_mods();
 
         if self.event.action != Action::Press && self.event.action != Action::Repeat {
@@ -25,10 +166,86 @@ impl KeyEncoder {
             return None;
         }
 
-        todo!()
+        if let Some(seq) = pc_style_function_key(
+            self.event.key,
+            self.event.mods,
+            self.cursor_key_application,
+            self.keypad_key_application,
+            self.ignore_keypad_with_numlock,
+            self.modify_other_keys_state_2,
+        ) {
+            return Some(seq.into_bytes());
+        }
+
+        let alt = effective_mods.contains(Mods::ALT);
+
+        if let Some(byte) = control_key(self.event.key, self.event.mods) {
+            return Some(with_alt_prefix(&[byte], alt));
+        }
+
+        if self.event.key == Key::Tab && self.event.mods.contains(Mods::SHIFT) {
+            return Some(b"\x1b[Z".to_vec());
+        }
+
+        // Ctrl with a character key that wasn't turned into text already.
+        if effective_mods.contains(Mods::CTRL) {
+            let c = self
+                .event
+                .unshifted_char
+                .or_else(|| self.event.key.codepoint());
+            if let Some(byte) = c.and_then(ctrl_byte) {
+                return Some(with_alt_prefix(&[byte], alt));
+            }
+        }
+
+        if self.event.utf8.is_empty() {
+            return None;
+        }
+
+        // Alt sends an ESC prefix before the text.
+        Some(with_alt_prefix(self.event.utf8.as_bytes(), alt))
     }
 }
 
+/// Legacy encoding of keys that produce a single control character.
+fn control_key(key: Key, mods: Mods) -> Option<u8> {
+    let byte = match key {
+        Key::Enter => b'\r',
+        Key::Tab if !mods.contains(Mods::SHIFT) => b'\t',
+        Key::Backspace if mods.contains(Mods::CTRL) => 0x08,
+        Key::Backspace => 0x7f,
+        _ => return None,
+    };
+
+    Some(byte)
+}
+
+/// C0 control character produced by holding ctrl with `c`, e.g. `0x01` for `a`.
+fn ctrl_byte(c: char) -> Option<u8> {
+    let byte = match c.to_ascii_lowercase() {
+        c @ 'a'..='z' => c as u8 - b'a' + 1,
+        '@' | ' ' | '2' => 0x00,
+        '[' | '3' => 0x1b,
+        '\\' | '4' => 0x1c,
+        ']' | '5' => 0x1d,
+        '^' | '6' => 0x1e,
+        '_' | '-' | '7' => 0x1f,
+        '?' | '8' => 0x7f,
+        _ => return None,
+    };
+
+    Some(byte)
+}
+
+fn with_alt_prefix(bytes: &[u8], alt: bool) -> Vec<u8> {
+    let mut seq = Vec::with_capacity(bytes.len() + 1);
+    if alt {
+        seq.push(0x1b);
+    }
+    seq.extend_from_slice(bytes);
+    seq
+}
+
 /// Determines whether the key should be encoded in the xterm
 /// "PC-style Function Key" syntax (roughly). This is a hardcoded
 /// table of keys and modifiers that result in a specific sequence.
@@ -39,8 +256,482 @@ fn pc_style_function_key(
     keypad_key_application_req: bool,
     ignore_keypad_with_numlock: bool,
     modify_other_keys: bool, // True if state 2
-) -> String {
+) -> Option<String> {
     let keypad_key_application = ignore_keypad_with_numlock || keypad_key_application_req;
+    let modifier = modifier_param(mods);
+
+    // Keys with a letter terminator, e.g. `CSI A`, `SS3 A` or `CSI 1 ; mod A`.
+    let letter = |c: char, ss3: bool| match modifier {
+        Some(m) => format!("\x1b[1;{m}{c}"),
+        None if ss3 => format!("\x1bO{c}"),
+        None => format!("\x1b[{c}"),
+    };
+
+    // Keys with a `~` terminator, e.g. `CSI 5 ~` or `CSI 5 ; mod ~`.
+    let tilde = |n: u8| match modifier {
+        Some(m) => format!("\x1b[{n};{m}~"),
+        None => format!("\x1b[{n}~"),
+    };
+
+    // Keypad keys in application mode, e.g. `SS3 p`.
+    let keypad = |c: char| {
+        if !keypad_key_application || (modify_other_keys && modifier.is_some()) {
+            return None;
+        }
+
+        Some(match modifier {
+            Some(m) => format!("\x1bO{m}{c}"),
+            None => format!("\x1bO{c}"),
+        })
+    };
+
+    let seq = match keyval {
+        Key::Up | Key::KpUp => letter('A', cursor_key_application),
+        Key::Down | Key::KpDown => letter('B', cursor_key_application),
+        Key::Right | Key::KpRight => letter('C', cursor_key_application),
+        Key::Left | Key::KpLeft => letter('D', cursor_key_application),
+        Key::Home | Key::KpHome => letter('H', cursor_key_application),
+        Key::End | Key::KpEnd => letter('F', cursor_key_application),
+        Key::KpBegin => letter('E', cursor_key_application),
+
+        Key::Insert | Key::KpInsert => tilde(2),
+        Key::Delete | Key::KpDelete => tilde(3),
+        Key::PageUp | Key::KpPageUp => tilde(5),
+        Key::PageDown | Key::KpPageDown => tilde(6),
+
+        Key::F1 => letter('P', true),
+        Key::F2 => letter('Q', true),
+        Key::F3 => letter('R', true),
+        Key::F4 => letter('S', true),
+        Key::F5 => tilde(15),
+        Key::F6 => tilde(17),
+        Key::F7 => tilde(18),
+        Key::F8 => tilde(19),
+        Key::F9 => tilde(20),
+        Key::F10 => tilde(21),
+        Key::F11 => tilde(23),
+        Key::F12 => tilde(24),
+
+        Key::Kp0 => return keypad('p'),
+        Key::Kp1 => return keypad('q'),
+        Key::Kp2 => return keypad('r'),
+        Key::Kp3 => return keypad('s'),
+        Key::Kp4 => return keypad('t'),
+        Key::Kp5 => return keypad('u'),
+        Key::Kp6 => return keypad('v'),
+        Key::Kp7 => return keypad('w'),
+        Key::Kp8 => return keypad('x'),
+        Key::Kp9 => return keypad('y'),
+        Key::KpDecimal => return keypad('n'),
+        Key::KpDivide => return keypad('o'),
+        Key::KpMultiply => return keypad('j'),
+        Key::KpSubtract => return keypad('m'),
+        Key::KpAdd => return keypad('k'),
+        Key::KpEnter => return keypad('M'),
+        Key::KpEqual => return keypad('X'),
+        Key::KpSeparator => return keypad('l'),
+
+        _ => return None,
+    };
+
+    Some(seq)
+}
+
+/// Modifier parameter used by xterm, `1 + mods` where shift is 1, alt is 2,
+/// ctrl is 4 and meta is 8. Returns `None` if no modifiers are pressed.
+fn modifier_param(mods: Mods) -> Option<u8> {
+    let mut value = 0;
 
-    todo!()
+    if mods.contains(Mods::SHIFT) {
+        value |= 1;
+    }
+    if mods.contains(Mods::ALT) {
+        value |= 2;
+    }
+    if mods.contains(Mods::CTRL) {
+        value |= 4;
+    }
+    if mods.contains(Mods::META) {
+        value |= 8;
+    }
+
+    (value != 0).then_some(value + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(key: Key, mods: Mods) -> Option<Vec<u8>> {
+        KeyEncoder::new(KeyEvent {
+            key,
+            mods,
+            ..Default::default()
+        })
+        .encode()
+    }
+
+    #[test]
+    fn arrow_keys() {
+        assert_eq!(encode(Key::Up, Mods::empty()), Some(b"\x1b[A".to_vec()));
+        assert_eq!(encode(Key::Down, Mods::empty()), Some(b"\x1b[B".to_vec()));
+        assert_eq!(encode(Key::Right, Mods::empty()), Some(b"\x1b[C".to_vec()));
+        assert_eq!(encode(Key::Left, Mods::empty()), Some(b"\x1b[D".to_vec()));
+    }
+
+    #[test]
+    fn arrow_keys_with_mods() {
+        assert_eq!(encode(Key::Up, Mods::SHIFT), Some(b"\x1b[1;2A".to_vec()));
+        assert_eq!(encode(Key::Left, Mods::ALT), Some(b"\x1b[1;3D".to_vec()));
+        assert_eq!(encode(Key::Right, Mods::CTRL), Some(b"\x1b[1;5C".to_vec()));
+        assert_eq!(
+            encode(Key::Down, Mods::CTRL | Mods::SHIFT),
+            Some(b"\x1b[1;6B".to_vec())
+        );
+    }
+
+    #[test]
+    fn cursor_key_application() {
+        let mut encoder = KeyEncoder::new(KeyEvent {
+            key: Key::Up,
+            ..Default::default()
+        });
+        encoder.cursor_key_application = true;
+        assert_eq!(encoder.encode(), Some(b"\x1bOA".to_vec()));
+
+        encoder.event.key = Key::Home;
+        assert_eq!(encoder.encode(), Some(b"\x1bOH".to_vec()));
+
+        // Modifiers always use the CSI form.
+        encoder.event.mods = Mods::SHIFT;
+        assert_eq!(encoder.encode(), Some(b"\x1b[1;2H".to_vec()));
+    }
+
+    #[test]
+    fn home_end() {
+        assert_eq!(encode(Key::Home, Mods::empty()), Some(b"\x1b[H".to_vec()));
+        assert_eq!(encode(Key::End, Mods::empty()), Some(b"\x1b[F".to_vec()));
+        assert_eq!(encode(Key::End, Mods::CTRL), Some(b"\x1b[1;5F".to_vec()));
+    }
+
+    #[test]
+    fn function_keys() {
+        let expected = [
+            (Key::F1, "\x1bOP"),
+            (Key::F2, "\x1bOQ"),
+            (Key::F3, "\x1bOR"),
+            (Key::F4, "\x1bOS"),
+            (Key::F5, "\x1b[15~"),
+            (Key::F6, "\x1b[17~"),
+            (Key::F7, "\x1b[18~"),
+            (Key::F8, "\x1b[19~"),
+            (Key::F9, "\x1b[20~"),
+            (Key::F10, "\x1b[21~"),
+            (Key::F11, "\x1b[23~"),
+            (Key::F12, "\x1b[24~"),
+        ];
+
+        for (key, seq) in expected {
+            assert_eq!(
+                encode(key, Mods::empty()).as_deref(),
+                Some(seq.as_bytes()),
+                "{key:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn function_keys_with_mods() {
+        assert_eq!(encode(Key::F1, Mods::SHIFT), Some(b"\x1b[1;2P".to_vec()));
+        assert_eq!(encode(Key::F5, Mods::CTRL), Some(b"\x1b[15;5~".to_vec()));
+    }
+
+    #[test]
+    fn ctrl_letters() {
+        for (i, key) in [Key::A, Key::B, Key::M, Key::Z].into_iter().enumerate() {
+            let expected = [1, 2, 13, 26][i];
+            assert_eq!(encode(key, Mods::CTRL), Some(vec![expected]));
+        }
+
+        assert_eq!(
+            encode(Key::C, Mods::CTRL | Mods::ALT),
+            Some(b"\x1b\x03".to_vec())
+        );
+        assert_eq!(encode(Key::LeftBracket, Mods::CTRL), Some(b"\x1b".to_vec()));
+        assert_eq!(encode(Key::Space, Mods::CTRL), Some(b"\x00".to_vec()));
+    }
+
+    #[test]
+    fn control_keys() {
+        assert_eq!(encode(Key::Enter, Mods::empty()), Some(b"\r".to_vec()));
+        assert_eq!(encode(Key::Enter, Mods::ALT), Some(b"\x1b\r".to_vec()));
+        assert_eq!(encode(Key::Tab, Mods::empty()), Some(b"\t".to_vec()));
+        assert_eq!(encode(Key::Tab, Mods::SHIFT), Some(b"\x1b[Z".to_vec()));
+        assert_eq!(
+            encode(Key::Backspace, Mods::empty()),
+            Some(b"\x7f".to_vec())
+        );
+        assert_eq!(encode(Key::Backspace, Mods::CTRL), Some(b"\x08".to_vec()));
+    }
+
+    #[test]
+    fn shifted_text() {
+        let encoder = KeyEncoder::new(KeyEvent {
+            key: Key::A,
+            mods: Mods::SHIFT,
+            consumed_mods: Mods::SHIFT,
+            utf8: String::from("A"),
+            ..Default::default()
+        });
+
+        assert_eq!(encoder.encode(), Some(b"A".to_vec()));
+    }
+
+    #[test]
+    fn release_is_not_encoded() {
+        let encoder = KeyEncoder::new(KeyEvent {
+            action: Action::Release,
+            key: Key::Up,
+            ..Default::default()
+        });
+
+        assert_eq!(encoder.encode(), None);
+    }
+
+    fn encode_kitty(event: KeyEvent, modes: KeyboardModes) -> Option<Vec<u8>> {
+        KeyEncoder::new(event).encode_kitty(modes)
+    }
+
+    #[test]
+    fn kitty_ctrl_a() {
+        let event = KeyEvent {
+            key: Key::A,
+            physical_key: Key::A,
+            mods: Mods::CTRL,
+            utf8: String::from("\x01"),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            encode_kitty(event, KeyboardModes::DISAMBIGUATE_ESC_CODES),
+            Some(b"\x1b[97;5u".to_vec())
+        );
+    }
+
+    #[test]
+    fn kitty_plain_text() {
+        let event = KeyEvent {
+            key: Key::A,
+            physical_key: Key::A,
+            mods: Mods::SHIFT,
+            consumed_mods: Mods::SHIFT,
+            utf8: String::from("A"),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            encode_kitty(event, KeyboardModes::DISAMBIGUATE_ESC_CODES),
+            Some(b"A".to_vec())
+        );
+    }
+
+    #[test]
+    fn kitty_escape() {
+        let event = KeyEvent {
+            key: Key::Escape,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            encode_kitty(event, KeyboardModes::DISAMBIGUATE_ESC_CODES),
+            Some(b"\x1b[27u".to_vec())
+        );
+    }
+
+    #[test]
+    fn kitty_release() {
+        let event = KeyEvent {
+            action: Action::Release,
+            key: Key::A,
+            physical_key: Key::A,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            encode_kitty(event.clone(), KeyboardModes::DISAMBIGUATE_ESC_CODES),
+            None
+        );
+        assert_eq!(
+            encode_kitty(
+                event,
+                KeyboardModes::DISAMBIGUATE_ESC_CODES | KeyboardModes::REPORT_EVENT_TYPES
+            ),
+            Some(b"\x1b[97;1:3u".to_vec())
+        );
+    }
+
+    #[test]
+    fn kitty_repeat_functional_key() {
+        let event = KeyEvent {
+            action: Action::Repeat,
+            key: Key::Up,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            encode_kitty(event, KeyboardModes::REPORT_EVENT_TYPES),
+            Some(b"\x1b[1;1:2A".to_vec())
+        );
+    }
+
+    #[test]
+    fn kitty_alternate_keys() {
+        let modes = KeyboardModes::DISAMBIGUATE_ESC_CODES
+            | KeyboardModes::REPORT_ALTERNATE_KEYS
+            | KeyboardModes::REPORT_ALL_KEYS_AS_ESC;
+
+        // Shifted key.
+        let event = KeyEvent {
+            key: Key::A,
+            physical_key: Key::A,
+            mods: Mods::SHIFT,
+            consumed_mods: Mods::SHIFT,
+            utf8: String::from("A"),
+            ..Default::default()
+        };
+        assert_eq!(encode_kitty(event, modes), Some(b"\x1b[97:65;2u".to_vec()));
+
+        // Base layout key differs, e.g. `c` on dvorak is physical `i`.
+        let event = KeyEvent {
+            key: Key::C,
+            physical_key: Key::I,
+            mods: Mods::CTRL,
+            ..Default::default()
+        };
+        assert_eq!(
+            encode_kitty(event, modes),
+            Some(b"\x1b[99::105;5u".to_vec())
+        );
+
+        // Both shifted and base layout keys.
+        let event = KeyEvent {
+            key: Key::C,
+            physical_key: Key::I,
+            mods: Mods::SHIFT,
+            consumed_mods: Mods::SHIFT,
+            utf8: String::from("C"),
+            ..Default::default()
+        };
+        assert_eq!(
+            encode_kitty(event, modes),
+            Some(b"\x1b[99:67:105;2u".to_vec())
+        );
+    }
+
+    #[test]
+    fn kitty_associated_text() {
+        let event = KeyEvent {
+            key: Key::A,
+            physical_key: Key::A,
+            utf8: String::from("a"),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            encode_kitty(
+                event,
+                KeyboardModes::REPORT_ALL_KEYS_AS_ESC | KeyboardModes::REPORT_ASSOCIATED_TEXT
+            ),
+            Some(b"\x1b[97;1;97u".to_vec())
+        );
+    }
+
+    #[test]
+    fn kitty_associated_text_with_modifiers() {
+        let modes = KeyboardModes::REPORT_ALL_KEYS_AS_ESC | KeyboardModes::REPORT_ASSOCIATED_TEXT;
+
+        let shifted = KeyEvent {
+            key: Key::A,
+            physical_key: Key::A,
+            mods: Mods::SHIFT,
+            consumed_mods: Mods::SHIFT,
+            utf8: String::from("A"),
+            unshifted_char: Some('a'),
+            ..Default::default()
+        };
+        assert_eq!(
+            encode_kitty(shifted, modes),
+            Some(b"\x1b[97;2;65u".to_vec())
+        );
+
+        // Text made of several codepoints, e.g. from a dead key.
+        let composed = KeyEvent {
+            key: Key::E,
+            physical_key: Key::E,
+            utf8: String::from("e\u{301}"),
+            ..Default::default()
+        };
+        assert_eq!(
+            encode_kitty(composed, modes),
+            Some(b"\x1b[101;1;101:769u".to_vec())
+        );
+    }
+
+    #[test]
+    fn kitty_associated_text_not_reported() {
+        let event = KeyEvent {
+            key: Key::A,
+            physical_key: Key::A,
+            utf8: String::from("a"),
+            ..Default::default()
+        };
+
+        // The flag has no effect unless all keys are reported as escape codes.
+        assert_eq!(
+            encode_kitty(event.clone(), KeyboardModes::REPORT_ASSOCIATED_TEXT),
+            Some(b"a".to_vec())
+        );
+
+        // Releases and control characters carry no text.
+        let release = KeyEvent {
+            action: Action::Release,
+            ..event
+        };
+        assert_eq!(
+            encode_kitty(
+                release,
+                KeyboardModes::REPORT_ALL_KEYS_AS_ESC
+                    | KeyboardModes::REPORT_EVENT_TYPES
+                    | KeyboardModes::REPORT_ASSOCIATED_TEXT
+            ),
+            Some(b"\x1b[97;1:3u".to_vec())
+        );
+
+        let ctrl = KeyEvent {
+            key: Key::A,
+            physical_key: Key::A,
+            mods: Mods::CTRL,
+            utf8: String::from("\x01"),
+            ..Default::default()
+        };
+        assert_eq!(
+            encode_kitty(
+                ctrl,
+                KeyboardModes::REPORT_ALL_KEYS_AS_ESC | KeyboardModes::REPORT_ASSOCIATED_TEXT
+            ),
+            Some(b"\x1b[97;5u".to_vec())
+        );
+    }
+
+    #[test]
+    fn kitty_mode_from_encoder() {
+        let mut encoder = KeyEncoder::new(KeyEvent {
+            key: Key::Escape,
+            ..Default::default()
+        });
+        assert_eq!(encoder.encode(), None);
+
+        encoder.keyboard_modes = KeyboardModes::DISAMBIGUATE_ESC_CODES;
+        assert_eq!(encoder.encode(), Some(b"\x1b[27u".to_vec()));
+    }
 }
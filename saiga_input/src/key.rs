@@ -1,5 +1,6 @@
 use bitflags::bitflags;
 
+#[derive(Debug, Clone)]
 pub struct KeyEvent {
     pub action: Action,
 
@@ -30,6 +31,10 @@ pub struct KeyEvent {
     /// If composing is true and this is non-empty, this is preedit
     /// text.
     pub utf8: String,
+
+    /// The codepoint of the key without any modifiers applied, used by the
+    /// kitty keyboard protocol to identify text keys.
+    pub unshifted_char: Option<char>,
 }
 
 impl Default for KeyEvent {
@@ -42,6 +47,7 @@ impl Default for KeyEvent {
             composing: false,
             consumed_mods: Mods::empty(),
             utf8: String::new(),
+            unshifted_char: None,
         }
     }
 }
@@ -56,6 +62,66 @@ impl KeyEvent {
     }
 }
 
+impl Key {
+    /// The character produced by the key on a US layout without modifiers.
+    pub fn codepoint(self) -> Option<char> {
+        let c = match self {
+            Key::A => 'a',
+            Key::B => 'b',
+            Key::C => 'c',
+            Key::D => 'd',
+            Key::E => 'e',
+            Key::F => 'f',
+            Key::G => 'g',
+            Key::H => 'h',
+            Key::I => 'i',
+            Key::J => 'j',
+            Key::K => 'k',
+            Key::L => 'l',
+            Key::M => 'm',
+            Key::N => 'n',
+            Key::O => 'o',
+            Key::P => 'p',
+            Key::Q => 'q',
+            Key::R => 'r',
+            Key::S => 's',
+            Key::T => 't',
+            Key::U => 'u',
+            Key::V => 'v',
+            Key::W => 'w',
+            Key::X => 'x',
+            Key::Y => 'y',
+            Key::Z => 'z',
+            Key::Zero => '0',
+            Key::One => '1',
+            Key::Two => '2',
+            Key::Three => '3',
+            Key::Four => '4',
+            Key::Five => '5',
+            Key::Six => '6',
+            Key::Seven => '7',
+            Key::Eight => '8',
+            Key::Nine => '9',
+            Key::Semicolon => ';',
+            Key::Space => ' ',
+            Key::Apostrophe => '\'',
+            Key::Comma => ',',
+            Key::GraveAccent => '`',
+            Key::Period => '.',
+            Key::Slash => '/',
+            Key::Minus => '-',
+            Key::Plus => '+',
+            Key::Equal => '=',
+            Key::LeftBracket => '[',
+            Key::RightBracket => ']',
+            Key::Backslash => '\\',
+            _ => return None,
+        };
+
+        Some(c)
+    }
+}
+
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct Mods: u8 {
@@ -73,6 +139,7 @@ pub enum Action {
     Repeat,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Key {
     Invalid,
     // a-z
@@ -0,0 +1,110 @@
+use crate::key::Key;
+
+/// Key code and final byte of a functional key in the kitty keyboard protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Entry {
+    pub code: u32,
+    pub final_byte: char,
+}
+
+impl Entry {
+    pub(crate) const fn new(code: u32, final_byte: char) -> Self {
+        Self { code, final_byte }
+    }
+}
+
+/// Returns the kitty encoding of keys that don't produce text.
+///
+/// See <https://sw.kovidgoyal.net/kitty/keyboard-protocol/#functional-key-definitions>.
+pub(crate) fn functional_key(key: Key) -> Option<Entry> {
+    let entry = match key {
+        Key::Escape => Entry::new(27, 'u'),
+        Key::Enter => Entry::new(13, 'u'),
+        Key::Tab => Entry::new(9, 'u'),
+        Key::Backspace => Entry::new(127, 'u'),
+        Key::Insert => Entry::new(2, '~'),
+        Key::Delete => Entry::new(3, '~'),
+        Key::Left => Entry::new(1, 'D'),
+        Key::Right => Entry::new(1, 'C'),
+        Key::Up => Entry::new(1, 'A'),
+        Key::Down => Entry::new(1, 'B'),
+        Key::PageUp => Entry::new(5, '~'),
+        Key::PageDown => Entry::new(6, '~'),
+        Key::Home => Entry::new(1, 'H'),
+        Key::End => Entry::new(1, 'F'),
+        Key::CapsLock => Entry::new(57358, 'u'),
+        Key::ScrollLock => Entry::new(57359, 'u'),
+        Key::NumLock => Entry::new(57360, 'u'),
+        Key::PrintScreen => Entry::new(57361, 'u'),
+        Key::Pause => Entry::new(57362, 'u'),
+
+        Key::F1 => Entry::new(1, 'P'),
+        Key::F2 => Entry::new(1, 'Q'),
+        Key::F3 => Entry::new(13, '~'),
+        Key::F4 => Entry::new(1, 'S'),
+        Key::F5 => Entry::new(15, '~'),
+        Key::F6 => Entry::new(17, '~'),
+        Key::F7 => Entry::new(18, '~'),
+        Key::F8 => Entry::new(19, '~'),
+        Key::F9 => Entry::new(20, '~'),
+        Key::F10 => Entry::new(21, '~'),
+        Key::F11 => Entry::new(23, '~'),
+        Key::F12 => Entry::new(24, '~'),
+        Key::F13 => Entry::new(57376, 'u'),
+        Key::F14 => Entry::new(57377, 'u'),
+        Key::F15 => Entry::new(57378, 'u'),
+        Key::F16 => Entry::new(57379, 'u'),
+        Key::F17 => Entry::new(57380, 'u'),
+        Key::F18 => Entry::new(57381, 'u'),
+        Key::F19 => Entry::new(57382, 'u'),
+        Key::F20 => Entry::new(57383, 'u'),
+        Key::F21 => Entry::new(57384, 'u'),
+        Key::F22 => Entry::new(57385, 'u'),
+        Key::F23 => Entry::new(57386, 'u'),
+        Key::F24 => Entry::new(57387, 'u'),
+        Key::F25 => Entry::new(57388, 'u'),
+
+        Key::Kp0 => Entry::new(57399, 'u'),
+        Key::Kp1 => Entry::new(57400, 'u'),
+        Key::Kp2 => Entry::new(57401, 'u'),
+        Key::Kp3 => Entry::new(57402, 'u'),
+        Key::Kp4 => Entry::new(57403, 'u'),
+        Key::Kp5 => Entry::new(57404, 'u'),
+        Key::Kp6 => Entry::new(57405, 'u'),
+        Key::Kp7 => Entry::new(57406, 'u'),
+        Key::Kp8 => Entry::new(57407, 'u'),
+        Key::Kp9 => Entry::new(57408, 'u'),
+        Key::KpDecimal => Entry::new(57409, 'u'),
+        Key::KpDivide => Entry::new(57410, 'u'),
+        Key::KpMultiply => Entry::new(57411, 'u'),
+        Key::KpSubtract => Entry::new(57412, 'u'),
+        Key::KpAdd => Entry::new(57413, 'u'),
+        Key::KpEnter => Entry::new(57414, 'u'),
+        Key::KpEqual => Entry::new(57415, 'u'),
+        Key::KpSeparator => Entry::new(57416, 'u'),
+        Key::KpLeft => Entry::new(57417, 'u'),
+        Key::KpRight => Entry::new(57418, 'u'),
+        Key::KpUp => Entry::new(57419, 'u'),
+        Key::KpDown => Entry::new(57420, 'u'),
+        Key::KpPageUp => Entry::new(57421, 'u'),
+        Key::KpPageDown => Entry::new(57422, 'u'),
+        Key::KpHome => Entry::new(57423, 'u'),
+        Key::KpEnd => Entry::new(57424, 'u'),
+        Key::KpInsert => Entry::new(57425, 'u'),
+        Key::KpDelete => Entry::new(57426, 'u'),
+        Key::KpBegin => Entry::new(57427, 'u'),
+
+        Key::LeftShift => Entry::new(57441, 'u'),
+        Key::LeftControl => Entry::new(57442, 'u'),
+        Key::LeftAlt => Entry::new(57443, 'u'),
+        Key::LeftSuper => Entry::new(57444, 'u'),
+        Key::RightShift => Entry::new(57447, 'u'),
+        Key::RightControl => Entry::new(57448, 'u'),
+        Key::RightAlt => Entry::new(57449, 'u'),
+        Key::RightSuper => Entry::new(57450, 'u'),
+
+        _ => return None,
+    };
+
+    Some(entry)
+}
@@ -1,2 +1,4 @@
 pub mod encoder;
 pub mod key;
+
+mod kitty;
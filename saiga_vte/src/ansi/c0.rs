@@ -1,9 +1,5 @@
 //! C0 set of 7-bit control characters (from ANSI X3.4-1977).
-use std::{
-    collections::HashSet,
-    simd::{cmp::SimdPartialEq, num::SimdUint, u8x16, Simd},
-    sync::LazyLock,
-};
+use core::simd::{cmp::SimdPartialEq, num::SimdUint, u8x16, Simd};
 
 /// Null filler, terminal should ignore this character.
 pub const NUL: u8 = 0x00;
@@ -76,8 +72,6 @@ const IN_USE: [u8; 11] = [NUL, BEL, BS, HT, LF, VT, FF, CR, SO, SI, ESC];
 
 const LANES: usize = 16;
 
-static C0_SET: LazyLock<HashSet<u8>> = LazyLock::new(|| IN_USE.into_iter().collect());
-
 pub fn first_index_of_c0(haystack: &[u8]) -> Option<usize> {
     const INDICES: Simd<u8, LANES> =
         u8x16::from_array([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
@@ -136,7 +130,7 @@ pub fn first_index_of_c0(haystack: &[u8]) -> Option<usize> {
 
 fn first_index_of_c0_scalar(haystack: &[u8]) -> Option<usize> {
     for (i, b) in haystack.iter().enumerate() {
-        if C0_SET.contains(b) {
+        if IN_USE.contains(b) {
             return Some(i);
         }
     }
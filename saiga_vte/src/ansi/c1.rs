@@ -0,0 +1,10 @@
+//! C1 set of 8-bit control characters (from ANSI X3.64-1979).
+
+/// Index, move to the same position on the next line.
+pub const IND: u8 = 0x84;
+/// Next Line, move to the first position of the next line.
+pub const NEL: u8 = 0x85;
+/// Horizontal Tabulation Set, set a tab stop at the current position.
+pub const HTS: u8 = 0x88;
+/// Reverse Index, move to the same position on the previous line.
+pub const RI: u8 = 0x8D;
@@ -1,8 +1,14 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::{Display, Formatter};
+use core::ops::{Add, Mul, Sub};
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
 use bitflags::bitflags;
-use std::fmt;
-use std::fmt::{Display, Formatter};
-use std::ops::{Add, Mul, Sub};
-use std::str::FromStr;
 
 /// Terminal character attributes.
 #[derive(Debug, Eq, PartialEq)]
@@ -59,6 +65,65 @@ pub enum Attribute {
     UnderlineColor(Option<Color>),
 }
 
+impl Attribute {
+    /// Encode the attribute as a `CSI ... m` sequence.
+    ///
+    /// Truecolor and indexed colors use the `38;2;r;g;b` and `38;5;n` forms, as do named underline
+    /// colors. Returns `None` for colors without an SGR encoding, such as the cursor or dim colors.
+    pub fn to_sgr(&self) -> Option<Vec<u8>> {
+        let params = match self {
+            Attribute::Reset => "0".into(),
+            Attribute::Bold => "1".into(),
+            Attribute::Dim => "2".into(),
+            Attribute::Italic => "3".into(),
+            Attribute::Underline => "4".into(),
+            Attribute::DoubleUnderline => "4:2".into(),
+            Attribute::Undercurl => "4:3".into(),
+            Attribute::DottedUnderline => "4:4".into(),
+            Attribute::DashedUnderline => "4:5".into(),
+            Attribute::BlinkSlow => "5".into(),
+            Attribute::BlinkFast => "6".into(),
+            Attribute::Reverse => "7".into(),
+            Attribute::Hidden => "8".into(),
+            Attribute::Strike => "9".into(),
+            Attribute::CancelBold => "21".into(),
+            Attribute::CancelBoldDim => "22".into(),
+            Attribute::CancelItalic => "23".into(),
+            Attribute::CancelUnderline => "24".into(),
+            Attribute::CancelBlink => "25".into(),
+            Attribute::CancelReverse => "27".into(),
+            Attribute::CancelHidden => "28".into(),
+            Attribute::CancelStrike => "29".into(),
+            Attribute::Foreground(Color::Named(NamedColor::Foreground)) => "39".into(),
+            Attribute::Foreground(color) => sgr_color(*color, Some(30), 38)?,
+            Attribute::Background(Color::Named(NamedColor::Background)) => "49".into(),
+            Attribute::Background(color) => sgr_color(*color, Some(40), 48)?,
+            Attribute::UnderlineColor(None) => "59".into(),
+            Attribute::UnderlineColor(Some(color)) => sgr_color(*color, None, 58)?,
+        };
+
+        Some(format!("\x1b[{params}m").into_bytes())
+    }
+}
+
+/// SGR parameters selecting `color`, given the code of the first named color and the extended
+/// color code.
+///
+/// Without a named code, the 16 named colors fall back to their `extended;5;n` index. Returns
+/// `None` for the remaining named colors, which have no SGR encoding.
+pub fn sgr_color(color: Color, named: Option<u16>, extended: u16) -> Option<String> {
+    match color {
+        Color::Named(color) => match (color as u16, named) {
+            (index @ 0..=7, Some(normal)) => Some((normal + index).to_string()),
+            (index @ 8..=15, Some(normal)) => Some((normal + 60 + index - 8).to_string()),
+            (index @ 0..=15, None) => Some(format!("{extended};5;{index}")),
+            _ => None,
+        },
+        Color::Spec(Rgb { r, g, b }) => Some(format!("{extended};2;{r};{g};{b}")),
+        Color::Indexed(index) => Some(format!("{extended};5;{index}")),
+    }
+}
+
 /// Wrapper for the ANSI modes.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Mode {
@@ -115,10 +180,12 @@ impl PrivateMode {
         match mode {
             1 => Self::Named(NamedPrivateMode::CursorKeys),
             3 => Self::Named(NamedPrivateMode::ColumnMode),
+            5 => Self::Named(NamedPrivateMode::ReverseVideo),
             6 => Self::Named(NamedPrivateMode::Origin),
             7 => Self::Named(NamedPrivateMode::LineWrap),
             12 => Self::Named(NamedPrivateMode::BlinkingCursor),
             25 => Self::Named(NamedPrivateMode::ShowCursor),
+            47 => Self::Named(NamedPrivateMode::SwapScreen),
             1000 => Self::Named(NamedPrivateMode::ReportMouseClicks),
             1002 => Self::Named(NamedPrivateMode::ReportCellMouseMotion),
             1003 => Self::Named(NamedPrivateMode::ReportAllMouseMotion),
@@ -127,6 +194,7 @@ impl PrivateMode {
             1006 => Self::Named(NamedPrivateMode::SgrMouse),
             1007 => Self::Named(NamedPrivateMode::AlternateScroll),
             1042 => Self::Named(NamedPrivateMode::UrgencyHints),
+            1047 => Self::Named(NamedPrivateMode::SwapScreenAndClear),
             1049 => Self::Named(NamedPrivateMode::SwapScreenAndSetRestoreCursor),
             2004 => Self::Named(NamedPrivateMode::BracketedPaste),
             2026 => Self::Named(NamedPrivateMode::SyncUpdate),
@@ -159,10 +227,14 @@ pub enum NamedPrivateMode {
     /// * resets DECLRMM to unavailable
     /// * clears data from the status line (if set to host-writable)
     ColumnMode = 3,
+    /// Reverse video of the whole screen (DECSCNM).
+    ReverseVideo = 5,
     Origin = 6,
     LineWrap = 7,
     BlinkingCursor = 12,
     ShowCursor = 25,
+    /// Legacy alternate screen buffer switch.
+    SwapScreen = 47,
     ReportMouseClicks = 1000,
     ReportCellMouseMotion = 1002,
     ReportAllMouseMotion = 1003,
@@ -171,6 +243,8 @@ pub enum NamedPrivateMode {
     SgrMouse = 1006,
     AlternateScroll = 1007,
     UrgencyHints = 1042,
+    /// Alternate screen buffer switch, without saving the cursor.
+    SwapScreenAndClear = 1047,
     SwapScreenAndSetRestoreCursor = 1049,
     BracketedPaste = 2004,
     /// The mode is handled automatically by [`Processor`].
@@ -271,6 +345,19 @@ pub enum KeyboardModesApplyBehavior {
     Difference,
 }
 
+/// Setting queried by DECRQSS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusStringRequest {
+    /// Graphic rendition (SGR).
+    Sgr,
+    /// Top and bottom margins (DECSTBM).
+    ScrollingRegion,
+    /// Cursor style (DECSCUSR).
+    CursorStyle,
+    /// Unsupported setting.
+    Unknown(Vec<u8>),
+}
+
 /// Terminal cursor configuration.
 #[derive(Default, Debug, Eq, PartialEq, Copy, Clone, Hash)]
 pub struct CursorStyle {
@@ -593,22 +680,93 @@ impl Sub<Rgb> for Rgb {
     }
 }
 
+/// RGB color with an alpha channel.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Rgba {
+    pub fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Color without the alpha channel.
+    pub fn rgb(self) -> Rgb {
+        Rgb::new(self.r, self.g, self.b)
+    }
+}
+
+impl From<Rgb> for Rgba {
+    fn from(rgb: Rgb) -> Self {
+        Self::new(rgb.r, rgb.g, rgb.b, u8::MAX)
+    }
+}
+
 impl Display for Rgb {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
     }
 }
 
+impl Rgb {
+    /// Default value of an ANSI color name, such as `red` or `bright_blue`.
+    fn from_name(name: &str) -> Option<Rgb> {
+        let rgb = match name.to_ascii_lowercase().as_str() {
+            "black" => Rgb::new(0x00, 0x00, 0x00),
+            "red" => Rgb::new(0xcd, 0x00, 0x00),
+            "green" => Rgb::new(0x00, 0xcd, 0x00),
+            "yellow" => Rgb::new(0xcd, 0xcd, 0x00),
+            "blue" => Rgb::new(0x00, 0x00, 0xee),
+            "magenta" => Rgb::new(0xcd, 0x00, 0xcd),
+            "cyan" => Rgb::new(0x00, 0xcd, 0xcd),
+            "white" => Rgb::new(0xe5, 0xe5, 0xe5),
+            "bright_black" => Rgb::new(0x7f, 0x7f, 0x7f),
+            "bright_red" => Rgb::new(0xff, 0x00, 0x00),
+            "bright_green" => Rgb::new(0x00, 0xff, 0x00),
+            "bright_yellow" => Rgb::new(0xff, 0xff, 0x00),
+            "bright_blue" => Rgb::new(0x5c, 0x5c, 0xff),
+            "bright_magenta" => Rgb::new(0xff, 0x00, 0xff),
+            "bright_cyan" => Rgb::new(0x00, 0xff, 0xff),
+            "bright_white" => Rgb::new(0xff, 0xff, 0xff),
+            _ => return None,
+        };
+
+        Some(rgb)
+    }
+
+    /// Parse the functional `rgb(r, g, b)` notation with decimal components.
+    fn from_function(s: &str) -> Option<Rgb> {
+        let components = s.strip_prefix("rgb(")?.strip_suffix(')')?;
+
+        let mut components = components.split(',').map(|c| c.trim().parse::<u8>().ok());
+        let rgb = Rgb::new(
+            components.next()??,
+            components.next()??,
+            components.next()??,
+        );
+
+        components.next().is_none().then_some(rgb)
+    }
+}
+
 impl FromStr for Rgb {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Rgb, ()> {
+        let s = s.trim();
+
         let chars = if s.starts_with("0x") && s.len() == 8 {
             &s[2..]
         } else if s.starts_with('#') && s.len() == 7 {
             &s[1..]
         } else {
-            return Err(());
+            return Rgb::from_function(s)
+                .or_else(|| Rgb::from_name(s))
+                .ok_or(());
         };
 
         match u32::from_str_radix(chars, 16) {
@@ -625,6 +783,27 @@ impl FromStr for Rgb {
     }
 }
 
+/// Device attributes report requested with DA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceAttributes {
+    /// Primary device attributes (`CSI c` or `ESC Z`), the terminal's class and features.
+    Primary,
+    /// Secondary device attributes (`CSI > c`), the terminal's type and version.
+    Secondary,
+}
+
+/// Rectangular area addressed by the DEC rectangular area operations.
+///
+/// Coordinates are 1-based and inclusive, as they appear in the escape sequence. A missing
+/// bottom or right edge extends the area to the last line or column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rectangle {
+    pub top: usize,
+    pub left: usize,
+    pub bottom: Option<usize>,
+    pub right: Option<usize>,
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Direction {
     Up,
@@ -673,9 +852,32 @@ pub trait Handler {
     /// Set the cursor shape.
     fn set_cursor_shape(&mut self, _shape: CursorShape) {}
 
+    /// OSC to set the mouse pointer shape, using names like `pointer` or `text`.
+    fn set_mouse_cursor_shape(&mut self, _shape: &str) {}
+
     /// A character to be displayed.
     fn input(&mut self, _c: char) {}
 
+    /// Display `c` `count` times, for REP.
+    ///
+    /// Every repetition goes through [`Handler::input`], so wide characters and wrapping behave
+    /// like regular input.
+    fn repeat(&mut self, c: char, count: usize) {
+        for _ in 0..count {
+            self.input(c);
+        }
+    }
+
+    /// A run of characters to be displayed.
+    ///
+    /// Override it to write the whole run at once instead of going through [`Handler::input`]
+    /// for every character.
+    fn input_str(&mut self, s: &str) {
+        for c in s.chars() {
+            self.input(c);
+        }
+    }
+
     /// Set cursor to position.
     fn goto(&mut self, _line: i32, _col: usize) {}
 
@@ -695,11 +897,21 @@ pub trait Handler {
     fn move_down(&mut self, _: usize) {}
 
     /// Identify the terminal (should write back to the pty stream).
-    fn identify_terminal(&mut self, _intermediate: Option<char>) {}
+    fn identify_terminal(&mut self, _attributes: DeviceAttributes) {}
+
+    /// Report the terminal unit ID for tertiary device attributes (`CSI = c`).
+    fn identify_terminal_tertiary(&mut self) {}
 
     /// Report device status.
     fn device_status(&mut self, _: usize) {}
 
+    /// Report DEC private device status.
+    fn private_device_status(&mut self, _: usize) {}
+
+    /// Report the cursor position (CPR), or the extended report with the page
+    /// number (DECXCPR) for the private `CSI ? 6 n` request.
+    fn report_cursor_position(&mut self, _extended: bool) {}
+
     /// Move cursor forward `cols`.
     fn move_forward(&mut self, _col: usize) {}
 
@@ -726,7 +938,7 @@ pub trait Handler {
 
     /// Ring the bell.
     ///
-    /// Hopefully this is never implemented.
+    /// The terminal may surface it audibly or visually.
     fn bell(&mut self) {}
 
     /// Substitute char under cursor.
@@ -756,6 +968,12 @@ pub trait Handler {
     /// no mode flags).
     fn erase_chars(&mut self, _: usize) {}
 
+    /// DECFRA - Fill a rectangular area with `c` using the current attributes.
+    fn fill_rectangle(&mut self, _c: char, _area: Rectangle) {}
+
+    /// DECRQCRA - Report the checksum of a rectangular area, tagged with the request `id`.
+    fn report_rectangle_checksum(&mut self, _id: usize, _area: Rectangle) {}
+
     /// Delete `count` chars.
     ///
     /// Deleting a character is like the delete key on the keyboard - everything
@@ -838,6 +1056,14 @@ pub trait Handler {
     /// Set an indexed color value.
     fn set_color(&mut self, _: usize, _: Rgb) {}
 
+    /// Set an indexed color value with transparency.
+    ///
+    /// Colors set without an alpha channel are opaque. Handlers which don't support transparency
+    /// only receive the color through `set_color`.
+    fn set_color_rgba(&mut self, index: usize, color: Rgba) {
+        self.set_color(index, color.rgb());
+    }
+
     /// Respond to a color query escape sequence.
     fn dynamic_color_sequence(&mut self, _: String, _: usize, _: &str) {}
 
@@ -859,6 +1085,12 @@ pub trait Handler {
     /// Pop the last title from the stack.
     fn pop_title(&mut self) {}
 
+    /// Report whether the window is iconified.
+    fn report_window_state(&mut self) {}
+
+    /// Report the position of the window on the screen in pixels.
+    fn report_window_position(&mut self) {}
+
     /// Report text area size in pixels.
     fn text_area_size_pixels(&mut self) {}
 
@@ -868,6 +1100,25 @@ pub trait Handler {
     /// Set hyperlink.
     fn set_hyperlink(&mut self, _: Option<Hyperlink>) {}
 
+    /// Set the working directory reported by the shell.
+    #[cfg(feature = "std")]
+    fn set_current_directory(&mut self, _path: Option<PathBuf>) {}
+
+    /// Show a desktop notification.
+    fn report_notification(&mut self, _title: Option<String>, _body: String) {}
+
+    /// Report the value of a setting.
+    ///
+    /// The output is of form `DCS 1 $ r setting ST`, or `DCS 0 $ r ST` for an invalid request.
+    fn report_status_string(&mut self, _request: StatusStringRequest) {}
+
+    /// Report terminfo capabilities requested with XTGETTCAP.
+    ///
+    /// Names which aren't valid hex are passed as empty strings. Every capability is answered
+    /// with `DCS 1 + r name=value ST`, using hex encoding for both name and value, or
+    /// `DCS 0 + r ST` when it is unknown.
+    fn report_termcap(&mut self, _names: &[String]) {}
+
     /// Report current keyboard mode.
     fn report_keyboard_mode(&mut self) {}
 
@@ -895,3 +1146,47 @@ pub trait Handler {
     // Set SCP control.
     fn set_scp(&mut self, _char_path: ScpCharPath, _update_mode: ScpUpdateMode) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgb_from_hex() {
+        assert_eq!("#11aaff".parse(), Ok(Rgb::new(0x11, 0xaa, 0xff)));
+        assert_eq!("0x11aaff".parse(), Ok(Rgb::new(0x11, 0xaa, 0xff)));
+    }
+
+    #[test]
+    fn rgb_from_function() {
+        assert_eq!("rgb(255, 0, 128)".parse(), Ok(Rgb::new(255, 0, 128)));
+        assert_eq!("rgb(1,2,3)".parse(), Ok(Rgb::new(1, 2, 3)));
+        assert_eq!("  rgb( 1 , 2 , 3 ) ".parse(), Ok(Rgb::new(1, 2, 3)));
+    }
+
+    #[test]
+    fn rgb_from_name() {
+        assert_eq!("red".parse(), Ok(Rgb::new(0xcd, 0x00, 0x00)));
+        assert_eq!("Bright_Blue".parse(), Ok(Rgb::new(0x5c, 0x5c, 0xff)));
+        assert_eq!("white".parse(), Ok(Rgb::new(0xe5, 0xe5, 0xe5)));
+    }
+
+    #[test]
+    fn rgb_from_malformed() {
+        for input in [
+            "",
+            "#11aaf",
+            "#11aafg",
+            "0x11aaffff",
+            "rgb(256, 0, 0)",
+            "rgb(-1, 0, 0)",
+            "rgb(1, 2)",
+            "rgb(1, 2, 3, 4)",
+            "rgb(1, 2, 3",
+            "rgb(a, b, c)",
+            "orange",
+        ] {
+            assert_eq!(input.parse::<Rgb>(), Err(()), "{input}");
+        }
+    }
+}
@@ -1,3 +1,4 @@
 pub mod c0;
+pub mod c1;
 pub mod handler;
 pub mod processor;
@@ -1,32 +1,39 @@
-use std::{
-    fmt::Write,
-    iter,
-    time::{Duration, Instant},
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec::Vec,
 };
+use core::{fmt::Write, time::Duration};
+#[cfg(feature = "std")]
+use std::{path::PathBuf, sync::OnceLock, time::Instant};
 
 use log::debug;
 
 use super::{
-    c0,
-    handler::{Charset, CharsetIndex, Handler, Rgb},
+    c0, c1,
+    handler::{Charset, CharsetIndex, Handler, Rgb, Rgba},
 };
 use crate::{
     ansi::handler::{
-        Attribute, Color, CursorShape, CursorStyle, Hyperlink, KeyboardModes,
+        Attribute, Color, CursorShape, CursorStyle, DeviceAttributes, Hyperlink, KeyboardModes,
         KeyboardModesApplyBehavior, LineClearMode, Mode, ModifyOtherKeys, NamedColor,
-        NamedPrivateMode, PrivateMode, ScpCharPath, ScpUpdateMode, ScreenClearMode,
-        TabulationClearMode,
+        NamedPrivateMode, PrivateMode, Rectangle, ScpCharPath, ScpUpdateMode, ScreenClearMode,
+        StatusStringRequest, TabulationClearMode,
     },
     param::{Param, Subparam},
 };
-use crate::{param, Executor, MAX_INTERMEDIATES};
+use crate::{param, C1Handling, Executor, MAX_INTERMEDIATES};
 
 /// Maximum time before a synchronized update is aborted.
 const SYNC_UPDATE_TIMEOUT: Duration = Duration::from_millis(150);
 
-/// Maximum number of bytes read in one synchronized update (2MiB).
+/// Default maximum number of bytes read in one synchronized update (2MiB).
 const SYNC_BUFFER_SIZE: usize = 0x20_0000;
 
+/// Maximum number of bytes collected for a device control string request.
+const MAX_DCS_REQUEST_LEN: usize = 1024;
+
 /// Number of bytes in the BSU/ESU CSI sequences.
 const SYNC_ESCAPE_LEN: usize = 8;
 
@@ -53,11 +60,13 @@ pub trait Timeout: Default {
     fn pending_timeout(&self) -> bool;
 }
 
+#[cfg(feature = "std")]
 #[derive(Default, Debug)]
 pub struct StdSyncHandler {
     timeout: Option<Instant>,
 }
 
+#[cfg(feature = "std")]
 impl StdSyncHandler {
     /// Synchronized update expiration time.
     #[inline]
@@ -66,6 +75,7 @@ impl StdSyncHandler {
     }
 }
 
+#[cfg(feature = "std")]
 impl StdSyncHandler {
     #[inline]
     fn set_timeout(&mut self, duration: Duration) {
@@ -83,6 +93,38 @@ impl StdSyncHandler {
     }
 }
 
+/// Synchronized update tracking without a clock.
+///
+/// Updates only end with an ESU sequence or once the buffer is full.
+#[cfg(not(feature = "std"))]
+#[derive(Default, Debug)]
+struct CoreSyncHandler {
+    pending: bool,
+}
+
+#[cfg(not(feature = "std"))]
+impl CoreSyncHandler {
+    #[inline]
+    fn set_timeout(&mut self, _duration: Duration) {
+        self.pending = true;
+    }
+
+    #[inline]
+    fn clear_timeout(&mut self) {
+        self.pending = false;
+    }
+
+    #[inline]
+    fn pending_timeout(&self) -> bool {
+        self.pending
+    }
+}
+
+#[cfg(feature = "std")]
+type SyncHandler = StdSyncHandler;
+#[cfg(not(feature = "std"))]
+type SyncHandler = CoreSyncHandler;
+
 /// Internal state for VTE processor.
 #[derive(Debug, Default)]
 struct ProcessorState {
@@ -91,26 +133,53 @@ struct ProcessorState {
 
     /// State for synchronized terminal updates.
     sync_state: SyncState,
+
+    /// Device control string currently being received.
+    dcs: Option<Dcs>,
+}
+
+/// Device control strings handled by the processor.
+#[derive(Debug)]
+enum Dcs {
+    /// DECRQSS with the name of the requested setting.
+    StatusString(Vec<u8>),
+
+    /// XTGETTCAP with hex encoded capability names separated by `;`.
+    Termcap(Vec<u8>),
 }
 
 #[derive(Debug)]
 struct SyncState {
     /// Handler for synchronized updates.
-    timeout: StdSyncHandler,
+    timeout: SyncHandler,
+
+    /// Maximum duration of a synchronized update.
+    duration: Duration,
 
     /// Bytes read during the synchronized update.
     buffer: Vec<u8>,
+
+    /// Maximum number of bytes read in one synchronized update.
+    buffer_size: usize,
 }
 
-impl Default for SyncState {
-    fn default() -> Self {
+impl SyncState {
+    fn with_buffer_size(buffer_size: usize) -> Self {
         Self {
-            buffer: Vec::with_capacity(SYNC_BUFFER_SIZE),
-            timeout: StdSyncHandler::default(),
+            buffer: Vec::with_capacity(buffer_size),
+            timeout: SyncHandler::default(),
+            duration: SYNC_UPDATE_TIMEOUT,
+            buffer_size,
         }
     }
 }
 
+impl Default for SyncState {
+    fn default() -> Self {
+        Self::with_buffer_size(SYNC_BUFFER_SIZE)
+    }
+}
+
 #[derive(Default)]
 pub struct Processor {
     state: ProcessorState,
@@ -122,11 +191,61 @@ impl Processor {
         Default::default()
     }
 
+    /// Create a processor which buffers at most `size` bytes per synchronized update.
+    ///
+    /// Once the buffer is full, the update is terminated and its content processed.
+    pub fn with_sync_buffer_size(size: usize) -> Self {
+        let state = ProcessorState {
+            sync_state: SyncState::with_buffer_size(size),
+            ..Default::default()
+        };
+
+        Self {
+            state,
+            parser: Default::default(),
+        }
+    }
+
     /// Synchronized update timeout.
+    #[cfg(feature = "std")]
     pub fn sync_timeout(&self) -> &StdSyncHandler {
         &self.state.sync_state.timeout
     }
 
+    /// Override the maximum duration of a synchronized update.
+    ///
+    /// Defaults to 150ms. Takes effect the next time a synchronized update is started or
+    /// extended.
+    pub fn set_sync_timeout(&mut self, duration: Duration) {
+        self.state.sync_state.duration = duration;
+    }
+
+    /// Maximum duration of a synchronized update.
+    #[inline]
+    pub fn sync_timeout_duration(&self) -> Duration {
+        self.state.sync_state.duration
+    }
+
+    /// Underlying parser.
+    #[inline]
+    pub fn parser(&self) -> &crate::Parser {
+        &self.parser
+    }
+
+    /// Mutable access to the underlying parser.
+    ///
+    /// Bytes buffered for a synchronized update are owned by the processor, they're not affected by
+    /// changes to the parser.
+    #[inline]
+    pub fn parser_mut(&mut self) -> &mut crate::Parser {
+        &mut self.parser
+    }
+
+    /// Change the treatment of C1 control bytes.
+    pub fn set_c1_handling(&mut self, handling: C1Handling) {
+        self.parser.set_c1_handling(handling);
+    }
+
     pub fn advance<H: Handler>(&mut self, handler: &mut H, bytes: &[u8]) {
         if self.state.sync_state.timeout.pending_timeout() {
             self.advance_sync(handler, bytes);
@@ -183,16 +302,156 @@ impl Processor {
         let end = &self.state.sync_state.buffer[offset..];
 
         if end == BSU_CSI {
-            self.state
-                .sync_state
-                .timeout
-                .set_timeout(SYNC_UPDATE_TIMEOUT);
-        } else if end == ESU_CSI || len >= SYNC_BUFFER_SIZE - 1 {
+            let sync_state = &mut self.state.sync_state;
+            sync_state.timeout.set_timeout(sync_state.duration);
+        } else if end == ESU_CSI || len >= self.state.sync_state.buffer_size.saturating_sub(1) {
             self.stop_sync(handler);
         }
     }
 }
 
+/// [`Executor`] forwarding parsed sequences to a [`Handler`].
+///
+/// This allows driving a [`Handler`] with a bare [`Parser`](crate::Parser). Unlike the
+/// [`Processor`], synchronized updates are not buffered, the mode changes are passed through to
+/// the handler like any other private mode.
+pub struct HandlerPerform<H> {
+    state: ProcessorState,
+    handler: H,
+}
+
+impl<H: Handler> HandlerPerform<H> {
+    pub fn new(handler: H) -> Self {
+        let state = ProcessorState {
+            sync_state: SyncState::with_buffer_size(0),
+            ..Default::default()
+        };
+
+        Self { state, handler }
+    }
+
+    #[inline]
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    #[inline]
+    pub fn handler_mut(&mut self) -> &mut H {
+        &mut self.handler
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> H {
+        self.handler
+    }
+
+    #[inline]
+    fn executor(&mut self) -> HandlerExecutor<'_, H> {
+        HandlerExecutor::new(&mut self.state, &mut self.handler)
+    }
+}
+
+impl<H: Handler> Executor for HandlerPerform<H> {
+    #[inline]
+    fn print(&mut self, c: char) {
+        self.executor().print(c);
+    }
+
+    #[inline]
+    fn print_str(&mut self, s: &str) {
+        self.executor().print_str(s);
+    }
+
+    #[inline]
+    fn execute(&mut self, byte: u8) {
+        self.executor().execute(byte);
+    }
+
+    #[inline]
+    fn put(&mut self, byte: u8) {
+        self.executor().put(byte);
+    }
+
+    #[inline]
+    fn hook(
+        &mut self,
+        params: &crate::param::Params,
+        intermediates: &[u8],
+        ignore: bool,
+        action: char,
+    ) {
+        self.executor().hook(params, intermediates, ignore, action);
+    }
+
+    #[inline]
+    fn unhook(&mut self) {
+        self.executor().unhook();
+    }
+
+    #[inline]
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        self.executor().osc_dispatch(params, bell_terminated);
+    }
+
+    #[inline]
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        self.executor().esc_dispatch(intermediates, ignore, byte);
+    }
+
+    #[inline]
+    fn csi_dispatch(
+        &mut self,
+        params: &crate::param::Params,
+        intermediates: &[u8],
+        ignore: bool,
+        action: char,
+    ) {
+        self.executor()
+            .csi_dispatch(params, intermediates, ignore, action);
+    }
+}
+
+/// [`Processor`] which only keeps track of the cursor style.
+///
+/// For integrations that want to follow cursor style changes, set with DECSCUSR or OSC 50,
+/// without implementing a full [`Handler`].
+#[derive(Default)]
+pub struct CursorStyleProcessor {
+    processor: Processor,
+    recorder: CursorStyleRecorder,
+}
+
+impl CursorStyleProcessor {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn advance(&mut self, bytes: &[u8]) {
+        self.processor.advance(&mut self.recorder, bytes);
+    }
+
+    /// Last requested cursor style, `None` when the default style is in use.
+    #[inline]
+    pub fn last_cursor_style(&self) -> Option<CursorStyle> {
+        self.recorder.style
+    }
+}
+
+#[derive(Default)]
+struct CursorStyleRecorder {
+    style: Option<CursorStyle>,
+}
+
+impl Handler for CursorStyleRecorder {
+    fn set_cursor_style(&mut self, style: Option<CursorStyle>) {
+        self.style = style;
+    }
+
+    fn set_cursor_shape(&mut self, shape: CursorShape) {
+        self.style.get_or_insert_with(CursorStyle::default).shape = shape;
+    }
+}
+
 struct HandlerExecutor<'a, H: Handler> {
     state: &'a mut ProcessorState,
     handler: &'a mut H,
@@ -210,6 +469,13 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
         self.state.preceding_char = Some(c)
     }
 
+    fn print_str(&mut self, s: &str) {
+        self.handler.input_str(s);
+        if let Some(c) = s.chars().next_back() {
+            self.state.preceding_char = Some(c);
+        }
+    }
+
     fn execute(&mut self, byte: u8) {
         match byte {
             c0::HT => self.handler.put_tab(1),
@@ -224,12 +490,27 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
                 .handler
                 .set_active_charset(super::handler::CharsetIndex::G1),
             c0::SUB => self.handler.substitute(),
+            c1::IND => self.handler.linefeed(),
+            c1::NEL => {
+                self.handler.linefeed();
+                self.handler.carriage_return();
+            }
+            c1::HTS => self.handler.set_horizontal_tabstop(),
+            c1::RI => self.handler.reverse_index(),
             _ => debug!("[Unhandled execute] byte={byte:02x}"),
         }
     }
 
     fn put(&mut self, byte: u8) {
-        debug!("[Unhandled put] byte={byte:02x}")
+        match &mut self.state.dcs {
+            Some(Dcs::StatusString(request) | Dcs::Termcap(request))
+                if request.len() < MAX_DCS_REQUEST_LEN =>
+            {
+                request.push(byte)
+            }
+            Some(_) => (),
+            None => debug!("[Unhandled put] byte={byte:02x}"),
+        }
     }
 
     fn hook(
@@ -239,11 +520,36 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
         ignore: bool,
         action: char,
     ) {
-        debug!("[Unhandled hook] params={params:?} intermediates={intermediates:?} ignore={ignore:?} action={action:?}");
+        self.state.dcs = match (intermediates, action) {
+            ([b'$'], 'q') if !ignore => Some(Dcs::StatusString(Vec::new())),
+            ([b'+'], 'q') if !ignore => Some(Dcs::Termcap(Vec::new())),
+            _ => {
+                debug!("[Unhandled hook] params={params:?} intermediates={intermediates:?} ignore={ignore:?} action={action:?}");
+                None
+            }
+        };
     }
 
     fn unhook(&mut self) {
-        debug!("[Unhandled unhook]");
+        match self.state.dcs.take() {
+            Some(Dcs::StatusString(request)) => {
+                let request = match request.as_slice() {
+                    b"m" => StatusStringRequest::Sgr,
+                    b"r" => StatusStringRequest::ScrollingRegion,
+                    b" q" => StatusStringRequest::CursorStyle,
+                    _ => StatusStringRequest::Unknown(request),
+                };
+                self.handler.report_status_string(request);
+            }
+            Some(Dcs::Termcap(request)) => {
+                let names: Vec<String> = request
+                    .split(|&byte| byte == b';')
+                    .map(|name| hex_decode(name).unwrap_or_default())
+                    .collect();
+                self.handler.report_termcap(&names);
+            }
+            None => debug!("[Unhandled unhook]"),
+        }
     }
 
     fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
@@ -293,8 +599,8 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
                         }
                     };
 
-                    if let Some(c) = xparse_color(chunk[1]) {
-                        self.handler.set_color(index as usize, c);
+                    if let Some(c) = xparse_color_rgba(chunk[1]) {
+                        self.handler.set_color_rgba(index as usize, c);
                     } else if chunk[1] == b"?" {
                         let prefix = format!("4;{index}");
                         self.handler
@@ -318,6 +624,7 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
 
                 if uri.is_empty() {
                     self.handler.set_hyperlink(None);
+                    return;
                 }
 
                 let id = link_params
@@ -328,6 +635,25 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
                 self.handler.set_hyperlink(Some(Hyperlink { id, uri }));
             }
 
+            // Report current working directory.
+            #[cfg(feature = "std")]
+            [b"7", uri @ ..] => {
+                let uri = uri.join(&param::PARAM_SEPARATOR);
+
+                if uri.is_empty() {
+                    self.handler.set_current_directory(None);
+                } else if let Some(path) = parse_file_uri(&uri) {
+                    self.handler.set_current_directory(Some(path));
+                } else {
+                    unhandled!();
+                }
+            }
+
+            // Desktop notification.
+            [b"9", body @ ..] if !body.is_empty() => {
+                self.handler.report_notification(None, join_params(body));
+            }
+
             [color_num @ (b"10" | b"11" | b"12"), params @ ..] if !params.is_empty() => {
                 let Some(mut dynamic_code) = parse_number(color_num) else {
                     return;
@@ -344,8 +670,8 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
                         break;
                     }
 
-                    if let Some(color) = xparse_color(param) {
-                        self.handler.set_color(index, color);
+                    if let Some(color) = xparse_color_rgba(param) {
+                        self.handler.set_color_rgba(index, color);
                     } else if param == b"?" {
                         self.handler.dynamic_color_sequence(
                             dynamic_code.to_string(),
@@ -374,6 +700,12 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
                 self.handler.set_cursor_shape(shape);
             }
 
+            // Set mouse cursor shape.
+            [b"22", shape] => match simdutf8::basic::from_utf8(shape) {
+                Ok(shape) if !shape.is_empty() => self.handler.set_mouse_cursor_shape(shape),
+                _ => unhandled!(),
+            },
+
             // Set or query clipboard
             [b"52", clipboard, payload, ..] => {
                 let clipboard = clipboard.first().unwrap_or(&b'c');
@@ -384,6 +716,16 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
                 }
             }
 
+            // Desktop notification with a title, using the `notify;title;body` syntax.
+            [b"777", b"notify", title, body @ ..] => {
+                let title = simdutf8::basic::from_utf8(title)
+                    .ok()
+                    .filter(|title| !title.is_empty())
+                    .map(str::to_owned);
+
+                self.handler.report_notification(title, join_params(body));
+            }
+
             // Reset color number `color` to themed color.
             [b"104", indices @ ..] => {
                 // Reset all color indexes when no parameters are given.
@@ -451,7 +793,7 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
             }
             (b'H', []) => self.handler.set_horizontal_tabstop(),
             (b'M', []) => self.handler.reverse_index(),
-            (b'Z', []) => self.handler.identify_terminal(None),
+            (b'Z', []) => self.handler.identify_terminal(DeviceAttributes::Primary),
             (b'c', []) => self.handler.reset_state(),
             (b'7', []) => self.handler.save_cursor_position(),
             (b'8', [b'#']) => self.handler.decaln(),
@@ -498,17 +840,18 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
             ('B' | 'e', []) => handler.move_down(next_param_or(1).into()),
             ('b', []) => {
                 if let Some(c) = self.state.preceding_char {
-                    for _ in 0..next_param_or(1) {
-                        handler.input(c);
-                    }
+                    handler.repeat(c, next_param_or(1).into());
                 } else {
                     debug!("tried to repeat with no preceding char");
                 }
             }
             ('C' | 'a', []) => handler.move_forward(next_param_or(1).into()),
-            ('c', intermediates) if next_param_or(0) == 0 => {
-                handler.identify_terminal(intermediates.first().map(|&i| i as char))
-            }
+            ('c', intermediates) if next_param_or(0) == 0 => match intermediates {
+                [] => handler.identify_terminal(DeviceAttributes::Primary),
+                [b'>'] => handler.identify_terminal(DeviceAttributes::Secondary),
+                [b'='] => handler.identify_terminal_tertiary(),
+                _ => unhandled!(),
+            },
             ('D', []) => handler.move_backward(next_param_or(1) as usize),
             ('d', []) => handler.goto_line(next_param_or(1) as i32 - 1),
             ('E', []) => handler.move_down_and_cr(next_param_or(1) as usize),
@@ -541,10 +884,8 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
             ('h', [b'?']) => {
                 for param in params_iter.map(|p| p.as_slice()[0]) {
                     if param == NamedPrivateMode::SyncUpdate as u16 {
-                        self.state
-                            .sync_state
-                            .timeout
-                            .set_timeout(SYNC_UPDATE_TIMEOUT);
+                        let sync_state = &mut self.state.sync_state;
+                        sync_state.timeout.set_timeout(sync_state.duration);
                     }
 
                     handler.set_private_mode(PrivateMode::new(param));
@@ -645,7 +986,14 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
                     handler.report_modify_other_keys();
                 }
             }
-            ('n', []) => handler.device_status(next_param_or(0) as usize),
+            ('n', []) => match next_param_or(0) {
+                6 => handler.report_cursor_position(false),
+                arg => handler.device_status(arg as usize),
+            },
+            ('n', [b'?']) => match next_param_or(0) {
+                6 => handler.report_cursor_position(true),
+                arg => handler.private_device_status(arg as usize),
+            },
             ('P', []) => handler.delete_chars(next_param_or(1).into()),
             ('p', [b'$']) => {
                 let mode = Mode::new(next_param_or(0));
@@ -690,6 +1038,8 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
             ('s', []) => handler.save_cursor_position(),
             ('T', []) => handler.scroll_down(next_param_or(1) as usize),
             ('t', []) => match next_param_or(1) as usize {
+                11 => handler.report_window_state(),
+                13 => handler.report_window_position(),
                 14 => handler.text_area_size_pixels(),
                 18 => handler.text_area_size_chars(),
                 22 => handler.push_title(),
@@ -718,6 +1068,54 @@ impl<H: Handler> Executor for HandlerExecutor<'_, H> {
             }
             ('u', []) => handler.restore_cursor_position(),
             ('X', []) => handler.erase_chars(next_param_or(1).into()),
+            ('x', [b'$']) => {
+                // Only printable GL and GR characters are valid fill characters.
+                let c = match next_param_or(0) {
+                    c @ (32..=126 | 160..=255) => char::from(c as u8),
+                    _ => {
+                        unhandled!();
+                        return;
+                    }
+                };
+
+                let top = next_param_or(1) as usize;
+                let left = next_param_or(1) as usize;
+                let mut next_edge = || {
+                    params_iter
+                        .next()
+                        .map(|p| p[0] as usize)
+                        .filter(|&p| p != 0)
+                };
+                let bottom = next_edge();
+                let right = next_edge();
+
+                let area = Rectangle {
+                    top,
+                    left,
+                    bottom,
+                    right,
+                };
+
+                handler.fill_rectangle(c, area);
+            }
+            ('y', [b'*']) => {
+                let id = next_param_or(0).into();
+                // There is a single page, its number is ignored.
+                let _page = next_param_or(1);
+                let top = next_param_or(1) as usize;
+                let left = next_param_or(1) as usize;
+                let bottom = Some(next_param_or(0) as usize).filter(|&edge| edge != 0);
+                let right = Some(next_param_or(0) as usize).filter(|&edge| edge != 0);
+
+                let area = Rectangle {
+                    top,
+                    left,
+                    bottom,
+                    right,
+                };
+
+                handler.report_rectangle_checksum(id, area);
+            }
             ('Z', []) => handler.move_backward_tabs(next_param_or(1)),
 
             _ => unhandled!(),
@@ -741,6 +1139,7 @@ fn attrs_from_sgr_parameters<'a, H: Handler, I: Iterator<Item = &'a Param>>(
             [4, 3] => Some(Attribute::Undercurl),
             [4, 4] => Some(Attribute::DottedUnderline),
             [4, 5] => Some(Attribute::DashedUnderline),
+            // Plain `4`, `4:1` and unknown styles fall back to a single underline.
             [4, ..] => Some(Attribute::Underline),
             [5] => Some(Attribute::BlinkSlow),
             [6] => Some(Attribute::BlinkFast),
@@ -826,49 +1225,51 @@ fn attrs_from_sgr_parameters<'a, H: Handler, I: Iterator<Item = &'a Param>>(
 }
 
 /// Handle colon separated rgb color escape sequence.
+///
+/// Truecolor comes either in the ITU form `38:2:id:r:g:b`, where the colorspace id is usually
+/// left empty, or in the compact `38:2:r:g:b` form. An indexed color is `38:5:n`.
 fn handle_colon_rgb(params: &[u16]) -> Option<Color> {
-    let rgb_start = if params.len() > 4 { 2 } else { 1 };
-    let rgb_iter = params[rgb_start..].iter().copied();
-    let mut iter = iter::once(params[0]).chain(rgb_iter);
-
-    subparam_sgr_to_color(&mut iter)
-}
+    let rgb = |r: u16, g: u16, b: u16| {
+        Some(Color::Spec(Rgb {
+            r: u8::try_from(r).ok()?,
+            g: u8::try_from(g).ok()?,
+            b: u8::try_from(b).ok()?,
+        }))
+    };
 
-/// Parse a color specifier from list of attributes.
-fn subparam_sgr_to_color<I>(params: &mut I) -> Option<Color>
-where
-    I: Iterator<Item = Subparam>,
-{
-    match params.next() {
-        Some(2) => Some(Color::Spec(Rgb {
-            r: u8::try_from(params.next()?).ok()?,
-            g: u8::try_from(params.next()?).ok()?,
-            b: u8::try_from(params.next()?).ok()?,
-        })),
-        Some(5) => Some(Color::Indexed(u8::try_from(params.next()?).ok()?)),
+    match *params {
+        [2, _, r, g, b, ..] => rgb(r, g, b),
+        [2, r, g, b] => rgb(r, g, b),
+        [5, index, ..] => Some(Color::Indexed(u8::try_from(index).ok()?)),
         _ => None,
     }
 }
 
-/// Parse colors in XParseColor format.
-fn xparse_color(color: &[u8]) -> Option<Rgb> {
-    if !color.is_empty() && color[0] == b'#' {
-        parse_legacy_color(&color[1..])
-    } else if color.len() >= 4 && &color[..4] == b"rgb:" {
-        parse_rgb_color(&color[4..])
+/// Parse colors in XParseColor format, extended with an optional alpha channel.
+///
+/// Colors without an alpha channel are opaque.
+fn xparse_color_rgba(color: &[u8]) -> Option<Rgba> {
+    if let Some(color) = color.strip_prefix(b"#") {
+        parse_legacy_color(color)
+    } else if let Some(color) = color.strip_prefix(b"rgba:") {
+        let [r, g, b, a] = parse_rgb_channels(color)?;
+        Some(Rgba::new(r, g, b, a))
+    } else if let Some(color) = color.strip_prefix(b"rgb:") {
+        let [r, g, b] = parse_rgb_channels(color)?;
+        Some(Rgba::new(r, g, b, u8::MAX))
     } else {
         None
     }
 }
 
-/// Parse colors in `rgb:r(rrr)/g(ggg)/b(bbb)` format.
-fn parse_rgb_color(color: &[u8]) -> Option<Rgb> {
+/// Parse colors in `rgb:r(rrr)/g(ggg)/b(bbb)` or `rgba:r(rrr)/g(ggg)/b(bbb)/a(aaa)` format.
+fn parse_rgb_channels<const N: usize>(color: &[u8]) -> Option<[u8; N]> {
     let colors = simdutf8::basic::from_utf8(color)
         .ok()?
         .split('/')
         .collect::<Vec<_>>();
 
-    if colors.len() != 3 {
+    if colors.len() != N {
         return None;
     }
 
@@ -883,15 +1284,28 @@ fn parse_rgb_color(color: &[u8]) -> Option<Rgb> {
         }
     };
 
-    Some(Rgb {
-        r: scale(colors[0])?,
-        g: scale(colors[1])?,
-        b: scale(colors[2])?,
-    })
+    let mut channels = [0; N];
+    for (channel, color) in channels.iter_mut().zip(colors) {
+        *channel = scale(color)?;
+    }
+
+    Some(channels)
 }
 
-/// Parse colors in `#r(rrr)g(ggg)b(bbb)` format.
-fn parse_legacy_color(color: &[u8]) -> Option<Rgb> {
+/// Parse colors in `#r(rrr)g(ggg)b(bbb)` or `#rrggbbaa` format.
+fn parse_legacy_color(color: &[u8]) -> Option<Rgba> {
+    if color.len() == 8 {
+        let channel =
+            |slice: &[u8]| u8::from_str_radix(simdutf8::basic::from_utf8(slice).ok()?, 16).ok();
+
+        return Some(Rgba::new(
+            channel(&color[0..2])?,
+            channel(&color[2..4])?,
+            channel(&color[4..6])?,
+            channel(&color[6..8])?,
+        ));
+    }
+
     let item_len = color.len() / 3;
 
     // Truncate/Fill to two byte precision.
@@ -900,11 +1314,99 @@ fn parse_legacy_color(color: &[u8]) -> Option<Rgb> {
         Some((col >> (4 * slice.len().saturating_sub(1))) as u8)
     };
 
-    Some(Rgb {
-        r: color_from_slice(&color[0..item_len])?,
-        g: color_from_slice(&color[item_len..item_len * 2])?,
-        b: color_from_slice(&color[item_len * 2..])?,
-    })
+    Some(Rgba::new(
+        color_from_slice(&color[0..item_len])?,
+        color_from_slice(&color[item_len..item_len * 2])?,
+        color_from_slice(&color[item_len * 2..])?,
+        u8::MAX,
+    ))
+}
+
+/// Join OSC parameters which were split on `;` back into a single string.
+fn join_params(params: &[&[u8]]) -> String {
+    String::from_utf8_lossy(&params.join(&param::PARAM_SEPARATOR)).into_owned()
+}
+
+/// Parse the local path of a `file://host/path` URI.
+///
+/// Returns `None` if the URI isn't a file URI or refers to another host.
+#[cfg(feature = "std")]
+fn parse_file_uri(uri: &[u8]) -> Option<PathBuf> {
+    let rest = uri.strip_prefix(b"file://")?;
+    let path_start = rest.iter().position(|&b| b == b'/')?;
+    let (host, path) = rest.split_at(path_start);
+
+    if !is_local_host(host) {
+        return None;
+    }
+
+    let path = percent_decode(path)?;
+
+    #[cfg(unix)]
+    {
+        use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+        Some(PathBuf::from(OsString::from_vec(path)))
+    }
+
+    #[cfg(not(unix))]
+    {
+        String::from_utf8(path).ok().map(PathBuf::from)
+    }
+}
+
+/// Check whether the host of a `file://` URI refers to this machine.
+#[cfg(feature = "std")]
+fn is_local_host(host: &[u8]) -> bool {
+    static HOSTNAME: OnceLock<Option<String>> = OnceLock::new();
+
+    if host.is_empty() || host == b"localhost" {
+        return true;
+    }
+
+    let hostname = HOSTNAME.get_or_init(|| {
+        std::env::var("HOSTNAME")
+            .ok()
+            .or_else(|| std::fs::read_to_string("/proc/sys/kernel/hostname").ok())
+            .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+            .map(|name| name.trim().to_owned())
+    });
+
+    hostname
+        .as_deref()
+        .is_some_and(|name| name.as_bytes() == host)
+}
+
+/// Decode `%XX` escapes, returning `None` for malformed escapes.
+#[cfg(feature = "std")]
+fn percent_decode(input: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut bytes = input.iter();
+
+    while let Some(&byte) = bytes.next() {
+        if byte == b'%' {
+            let high = (*bytes.next()? as char).to_digit(16)?;
+            let low = (*bytes.next()? as char).to_digit(16)?;
+            output.push((high * 16 + low) as u8);
+        } else {
+            output.push(byte);
+        }
+    }
+
+    Some(output)
+}
+
+/// Decode a hex encoded string, like the capability names of XTGETTCAP.
+fn hex_decode(input: &[u8]) -> Option<String> {
+    if !input.len().is_multiple_of(2) {
+        return None;
+    }
+
+    let bytes = input
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(core::str::from_utf8(pair).ok()?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+
+    String::from_utf8(bytes).ok()
 }
 
 fn parse_number(input: &[u8]) -> Option<u8> {
@@ -938,7 +1440,7 @@ fn parse_sgr_color<I: Iterator<Item = u16>>(params: &mut I) -> Option<Color> {
 // Tests for parsing escape sequences.
 //
 // Byte sequences used in these tests are recording of pty stdout.
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
@@ -947,12 +1449,36 @@ mod tests {
         charset: Charset,
         attr: Option<Attribute>,
         identity_reported: bool,
+        device_attributes: Vec<DeviceAttributes>,
+        tertiary_attributes_reported: bool,
         color: Option<Rgb>,
+        alpha: Option<u8>,
         reset_colors: Vec<usize>,
         cursor_style: Option<CursorStyle>,
+        current_directory: Option<PathBuf>,
+        mouse_cursor_shape: Option<String>,
+        notification: Option<(Option<String>, String)>,
+        sync_terminations: usize,
+        status_string_requests: Vec<StatusStringRequest>,
+        termcap_requests: Vec<Vec<String>>,
+        device_status_requests: Vec<usize>,
+        cursor_position_reports: Vec<bool>,
+        window_reports: Vec<&'static str>,
+        checksum_requests: Vec<(usize, Rectangle)>,
+        input: String,
+        input_runs: Vec<String>,
     }
 
     impl Handler for MockHandler {
+        fn input(&mut self, c: char) {
+            self.input.push(c);
+        }
+
+        fn input_str(&mut self, s: &str) {
+            self.input_runs.push(s.into());
+            self.input.push_str(s);
+        }
+
         fn terminal_attribute(&mut self, attr: Attribute) {
             self.attr = Some(attr);
         }
@@ -966,8 +1492,13 @@ mod tests {
             self.index = index;
         }
 
-        fn identify_terminal(&mut self, _intermediate: Option<char>) {
+        fn identify_terminal(&mut self, attributes: DeviceAttributes) {
             self.identity_reported = true;
+            self.device_attributes.push(attributes);
+        }
+
+        fn identify_terminal_tertiary(&mut self) {
+            self.tertiary_attributes_reported = true;
         }
 
         fn reset_state(&mut self) {
@@ -978,6 +1509,11 @@ mod tests {
             self.color = Some(c);
         }
 
+        fn set_color_rgba(&mut self, index: usize, c: Rgba) {
+            self.alpha = Some(c.a);
+            self.set_color(index, c.rgb());
+        }
+
         fn reset_color(&mut self, index: usize) {
             self.reset_colors.push(index)
         }
@@ -985,22 +1521,94 @@ mod tests {
         fn set_cursor_style(&mut self, style: Option<CursorStyle>) {
             self.cursor_style = style;
         }
-    }
 
-    impl Default for MockHandler {
-        fn default() -> MockHandler {
+        fn set_current_directory(&mut self, path: Option<PathBuf>) {
+            self.current_directory = path;
+        }
+
+        fn set_mouse_cursor_shape(&mut self, shape: &str) {
+            self.mouse_cursor_shape = Some(shape.to_owned());
+        }
+
+        fn report_notification(&mut self, title: Option<String>, body: String) {
+            self.notification = Some((title, body));
+        }
+
+        fn report_status_string(&mut self, request: StatusStringRequest) {
+            self.status_string_requests.push(request);
+        }
+
+        fn report_termcap(&mut self, names: &[String]) {
+            self.termcap_requests.push(names.to_vec());
+        }
+
+        fn device_status(&mut self, arg: usize) {
+            self.device_status_requests.push(arg);
+        }
+
+        fn report_cursor_position(&mut self, extended: bool) {
+            self.cursor_position_reports.push(extended);
+        }
+
+        fn report_window_state(&mut self) {
+            self.window_reports.push("state");
+        }
+
+        fn report_window_position(&mut self) {
+            self.window_reports.push("position");
+        }
+
+        fn report_rectangle_checksum(&mut self, id: usize, area: Rectangle) {
+            self.checksum_requests.push((id, area));
+        }
+
+        fn unset_private_mode(&mut self, mode: PrivateMode) {
+            if mode == PrivateMode::Named(NamedPrivateMode::SyncUpdate) {
+                self.sync_terminations += 1;
+            }
+        }
+    }
+
+    impl Default for MockHandler {
+        fn default() -> MockHandler {
             MockHandler {
                 index: CharsetIndex::G0,
                 charset: Charset::Ascii,
                 attr: None,
                 identity_reported: false,
+                device_attributes: Vec::new(),
+                tertiary_attributes_reported: false,
                 color: None,
+                alpha: None,
                 reset_colors: Vec::new(),
                 cursor_style: None,
+                current_directory: None,
+                mouse_cursor_shape: None,
+                notification: None,
+                sync_terminations: 0,
+                status_string_requests: Vec::new(),
+                termcap_requests: Vec::new(),
+                device_status_requests: Vec::new(),
+                cursor_position_reports: Vec::new(),
+                window_reports: Vec::new(),
+                checksum_requests: Vec::new(),
+                input: String::new(),
+                input_runs: Vec::new(),
             }
         }
     }
 
+    #[test]
+    fn input_runs_set_preceding_char() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, "héllo\x1b[2b".as_bytes());
+
+        assert_eq!(handler.input_runs, ["héllo"]);
+        assert_eq!(handler.input, "héllooo");
+    }
+
     #[test]
     fn parse_control_attribute() {
         static BYTES: &[u8] = &[0x1b, b'[', b'1', b'm'];
@@ -1013,6 +1621,95 @@ mod tests {
         assert_eq!(handler.attr, Some(Attribute::Bold));
     }
 
+    #[test]
+    fn parse_underline_style() {
+        for (bytes, attr) in [
+            (&b"\x1b[4m"[..], Attribute::Underline),
+            (b"\x1b[4:1m", Attribute::Underline),
+            (b"\x1b[4:3m", Attribute::Undercurl),
+            (b"\x1b[4:0m", Attribute::CancelUnderline),
+        ] {
+            let mut parser = Processor::new();
+            let mut handler = MockHandler::default();
+
+            parser.advance(&mut handler, bytes);
+
+            assert_eq!(handler.attr, Some(attr));
+        }
+    }
+
+    #[test]
+    fn sgr_round_trip() {
+        let attrs = [
+            Attribute::Reset,
+            Attribute::Bold,
+            Attribute::Dim,
+            Attribute::Italic,
+            Attribute::Underline,
+            Attribute::DoubleUnderline,
+            Attribute::Undercurl,
+            Attribute::DottedUnderline,
+            Attribute::DashedUnderline,
+            Attribute::BlinkSlow,
+            Attribute::BlinkFast,
+            Attribute::Reverse,
+            Attribute::Hidden,
+            Attribute::Strike,
+            Attribute::CancelBold,
+            Attribute::CancelBoldDim,
+            Attribute::CancelItalic,
+            Attribute::CancelUnderline,
+            Attribute::CancelBlink,
+            Attribute::CancelReverse,
+            Attribute::CancelHidden,
+            Attribute::CancelStrike,
+            Attribute::Foreground(Color::Named(NamedColor::Red)),
+            Attribute::Foreground(Color::Named(NamedColor::BrightWhite)),
+            Attribute::Foreground(Color::Named(NamedColor::Foreground)),
+            Attribute::Foreground(Color::Indexed(208)),
+            Attribute::Foreground(Color::Spec(Rgb::new(1, 2, 3))),
+            Attribute::Background(Color::Named(NamedColor::Black)),
+            Attribute::Background(Color::Named(NamedColor::BrightCyan)),
+            Attribute::Background(Color::Named(NamedColor::Background)),
+            Attribute::Background(Color::Indexed(17)),
+            Attribute::Background(Color::Spec(Rgb::new(255, 0, 128))),
+            Attribute::UnderlineColor(None),
+            Attribute::UnderlineColor(Some(Color::Indexed(3))),
+            Attribute::UnderlineColor(Some(Color::Spec(Rgb::new(9, 8, 7)))),
+        ];
+
+        for attr in attrs {
+            let mut parser = Processor::new();
+            let mut handler = MockHandler::default();
+
+            parser.advance(&mut handler, &attr.to_sgr().unwrap());
+
+            assert_eq!(handler.attr, Some(attr));
+        }
+
+        let truecolor = Attribute::Foreground(Color::Spec(Rgb::new(1, 2, 3)));
+        assert_eq!(truecolor.to_sgr().unwrap(), b"\x1b[38;2;1;2;3m");
+
+        let underline = Attribute::UnderlineColor(Some(Color::Named(NamedColor::BrightRed)));
+        assert_eq!(underline.to_sgr().unwrap(), b"\x1b[58;5;9m");
+
+        let cursor = Attribute::Foreground(Color::Named(NamedColor::Cursor));
+        assert_eq!(cursor.to_sgr(), None);
+    }
+
+    #[test]
+    fn parse_unknown_underline_style() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b[4:9m");
+        assert_eq!(handler.attr, Some(Attribute::Underline));
+
+        // Parameters following the unknown style are still applied.
+        parser.advance(&mut handler, b"\x1b[4:9;1m");
+        assert_eq!(handler.attr, Some(Attribute::Bold));
+    }
+
     #[test]
     fn parse_terminal_identity_csi() {
         let bytes: &[u8] = &[0x1b, b'[', b'1', b'c'];
@@ -1039,6 +1736,78 @@ mod tests {
         assert!(handler.identity_reported);
     }
 
+    #[test]
+    fn parse_device_attributes_kinds() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b[c\x1b[>c\x1b[>0c\x1bZ");
+        assert_eq!(
+            handler.device_attributes,
+            [
+                DeviceAttributes::Primary,
+                DeviceAttributes::Secondary,
+                DeviceAttributes::Secondary,
+                DeviceAttributes::Primary,
+            ]
+        );
+        assert!(!handler.tertiary_attributes_reported);
+
+        let mut handler = MockHandler::default();
+        parser.advance(&mut handler, b"\x1b[=c");
+        assert!(handler.device_attributes.is_empty());
+        assert!(handler.tertiary_attributes_reported);
+
+        // Unknown intermediates are not answered.
+        let mut handler = MockHandler::default();
+        parser.advance(&mut handler, b"\x1b[?c\x1b[<c");
+        assert!(handler.device_attributes.is_empty());
+        assert!(!handler.tertiary_attributes_reported);
+    }
+
+    #[test]
+    fn parse_device_status_reports() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b[5n\x1b[6n\x1b[?6n\x1b[?15n");
+
+        assert_eq!(handler.device_status_requests, [5]);
+        assert_eq!(handler.cursor_position_reports, [false, true]);
+    }
+
+    #[test]
+    fn parse_window_reports() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b[11t\x1b[13t\x1b[13;2t\x1b[12t");
+
+        assert_eq!(handler.window_reports, ["state", "position", "position"]);
+    }
+
+    #[test]
+    fn parse_rectangle_checksum_request() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b[7;1;2;3;4;5*y\x1b[*y");
+
+        let area = Rectangle {
+            top: 2,
+            left: 3,
+            bottom: Some(4),
+            right: Some(5),
+        };
+        let whole_screen = Rectangle {
+            top: 1,
+            left: 1,
+            bottom: None,
+            right: None,
+        };
+        assert_eq!(handler.checksum_requests, [(7, area), (0, whole_screen)]);
+    }
+
     #[test]
     fn parse_terminal_identity_esc() {
         let bytes: &[u8] = &[0x1b, b'Z'];
@@ -1083,6 +1852,47 @@ mod tests {
         assert_eq!(handler.attr, Some(Attribute::Foreground(Color::Spec(spec))));
     }
 
+    #[test]
+    fn parse_colon_truecolor_attr() {
+        let purple = Color::Spec(Rgb::new(255, 0, 255));
+
+        for (bytes, attr) in [
+            (&b"\x1b[38:2::255:0:255m"[..], Attribute::Foreground(purple)),
+            (b"\x1b[38:2:0:255:0:255m", Attribute::Foreground(purple)),
+            (b"\x1b[38:2:255:0:255m", Attribute::Foreground(purple)),
+            (b"\x1b[48:2::255:0:255m", Attribute::Background(purple)),
+            (
+                b"\x1b[58:2::255:0:255m",
+                Attribute::UnderlineColor(Some(purple)),
+            ),
+            (
+                b"\x1b[38:5:123m",
+                Attribute::Foreground(Color::Indexed(123)),
+            ),
+        ] {
+            let mut parser = Processor::new();
+            let mut handler = MockHandler::default();
+
+            parser.advance(&mut handler, bytes);
+
+            assert_eq!(handler.attr, Some(attr));
+        }
+
+        // Missing channels and out of range values are rejected.
+        for bytes in [
+            &b"\x1b[38:2:255:0m"[..],
+            b"\x1b[38:2::256:0:0m",
+            b"\x1b[38:5m",
+        ] {
+            let mut parser = Processor::new();
+            let mut handler = MockHandler::default();
+
+            parser.advance(&mut handler, bytes);
+
+            assert_eq!(handler.attr, None);
+        }
+    }
+
     /// No exactly a test; useful for debugging.
     #[test]
     fn parse_zsh_startup() {
@@ -1140,7 +1950,7 @@ mod tests {
     #[test]
     fn parse_valid_rgb_colors() {
         assert_eq!(
-            xparse_color(b"rgb:f/e/d"),
+            xparse_color_rgba(b"rgb:f/e/d").map(Rgba::rgb),
             Some(Rgb {
                 r: 0xff,
                 g: 0xee,
@@ -1148,7 +1958,7 @@ mod tests {
             })
         );
         assert_eq!(
-            xparse_color(b"rgb:11/aa/ff"),
+            xparse_color_rgba(b"rgb:11/aa/ff").map(Rgba::rgb),
             Some(Rgb {
                 r: 0x11,
                 g: 0xaa,
@@ -1156,7 +1966,7 @@ mod tests {
             })
         );
         assert_eq!(
-            xparse_color(b"rgb:f/ed1/cb23"),
+            xparse_color_rgba(b"rgb:f/ed1/cb23").map(Rgba::rgb),
             Some(Rgb {
                 r: 0xff,
                 g: 0xec,
@@ -1164,7 +1974,7 @@ mod tests {
             })
         );
         assert_eq!(
-            xparse_color(b"rgb:ffff/0/0"),
+            xparse_color_rgba(b"rgb:ffff/0/0").map(Rgba::rgb),
             Some(Rgb {
                 r: 0xff,
                 g: 0x0,
@@ -1176,7 +1986,7 @@ mod tests {
     #[test]
     fn parse_valid_legacy_rgb_colors() {
         assert_eq!(
-            xparse_color(b"#1af"),
+            xparse_color_rgba(b"#1af").map(Rgba::rgb),
             Some(Rgb {
                 r: 0x10,
                 g: 0xa0,
@@ -1184,7 +1994,7 @@ mod tests {
             })
         );
         assert_eq!(
-            xparse_color(b"#11aaff"),
+            xparse_color_rgba(b"#11aaff").map(Rgba::rgb),
             Some(Rgb {
                 r: 0x11,
                 g: 0xaa,
@@ -1192,7 +2002,7 @@ mod tests {
             })
         );
         assert_eq!(
-            xparse_color(b"#110aa0ff0"),
+            xparse_color_rgba(b"#110aa0ff0").map(Rgba::rgb),
             Some(Rgb {
                 r: 0x11,
                 g: 0xaa,
@@ -1200,7 +2010,7 @@ mod tests {
             })
         );
         assert_eq!(
-            xparse_color(b"#1100aa00ff00"),
+            xparse_color_rgba(b"#1100aa00ff00").map(Rgba::rgb),
             Some(Rgb {
                 r: 0x11,
                 g: 0xaa,
@@ -1209,16 +2019,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_valid_rgba_colors() {
+        assert_eq!(
+            xparse_color_rgba(b"#11223344"),
+            Some(Rgba::new(0x11, 0x22, 0x33, 0x44))
+        );
+        assert_eq!(
+            xparse_color_rgba(b"rgba:11/22/33/44"),
+            Some(Rgba::new(0x11, 0x22, 0x33, 0x44))
+        );
+        assert_eq!(
+            xparse_color_rgba(b"rgba:f/ed1/cb23/8"),
+            Some(Rgba::new(0xff, 0xec, 0xca, 0x88))
+        );
+
+        // Colors without alpha are opaque.
+        assert_eq!(
+            xparse_color_rgba(b"#11aaff"),
+            Some(Rgba::new(0x11, 0xaa, 0xff, 0xff))
+        );
+        assert_eq!(
+            xparse_color_rgba(b"rgb:11/aa/ff"),
+            Some(Rgba::new(0x11, 0xaa, 0xff, 0xff))
+        );
+    }
+
+    #[test]
+    fn parse_invalid_rgba_colors() {
+        assert_eq!(xparse_color_rgba(b"rgba:11/22/33"), None);
+        assert_eq!(xparse_color_rgba(b"rgb:11/22/33/44"), None);
+        assert_eq!(xparse_color_rgba(b"#1122334g"), None);
+    }
+
+    #[test]
+    fn parse_osc_color_with_alpha() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b]11;rgba:11/22/33/44\x1b\\");
+
+        assert_eq!(handler.color, Some(Rgb::new(0x11, 0x22, 0x33)));
+        assert_eq!(handler.alpha, Some(0x44));
+
+        parser.advance(&mut handler, b"\x1b]4;1;#112233\x1b\\");
+
+        assert_eq!(handler.color, Some(Rgb::new(0x11, 0x22, 0x33)));
+        assert_eq!(handler.alpha, Some(0xff));
+    }
+
     #[test]
     fn parse_invalid_rgb_colors() {
-        assert_eq!(xparse_color(b"rgb:0//"), None);
-        assert_eq!(xparse_color(b"rgb://///"), None);
+        assert_eq!(xparse_color_rgba(b"rgb:0//").map(Rgba::rgb), None);
+        assert_eq!(xparse_color_rgba(b"rgb://///").map(Rgba::rgb), None);
     }
 
     #[test]
     fn parse_invalid_legacy_rgb_colors() {
-        assert_eq!(xparse_color(b"#"), None);
-        assert_eq!(xparse_color(b"#f"), None);
+        assert_eq!(xparse_color_rgba(b"#").map(Rgba::rgb), None);
+        assert_eq!(xparse_color_rgba(b"#f").map(Rgba::rgb), None);
     }
 
     #[test]
@@ -1254,6 +2113,39 @@ mod tests {
         )
     }
 
+    #[test]
+    fn cursor_style_processor() {
+        let mut processor = CursorStyleProcessor::new();
+        assert_eq!(processor.last_cursor_style(), None);
+
+        processor.advance(b"\x1b[5 q");
+        assert_eq!(
+            processor.last_cursor_style(),
+            Some(CursorStyle {
+                shape: CursorShape::Beam,
+                blinking: true
+            })
+        );
+
+        processor.advance(b"\x1b]50;CursorShape=2\x07");
+        assert_eq!(
+            processor.last_cursor_style(),
+            Some(CursorStyle {
+                shape: CursorShape::Underline,
+                blinking: true
+            })
+        );
+
+        processor.advance(b"\x1b[0 q\x1b]50;CursorShape=1\x07");
+        assert_eq!(
+            processor.last_cursor_style(),
+            Some(CursorStyle {
+                shape: CursorShape::Beam,
+                blinking: false
+            })
+        );
+    }
+
     #[test]
     fn parse_osc4_set_color() {
         let bytes: &[u8] = b"\x1b]4;0;#fff\x1b\\";
@@ -1273,6 +2165,116 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_osc22_mouse_cursor_shape() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b]22;\x07");
+        assert_eq!(handler.mouse_cursor_shape, None);
+
+        parser.advance(&mut handler, b"\x1b]22;pointer\x07");
+        assert_eq!(handler.mouse_cursor_shape.as_deref(), Some("pointer"));
+    }
+
+    #[test]
+    fn parse_osc7_current_directory() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b]7;file:///home/user/src\x07");
+        assert_eq!(
+            handler.current_directory,
+            Some(PathBuf::from("/home/user/src"))
+        );
+
+        parser.advance(&mut handler, b"\x1b]7;file://localhost/tmp\x1b\\");
+        assert_eq!(handler.current_directory, Some(PathBuf::from("/tmp")));
+    }
+
+    #[test]
+    fn parse_osc7_percent_encoded() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b]7;file:///tmp/my%20dir/%E2%9C%93;x\x07");
+        assert_eq!(
+            handler.current_directory,
+            Some(PathBuf::from("/tmp/my dir/\u{2713};x"))
+        );
+    }
+
+    #[test]
+    fn parse_osc7_invalid_uri() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b]7;file:///tmp\x07");
+        parser.advance(&mut handler, b"\x1b]7;http://example.com/\x07");
+        parser.advance(&mut handler, b"\x1b]7;file:///tmp/%zz\x07");
+        parser.advance(&mut handler, b"\x1b]7;file://remote.invalid/home\x07");
+
+        assert_eq!(handler.current_directory, Some(PathBuf::from("/tmp")));
+    }
+
+    #[test]
+    fn parse_osc7_empty_resets() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b]7;file:///tmp\x07");
+        assert!(handler.current_directory.is_some());
+
+        parser.advance(&mut handler, b"\x1b]7;\x07");
+        assert_eq!(handler.current_directory, None);
+    }
+
+    #[test]
+    fn parse_osc9_notification() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b]9;Build finished; 0 errors\x07");
+
+        assert_eq!(
+            handler.notification,
+            Some((None, String::from("Build finished; 0 errors")))
+        );
+    }
+
+    #[test]
+    fn parse_osc777_notification() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(
+            &mut handler,
+            b"\x1b]777;notify;cargo;tests passed; 0 failed\x1b\\",
+        );
+
+        assert_eq!(
+            handler.notification,
+            Some((
+                Some(String::from("cargo")),
+                String::from("tests passed; 0 failed")
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_osc777_notification_without_title() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b]777;notify;;done\x07");
+        assert_eq!(handler.notification, Some((None, String::from("done"))));
+
+        // Other OSC 777 extensions are ignored.
+        handler.notification = None;
+        parser.advance(&mut handler, b"\x1b]777;precmd\x07");
+        assert_eq!(handler.notification, None);
+    }
+
     #[test]
     fn parse_osc104_reset_color() {
         let bytes: &[u8] = b"\x1b]104;1;\x1b\\";
@@ -1310,4 +2312,182 @@ mod tests {
         let expected: Vec<usize> = (0..256).collect();
         assert_eq!(handler.reset_colors, expected);
     }
+
+    #[test]
+    fn sync_timeout_default() {
+        let parser = Processor::new();
+
+        assert_eq!(parser.sync_timeout_duration(), SYNC_UPDATE_TIMEOUT);
+    }
+
+    #[test]
+    fn sync_timeout_override() {
+        let duration = Duration::from_millis(10);
+
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+        parser.set_sync_timeout(duration);
+        assert_eq!(parser.sync_timeout_duration(), duration);
+
+        let before = Instant::now();
+        parser.advance(&mut handler, b"\x1b[?2026h");
+        let after = Instant::now();
+
+        let deadline = parser.sync_timeout().sync_timeout().unwrap();
+        assert!(deadline >= before + duration);
+        assert!(deadline <= after + duration);
+
+        // Extending the update uses the same duration.
+        let before = Instant::now();
+        parser.advance(&mut handler, b"\x1b[?2026h");
+        let after = Instant::now();
+
+        let deadline = parser.sync_timeout().sync_timeout().unwrap();
+        assert!(deadline >= before + duration);
+        assert!(deadline <= after + duration);
+    }
+
+    #[test]
+    fn sync_buffer_overflow_terminates_once() {
+        let mut parser = Processor::with_sync_buffer_size(16);
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b[?2026h");
+        assert!(parser.sync_timeout().sync_timeout().is_some());
+
+        for _ in 0..32 {
+            parser.advance(&mut handler, b"a");
+        }
+
+        assert_eq!(handler.sync_terminations, 1);
+        assert_eq!(parser.sync_bytes_count(), 0);
+        assert!(parser.sync_timeout().sync_timeout().is_none());
+    }
+
+    #[test]
+    fn sync_buffer_size_zero() {
+        let mut parser = Processor::with_sync_buffer_size(0);
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b[?2026h");
+        parser.advance(&mut handler, b"abc");
+        parser.advance(&mut handler, b"def");
+
+        assert_eq!(handler.sync_terminations, 1);
+        assert_eq!(parser.sync_bytes_count(), 0);
+    }
+
+    #[test]
+    fn handler_perform_with_raw_parser() {
+        let mut parser = crate::Parser::new();
+        let mut perform = HandlerPerform::new(MockHandler::default());
+
+        parser.advance(&mut perform, b"\x1b[1m\x1b(0\x1b[?2026h\x1b[?2026l");
+
+        let handler = perform.into_inner();
+        assert_eq!(handler.attr, Some(Attribute::Bold));
+        assert_eq!(handler.index, CharsetIndex::G0);
+        assert_eq!(handler.charset, Charset::SpecialCharacterAndLineDrawing);
+        assert_eq!(handler.sync_terminations, 1);
+    }
+
+    #[test]
+    fn parse_decrqss_sgr() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1bP$qm\x1b\\");
+
+        assert_eq!(
+            handler.status_string_requests,
+            vec![StatusStringRequest::Sgr]
+        );
+    }
+
+    #[test]
+    fn parse_decrqss_settings() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(
+            &mut handler,
+            b"\x1bP$qr\x1b\\\x1bP$q q\x1b\\\x1bP$q\"p\x1b\\",
+        );
+
+        assert_eq!(
+            handler.status_string_requests,
+            vec![
+                StatusStringRequest::ScrollingRegion,
+                StatusStringRequest::CursorStyle,
+                StatusStringRequest::Unknown(b"\"p".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_unrelated_dcs() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1bP1;2|m\x1b\\\x1bPq#0\x1b\\");
+
+        assert!(handler.status_string_requests.is_empty());
+    }
+
+    #[test]
+    fn parse_xtgettcap() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        // "Tc"
+        parser.advance(&mut handler, b"\x1bP+q5463\x1b\\");
+
+        assert_eq!(handler.termcap_requests, vec![vec![String::from("Tc")]]);
+    }
+
+    #[test]
+    fn parse_xtgettcap_multiple() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        // "RGB;colors;<invalid>"
+        parser.advance(&mut handler, b"\x1bP+q524742;636f6c6f7273;7A7\x1b\\");
+
+        let names = vec![String::from("RGB"), String::from("colors"), String::new()];
+        assert_eq!(handler.termcap_requests, vec![names]);
+    }
+
+    #[test]
+    fn reset_parser_discards_partial_sequence() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b[1");
+        parser.parser_mut().reset();
+
+        // The final byte is printed instead of completing the SGR sequence.
+        parser.advance(&mut handler, b"m");
+        assert_eq!(handler.attr, None);
+
+        parser.advance(&mut handler, b"\x1b[1m");
+        assert_eq!(handler.attr, Some(Attribute::Bold));
+    }
+
+    #[test]
+    fn reset_parser_keeps_sync_state() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, b"\x1b[?2026h");
+        parser.advance(&mut handler, b"abc");
+        parser.parser_mut().reset();
+
+        assert!(parser.sync_timeout().sync_timeout().is_some());
+        assert_eq!(parser.sync_bytes_count(), 3);
+
+        parser.advance(&mut handler, b"\x1b[?2026l");
+
+        assert_eq!(parser.sync_bytes_count(), 0);
+        assert!(parser.sync_timeout().sync_timeout().is_none());
+    }
 }
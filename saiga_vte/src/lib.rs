@@ -1,15 +1,22 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![feature(test)]
 #![feature(portable_simd)]
 
+extern crate alloc;
+
 pub mod ansi;
 pub mod param;
 
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+
 mod table;
 mod utf8;
 
+use alloc::vec::Vec;
+
 use ansi::c0;
 use param::{Params, Subparam, PARAM_SEPARATOR};
-use std::cmp::min;
 use table::{Action, State};
 
 /// X3.64 doesn’t place any limit on the number of intermediate characters allowed before a final character,
@@ -26,6 +33,17 @@ pub trait Executor {
     /// Draw a character to the screen.
     fn print(&mut self, c: char);
 
+    /// Draw a run of characters to the screen.
+    ///
+    /// Called for runs of valid UTF-8 between control functions. Defaults to calling
+    /// [`Executor::print`] for every character.
+    #[inline]
+    fn print_str(&mut self, s: &str) {
+        for c in s.chars() {
+            self.print(c);
+        }
+    }
+
     /// Execute C0 or C1 control function
     fn execute(&mut self, byte: u8);
 
@@ -53,6 +71,13 @@ pub trait Executor {
     /// Dispatch an operating system command.
     fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool);
 
+    /// A C0 control arrived inside an operating system command, before it either terminates the
+    /// command or gets ignored.
+    ///
+    /// Only called once enabled with [`Parser::set_report_osc_controls`].
+    #[inline]
+    fn osc_control(&mut self, _byte: u8) {}
+
     /// The final character of an escape sequence has arrived.
     ///
     /// The `ignore` flag indicates that more than two intermediates arrived and
@@ -66,6 +91,105 @@ pub trait Executor {
     /// and subsequent characters were ignored.
     fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char);
 }
+
+/// Treatment of 8-bit C1 control bytes (0x80–0x9F) which are not part of a UTF-8 sequence.
+///
+/// Unless executed, C1 bytes inside control strings are kept as string data.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum C1Handling {
+    /// Execute the control function, or start the sequence it introduces.
+    #[default]
+    Execute,
+
+    /// Drop the byte.
+    Ignore,
+
+    /// Print the byte as the Latin-1 character with the same code point.
+    Print,
+
+    /// Print the replacement character instead of the byte.
+    Replace,
+}
+
+/// Counts of what a call to [`Parser::advance_counting`] produced.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AdvanceStats {
+    /// Printed characters.
+    pub prints: usize,
+
+    /// Executed control functions.
+    pub executes: usize,
+
+    /// Dispatched escape sequences, CSI, OSC and DCS.
+    pub dispatches: usize,
+}
+
+/// [`Executor`] counting the calls before forwarding them.
+struct CountingExecutor<'a, E> {
+    executor: &'a mut E,
+    stats: AdvanceStats,
+}
+
+impl<E: Executor> Executor for CountingExecutor<'_, E> {
+    #[inline]
+    fn print(&mut self, c: char) {
+        self.stats.prints += 1;
+        self.executor.print(c);
+    }
+
+    #[inline]
+    fn print_str(&mut self, s: &str) {
+        self.stats.prints += s.chars().count();
+        self.executor.print_str(s);
+    }
+
+    #[inline]
+    fn execute(&mut self, byte: u8) {
+        self.stats.executes += 1;
+        self.executor.execute(byte);
+    }
+
+    #[inline]
+    fn put(&mut self, byte: u8) {
+        self.executor.put(byte);
+    }
+
+    #[inline]
+    fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        self.stats.dispatches += 1;
+        self.executor.hook(params, intermediates, ignore, action);
+    }
+
+    #[inline]
+    fn unhook(&mut self) {
+        self.executor.unhook();
+    }
+
+    #[inline]
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        self.stats.dispatches += 1;
+        self.executor.osc_dispatch(params, bell_terminated);
+    }
+
+    #[inline]
+    fn osc_control(&mut self, byte: u8) {
+        self.executor.osc_control(byte);
+    }
+
+    #[inline]
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        self.stats.dispatches += 1;
+        self.executor.esc_dispatch(intermediates, ignore, byte);
+    }
+
+    #[inline]
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        self.stats.dispatches += 1;
+        self.executor
+            .csi_dispatch(params, intermediates, ignore, action);
+    }
+}
+
 #[derive(Default)]
 pub struct Intermediates {
     array: [u8; MAX_INTERMEDIATES],
@@ -163,12 +287,11 @@ impl OscHandler {
         let slices: Vec<&[u8]> = self
             .params
             .iter()
+            .take(self.params_num)
             .map(|(start, end)| &self.raw[*start..*end])
             .collect();
 
-        let params = &slices[..self.params_num];
-
-        executor.osc_dispatch(params, byte == ansi::c0::BEL)
+        executor.osc_dispatch(&slices, byte == ansi::c0::BEL)
     }
 }
 
@@ -180,12 +303,19 @@ pub struct Parser {
 
     params: Params,
     subparam: Subparam,
+    subparam_digits: bool,
 
     intermediate_handler: Intermediates,
 
     ignoring: bool,
 
+    c1_handling: C1Handling,
+
+    report_osc_controls: bool,
+
     utf8: utf8::UTF8Collector,
+
+    last_complete_offset: usize,
 }
 
 impl Parser {
@@ -193,23 +323,97 @@ impl Parser {
         Self::default()
     }
 
+    /// Discard any partially parsed sequence and return to the ground state.
+    ///
+    /// Configuration like the C1 handling is kept.
+    pub fn reset(&mut self) {
+        *self = Self {
+            c1_handling: self.c1_handling,
+            report_osc_controls: self.report_osc_controls,
+            ..Self::default()
+        };
+    }
+
+    /// Create a parser which either executes C1 control bytes, or prints them as the replacement
+    /// character.
+    ///
+    /// Transmission is enabled by default.
+    pub fn with_c1_transmission(enabled: bool) -> Self {
+        let c1_handling = if enabled {
+            C1Handling::Execute
+        } else {
+            C1Handling::Replace
+        };
+
+        Self {
+            c1_handling,
+            ..Self::default()
+        }
+    }
+
+    /// Treatment of C1 control bytes.
+    pub fn c1_handling(&self) -> C1Handling {
+        self.c1_handling
+    }
+
+    /// Change the treatment of C1 control bytes.
+    pub fn set_c1_handling(&mut self, handling: C1Handling) {
+        self.c1_handling = handling;
+    }
+
+    /// Whether C0 controls inside OSC strings are reported with [`Executor::osc_control`].
+    pub fn reports_osc_controls(&self) -> bool {
+        self.report_osc_controls
+    }
+
+    /// Report C0 controls inside OSC strings with [`Executor::osc_control`], which is useful for
+    /// debugging protocols. Disabled by default.
+    pub fn set_report_osc_controls(&mut self, enabled: bool) {
+        self.report_osc_controls = enabled;
+    }
+
+    /// Offset into the bytes of the last [`Parser::advance`] call right after the last fully
+    /// processed sequence.
+    ///
+    /// Everything past it belongs to an unterminated escape sequence or UTF-8 character. The
+    /// parser holds on to that tail itself and finishes it on the next call, so callers must not
+    /// feed it again; they should only avoid forwarding those bytes elsewhere, e.g. to a
+    /// recording, until the sequence completes.
+    pub fn last_complete_offset(&self) -> usize {
+        self.last_complete_offset
+    }
+
     pub fn advance<E: Executor>(&mut self, executor: &mut E, bytes: &[u8]) {
+        self.last_complete_offset = 0;
+
         let mut i = 0;
 
         while self.in_escape_sequence() && i < bytes.len() {
             self.advance_sequence(executor, bytes[i]);
             i += 1;
+
+            if !self.in_escape_sequence() {
+                self.last_complete_offset = i;
+            }
         }
 
         let mut remaining_bytes = &bytes[i..];
+        let mut offset = i;
 
         while !remaining_bytes.is_empty() {
-            let Some(next_sequence_start) = c0::first_index_of_c0(remaining_bytes) else {
-                self.advance_utf8(executor, remaining_bytes);
-                return;
-            };
+            let next_sequence_start =
+                c0::first_index_of_c0(remaining_bytes).unwrap_or(remaining_bytes.len());
 
-            self.advance_utf8(executor, &remaining_bytes[..next_sequence_start]);
+            // Stops early at a C1 control which has to be executed.
+            let next_sequence_start =
+                self.advance_utf8(executor, &remaining_bytes[..next_sequence_start]);
+
+            if next_sequence_start == remaining_bytes.len() {
+                // A trailing partial UTF-8 character is not complete yet.
+                self.last_complete_offset =
+                    (offset + next_sequence_start).saturating_sub(self.utf8.pending_count());
+                return;
+            }
 
             if self.utf8.remaining_count > 0 {
                 executor.print(char::REPLACEMENT_CHARACTER);
@@ -217,6 +421,8 @@ impl Parser {
             }
 
             remaining_bytes = &remaining_bytes[next_sequence_start..];
+            offset += next_sequence_start;
+            self.last_complete_offset = offset;
 
             let mut i = 0;
 
@@ -224,50 +430,91 @@ impl Parser {
                 self.advance_sequence(executor, remaining_bytes[i]);
                 i += 1;
 
+                if !self.in_escape_sequence() {
+                    self.last_complete_offset = offset + i;
+                }
+
                 if !(self.in_escape_sequence() && i < remaining_bytes.len()) {
                     break;
                 }
             }
 
             remaining_bytes = &remaining_bytes[i..];
+            offset += i;
         }
     }
 
-    fn advance_utf8<E: Executor>(&mut self, executor: &mut E, bytes: &[u8]) {
-        let mut remaining_bytes = bytes;
+    /// Same as [`Parser::advance`], also counting what the bytes produced.
+    ///
+    /// The counting is done by wrapping the executor, so [`Parser::advance`] itself stays free of
+    /// any overhead.
+    pub fn advance_counting<E: Executor>(
+        &mut self,
+        executor: &mut E,
+        bytes: &[u8],
+    ) -> AdvanceStats {
+        let mut executor = CountingExecutor {
+            executor,
+            stats: AdvanceStats::default(),
+        };
+
+        self.advance(&mut executor, bytes);
 
-        while !remaining_bytes.is_empty() {
-            let want_bytes_count: usize;
+        executor.stats
+    }
+
+    /// Print `bytes`, returning how many of them were consumed.
+    fn advance_utf8<E: Executor>(&mut self, executor: &mut E, bytes: &[u8]) -> usize {
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let byte = bytes[i];
 
             if self.utf8.remaining_count > 0 {
-                want_bytes_count = self.utf8.remaining_count
-            } else if let Some(count) = utf8::expected_bytes_count(remaining_bytes[0]) {
-                // Optimize for ASCII
-                if count == 1 {
-                    executor.print(remaining_bytes[0] as char);
-                    remaining_bytes = &remaining_bytes[1..];
+                // The sequence was cut short, so the byte starts over on its own.
+                if !self.utf8.continues_with(byte) {
+                    executor.print(char::REPLACEMENT_CHARACTER);
+                    self.utf8.reset();
                     continue;
                 }
 
-                want_bytes_count = count;
-            } else {
-                want_bytes_count = 1;
-            }
+                self.utf8.push(byte);
+                self.utf8.remaining_count -= 1;
+                i += 1;
 
-            let bytes_count = min(want_bytes_count, remaining_bytes.len());
+                if self.utf8.remaining_count == 0 {
+                    self.consume_utf8(executor);
+                }
 
-            for b in remaining_bytes.iter().take(bytes_count) {
-                self.utf8.push(*b);
+                continue;
             }
 
-            self.utf8.remaining_count = want_bytes_count - bytes_count;
+            let valid = utf8::valid_prefix(&bytes[i..]);
+            if !valid.is_empty() {
+                executor.print_str(valid);
+                i += valid.len();
+                continue;
+            }
 
-            if self.utf8.remaining_count == 0 {
-                self.consume_utf8(executor);
+            match utf8::expected_bytes_count(byte) {
+                Some(1) => executor.print(byte as char),
+                Some(count) => {
+                    self.utf8.push(byte);
+                    self.utf8.remaining_count = count - 1;
+                }
+                None if matches!(byte, 0x80..=0x9F) => match self.c1_handling {
+                    C1Handling::Execute => return i,
+                    C1Handling::Ignore => (),
+                    C1Handling::Print => executor.print(byte as char),
+                    C1Handling::Replace => executor.print(char::REPLACEMENT_CHARACTER),
+                },
+                None => executor.print(char::REPLACEMENT_CHARACTER),
             }
 
-            remaining_bytes = &remaining_bytes[bytes_count..];
+            i += 1;
         }
+
+        bytes.len()
     }
 
     fn consume_utf8<E: Executor>(&mut self, executor: &mut E) {
@@ -277,8 +524,18 @@ impl Parser {
     }
 
     fn advance_sequence<E: Executor>(&mut self, executor: &mut E, byte: u8) {
-        let change = table::change_state(State::Anywhere, byte)
-            .or_else(|| table::change_state(self.state, byte));
+        if self.report_osc_controls && self.state == State::OscString && byte < 0x20 {
+            executor.osc_control(byte);
+        }
+
+        let c1 = matches!(byte, 0x80..=0x9F);
+
+        let change = if c1 && self.c1_handling != C1Handling::Execute {
+            table::change_state(self.state, byte)
+        } else {
+            table::change_state(State::Anywhere, byte)
+                .or_else(|| table::change_state(self.state, byte))
+        };
 
         let Some((state, action)) = change else {
             return;
@@ -363,7 +620,7 @@ impl Parser {
                 if self.params.is_full() {
                     self.ignoring = true;
                 } else {
-                    self.params.push_subparam(self.subparam);
+                    self.push_subparam();
                     self.params.next_param();
                 }
 
@@ -383,17 +640,24 @@ impl Parser {
 
                 match byte {
                     param::PARAM_SEPARATOR => {
-                        self.params.push_subparam(self.subparam);
+                        self.push_subparam();
                         self.params.next_param();
-                        self.subparam = Subparam::default();
                     }
                     param::SUBPARAM_SEPARATOR => {
-                        self.params.push_subparam(self.subparam);
-                        self.subparam = Subparam::default();
+                        self.push_subparam();
                     }
                     byte => {
+                        // The state table only routes digits and separators to this action, other
+                        // bytes are dropped rather than wrapping into the parameter.
+                        debug_assert!(byte.is_ascii_digit(), "invalid parameter byte {byte:#04x}");
+                        let digit = byte.wrapping_sub(b'0');
+                        if digit > 9 {
+                            return;
+                        }
+
                         self.subparam = self.subparam.saturating_mul(10);
-                        self.subparam = self.subparam.saturating_add((byte - b'0').into());
+                        self.subparam = self.subparam.saturating_add(digit.into());
+                        self.subparam_digits = true;
                     }
                 };
             }
@@ -401,7 +665,7 @@ impl Parser {
                 if self.params.is_full() {
                     self.ignoring = true
                 } else {
-                    self.params.push_subparam(self.subparam);
+                    self.push_subparam();
                     self.params.next_param();
                 }
 
@@ -424,6 +688,7 @@ impl Parser {
             }
             Clear => {
                 self.subparam = Subparam::default();
+                self.subparam_digits = false;
                 self.params.clear();
 
                 self.ignoring = false;
@@ -433,104 +698,268 @@ impl Parser {
             Ignore => (),
         }
     }
+
+    /// Push the pending subparameter and start a new one.
+    fn push_subparam(&mut self) {
+        if self.params.is_current_full() {
+            self.ignoring = true;
+        }
+
+        if self.subparam_digits {
+            self.params.push_subparam(self.subparam);
+        } else {
+            self.params.push_omitted_subparam();
+        }
+
+        self.subparam = Subparam::default();
+        self.subparam_digits = false;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::{Recorder, Sequence};
 
-    #[derive(Default)]
-    struct Dispatcher {
-        dispatched: Vec<Sequence>,
-    }
+    mod c0_or_c1 {
+        use std::iter;
 
-    #[derive(Debug, PartialEq, Eq)]
-    enum Sequence {
-        Osc(Vec<Vec<u8>>, bool),
-        Csi(Vec<Vec<u16>>, Vec<u8>, bool, char),
-        Esc(Vec<u8>, bool, u8),
-        DcsHook(Vec<Vec<u16>>, Vec<u8>, bool, char),
-        DcsPut(u8),
-        DcsUnhook,
-        Execute(u8),
-        Print(char),
-    }
+        use super::*;
 
-    impl Executor for Dispatcher {
-        fn print(&mut self, c: char) {
-            self.dispatched.push(Sequence::Print(c));
-        }
+        #[test]
+        fn all() {
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
 
-        fn execute(&mut self, byte: u8) {
-            self.dispatched.push(Sequence::Execute(byte))
+            parser.advance(&mut dispatcher, b"\x07\x08\x00");
+
+            assert_eq!(
+                dispatcher.sequences,
+                vec![
+                    Sequence::Execute(0x07),
+                    Sequence::Execute(0x08),
+                    Sequence::Execute(0x00),
+                ]
+            )
         }
 
-        fn put(&mut self, byte: u8) {
-            self.dispatched.push(Sequence::DcsPut(byte));
+        fn c1_with(handling: C1Handling) -> Vec<Sequence> {
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+            parser.set_c1_handling(handling);
+
+            parser.advance(&mut dispatcher, b"a\x85b");
+
+            dispatcher.sequences
         }
 
-        fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, c: char) {
-            let params = params
-                .as_slice()
-                .iter()
-                .map(|param| param.as_slice().to_vec())
-                .collect();
+        #[test]
+        fn c1_execute() {
+            assert_eq!(
+                c1_with(C1Handling::Execute),
+                vec![
+                    Sequence::Print('a'),
+                    Sequence::Execute(0x85),
+                    Sequence::Print('b')
+                ]
+            );
+        }
 
-            let intermediates = intermediates.to_vec();
+        #[test]
+        fn c1_ignore() {
+            assert_eq!(
+                c1_with(C1Handling::Ignore),
+                vec![Sequence::Print('a'), Sequence::Print('b')]
+            );
+        }
 
-            self.dispatched
-                .push(Sequence::DcsHook(params, intermediates, ignore, c));
+        #[test]
+        fn c1_print() {
+            assert_eq!(
+                c1_with(C1Handling::Print),
+                vec![
+                    Sequence::Print('a'),
+                    Sequence::Print('\u{85}'),
+                    Sequence::Print('b')
+                ]
+            );
         }
 
-        fn unhook(&mut self) {
-            self.dispatched.push(Sequence::DcsUnhook);
+        #[test]
+        fn c1_transmission() {
+            let c1s: Vec<u8> = (0x80..=0x9F).collect();
+
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::with_c1_transmission(false);
+            parser.advance(&mut dispatcher, &c1s);
+
+            let replaced = iter::repeat_with(|| Sequence::Print(char::REPLACEMENT_CHARACTER));
+            let replaced: Vec<_> = replaced.take(c1s.len()).collect();
+            assert_eq!(dispatcher.sequences, replaced);
+
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::with_c1_transmission(true);
+            parser.advance(&mut dispatcher, b"\x85\x88");
+
+            assert_eq!(parser.c1_handling(), C1Handling::Execute);
+            assert_eq!(
+                dispatcher.sequences,
+                vec![Sequence::Execute(0x85), Sequence::Execute(0x88)]
+            );
         }
 
-        fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
-            let params = params.iter().map(|p| p.to_vec()).collect();
+        #[test]
+        fn c1_inside_utf8_sequence() {
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+
+            // U+0145 is encoded as 0xC5 0x85.
+            parser.advance(&mut dispatcher, "Ņ".as_bytes());
 
-            self.dispatched.push(Sequence::Osc(params, bell_terminated));
+            assert_eq!(dispatcher.sequences, vec![Sequence::Print('Ņ')]);
         }
 
-        fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
-            let intermediates = intermediates.to_vec();
+        #[test]
+        fn c1_st_after_partial_utf8() {
+            // 0xE0 can't be followed by 0x9C, the partial character is flushed before the ST.
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+
+            parser.advance(&mut dispatcher, b"\xE0\x9C");
+
+            assert_eq!(
+                dispatcher.sequences,
+                vec![Sequence::Print(char::REPLACEMENT_CHARACTER)]
+            );
+            assert_eq!(parser.last_complete_offset(), 2);
 
-            self.dispatched
-                .push(Sequence::Esc(intermediates, ignore, byte));
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+            parser.set_c1_handling(C1Handling::Print);
+
+            parser.advance(&mut dispatcher, b"\xE0\x9Cx");
+
+            assert_eq!(
+                dispatcher.sequences,
+                vec![
+                    Sequence::Print(char::REPLACEMENT_CHARACTER),
+                    Sequence::Print('\u{9c}'),
+                    Sequence::Print('x')
+                ]
+            );
         }
 
-        fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, c: char) {
-            let params = params
-                .as_slice()
-                .iter()
-                .map(|param| param.as_slice().to_vec())
-                .collect();
+        #[test]
+        fn c1_csi_introducer() {
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
 
-            let intermediates = intermediates.to_vec();
+            parser.advance(&mut dispatcher, b"\x9b1m");
 
-            self.dispatched
-                .push(Sequence::Csi(params, intermediates, ignore, c));
+            assert_eq!(
+                dispatcher.sequences,
+                vec![Sequence::Csi(vec![vec![1]], vec![], false, 'm')]
+            );
         }
     }
 
-    mod c0_or_c1 {
+    mod print {
         use super::*;
 
-        #[test]
-        fn all() {
-            let mut dispatcher = Dispatcher::default();
+        fn advance_chunks(chunks: &[&[u8]]) -> Vec<Sequence> {
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
-            parser.advance(&mut dispatcher, b"\x07\x08\x00");
+            for chunk in chunks {
+                parser.advance(&mut dispatcher, chunk);
+            }
+
+            dispatcher.sequences
+        }
 
+        #[test]
+        fn codepoint_split_across_calls() {
+            assert_eq!(
+                advance_chunks(&[b"\xC3", b"\xA9"]),
+                vec![Sequence::Print('é')]
+            );
             assert_eq!(
-                dispatcher.dispatched,
+                advance_chunks(&[b"\xE2", b"\x82\xAC"]),
+                vec![Sequence::Print('€')]
+            );
+            assert_eq!(
+                advance_chunks(&[b"\xE2\x82", b"\xAC"]),
+                vec![Sequence::Print('€')]
+            );
+            assert_eq!(
+                advance_chunks(&[b"\xF0", b"\x9F", b"\xA6", b"\x80"]),
+                vec![Sequence::Print('🦀')]
+            );
+        }
+
+        #[test]
+        fn invalid_lead_bytes() {
+            let replaced_before_a = vec![
+                Sequence::Print(char::REPLACEMENT_CHARACTER),
+                Sequence::Print('a'),
+            ];
+
+            assert_eq!(advance_chunks(&[b"\xFFa"]), replaced_before_a);
+            // Overlong encoding of '/'.
+            assert_eq!(advance_chunks(&[b"\xC0\xAFa"]), replaced_before_a);
+            // Past U+10FFFF.
+            assert_eq!(advance_chunks(&[b"\xF5\x80\x80\x80a"]), replaced_before_a);
+        }
+
+        #[test]
+        fn split_codepoint_between_text() {
+            assert_eq!(
+                advance_chunks(&[b"a\xD0", b"\xB6b"]),
                 vec![
-                    Sequence::Execute(0x07),
-                    Sequence::Execute(0x08),
-                    Sequence::Execute(0x00),
+                    Sequence::Print('a'),
+                    Sequence::Print('ж'),
+                    Sequence::Print('b')
                 ]
-            )
+            );
+        }
+
+        #[test]
+        fn split_codepoint_followed_by_ascii() {
+            assert_eq!(
+                advance_chunks(&[b"\xE2", b"\x82\xACab"]),
+                vec![
+                    Sequence::Print('€'),
+                    Sequence::Print('a'),
+                    Sequence::Print('b')
+                ]
+            );
+        }
+
+        #[test]
+        fn interrupted_codepoint() {
+            assert_eq!(
+                advance_chunks(&[b"\xE2", b"ab"]),
+                vec![
+                    Sequence::Print(char::REPLACEMENT_CHARACTER),
+                    Sequence::Print('a'),
+                    Sequence::Print('b')
+                ]
+            );
+            assert_eq!(
+                advance_chunks(&[b"\xE2\x82\xC3\xA9"]),
+                vec![
+                    Sequence::Print(char::REPLACEMENT_CHARACTER),
+                    Sequence::Print('é')
+                ]
+            );
+        }
+
+        #[test]
+        fn split_codepoint_before_control() {
+            assert_eq!(
+                advance_chunks(&[b"\xE2\x82", b"\xAC\n"]),
+                vec![Sequence::Print('€'), Sequence::Execute(b'\n')]
+            );
         }
     }
 
@@ -546,13 +975,13 @@ mod tests {
 
         #[test]
         fn parse() {
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, OSC_BYTES);
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
-            match &dispatcher.dispatched[0] {
+            assert_eq!(dispatcher.sequences.len(), 1);
+            match &dispatcher.sequences[0] {
                 Sequence::Osc(params, _) => {
                     assert_eq!(params.len(), 2);
                     assert_eq!(params[0], &OSC_BYTES[2..3]);
@@ -562,15 +991,37 @@ mod tests {
             }
         }
 
+        #[test]
+        fn report_controls() {
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+
+            parser.advance(&mut dispatcher, b"\x1b]0;a\x01b\x07");
+            assert_eq!(dispatcher.sequences.len(), 1);
+
+            parser.set_report_osc_controls(true);
+            parser.advance(&mut dispatcher, b"\x1b]0;a\x01b\x07\x1b]0;c\x18");
+
+            dispatcher.assert_sequences(&[
+                Sequence::Osc(vec![b"0".to_vec(), b"ab".to_vec()], true),
+                Sequence::OscControl(0x01),
+                Sequence::OscControl(0x07),
+                Sequence::Osc(vec![b"0".to_vec(), b"ab".to_vec()], true),
+                Sequence::OscControl(0x18),
+                Sequence::Osc(vec![b"0".to_vec(), b"c".to_vec()], false),
+                Sequence::Execute(0x18),
+            ]);
+        }
+
         #[test]
         fn parse_empty() {
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, &[0x1b, 0x5d, 0x07]);
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
-            match &dispatcher.dispatched[0] {
+            assert_eq!(dispatcher.sequences.len(), 1);
+            match &dispatcher.sequences[0] {
                 Sequence::Osc(..) => (),
                 _ => panic!("expected osc sequence"),
             }
@@ -580,14 +1031,14 @@ mod tests {
         fn parse_max_params() {
             let params = ";".repeat(param::MAX_PARAMS + 1);
             let input = format!("\x1b]{}\x1b", &params[..]).into_bytes();
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, input.as_slice());
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
+            assert_eq!(dispatcher.sequences.len(), 1);
 
-            match &dispatcher.dispatched[0] {
+            match &dispatcher.sequences[0] {
                 Sequence::Osc(params, _) => {
                     assert_eq!(params.len(), MAX_OSC_PARAMS);
                     assert!(params.iter().all(Vec::is_empty));
@@ -602,7 +1053,7 @@ mod tests {
             static INPUT_START: &[u8] = &[0x1b, b']', b'5', b'2', b';', b's'];
             static INPUT_END: &[u8] = b"\x07";
 
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             // Create valid OSC escape
@@ -614,8 +1065,8 @@ mod tests {
             // Terminate escape for dispatch
             parser.advance(&mut dispatcher, INPUT_END);
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
-            match &dispatcher.dispatched[0] {
+            assert_eq!(dispatcher.sequences.len(), 1);
+            match &dispatcher.sequences[0] {
                 Sequence::Osc(params, _) => {
                     assert_eq!(params.len(), 2);
                     assert_eq!(params[0], b"52");
@@ -628,13 +1079,13 @@ mod tests {
         #[test]
         fn bell_terminated() {
             static INPUT: &[u8] = b"\x1b]11;ff/00/ff\x07";
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, INPUT);
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
-            match &dispatcher.dispatched[0] {
+            assert_eq!(dispatcher.sequences.len(), 1);
+            match &dispatcher.sequences[0] {
                 Sequence::Osc(_, true) => (),
                 _ => panic!("expected osc with bell terminator"),
             }
@@ -652,13 +1103,13 @@ mod tests {
             let params = "1;".repeat(param::MAX_PARAMS - 1);
             let input = format!("\x1b[{}p", &params[..]).into_bytes();
 
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, &input);
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
-            match &dispatcher.dispatched[0] {
+            assert_eq!(dispatcher.sequences.len(), 1);
+            match &dispatcher.sequences[0] {
                 Sequence::Csi(params, _, ignore, _) => {
                     assert_eq!(params.len(), param::MAX_PARAMS);
                     assert!(!ignore);
@@ -675,13 +1126,13 @@ mod tests {
             let params = "1;".repeat(param::MAX_PARAMS);
             let input = format!("\x1b[{}p", &params[..]).into_bytes();
 
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, &input);
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
-            match &dispatcher.dispatched[0] {
+            assert_eq!(dispatcher.sequences.len(), 1);
+            match &dispatcher.sequences[0] {
                 Sequence::Csi(params, _, ignore, _) => {
                     assert_eq!(params.len(), param::MAX_PARAMS);
                     assert!(ignore);
@@ -692,29 +1143,70 @@ mod tests {
 
         #[test]
         fn parse_params_trailing_semicolon() {
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, b"\x1b[4;m");
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
+            assert_eq!(dispatcher.sequences.len(), 1);
 
-            match &dispatcher.dispatched[0] {
+            match &dispatcher.sequences[0] {
                 Sequence::Csi(params, ..) => assert_eq!(params, &[[4], [0]]),
                 _ => panic!("expected csi sequence"),
             }
         }
 
+        #[test]
+        fn params_distinguish_omitted_from_zero() {
+            #[derive(Default)]
+            struct Defaults(Vec<Vec<(usize, Option<u16>)>>);
+
+            impl Executor for Defaults {
+                fn print(&mut self, _c: char) {}
+                fn execute(&mut self, _byte: u8) {}
+                fn put(&mut self, _byte: u8) {}
+                fn hook(
+                    &mut self,
+                    _params: &Params,
+                    _intermediates: &[u8],
+                    _ignore: bool,
+                    _c: char,
+                ) {
+                }
+                fn unhook(&mut self) {}
+                fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {}
+                fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, _byte: u8) {}
+
+                fn csi_dispatch(&mut self, params: &Params, _: &[u8], _: bool, _: char) {
+                    self.0.push(params.iter_with_defaults().collect());
+                }
+            }
+
+            let mut defaults = Defaults::default();
+            let mut parser = Parser::new();
+
+            parser.advance(&mut defaults, b"\x1b[;5m\x1b[0;5m\x1b[5m");
+
+            assert_eq!(
+                defaults.0,
+                [
+                    vec![(0, None), (1, Some(5))],
+                    vec![(0, Some(0)), (1, Some(5))],
+                    vec![(0, Some(5))],
+                ]
+            );
+        }
+
         #[test]
         fn parse_params_leading_semicolon() {
             // Create dispatcher and check state
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, b"\x1b[;4m");
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
-            match &dispatcher.dispatched[0] {
+            assert_eq!(dispatcher.sequences.len(), 1);
+            match &dispatcher.sequences[0] {
                 Sequence::Csi(params, ..) => assert_eq!(params, &[[0], [4]]),
                 _ => panic!("expected csi sequence"),
             }
@@ -725,29 +1217,124 @@ mod tests {
             // The important part is the parameter, which is (i64::MAX + 1)
             static INPUT: &[u8] = b"\x1b[9223372036854775808m";
 
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, INPUT);
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
-            match &dispatcher.dispatched[0] {
+            assert_eq!(dispatcher.sequences.len(), 1);
+            match &dispatcher.sequences[0] {
                 Sequence::Csi(params, ..) => assert_eq!(params, &[[u16::MAX]]),
                 _ => panic!("expected csi sequence"),
             }
         }
 
+        #[test]
+        fn ignore_reset_after_long_params() {
+            let params = "1;".repeat(param::MAX_PARAMS);
+            let input = format!("\x1b[{}p\x1b[2;3H", &params[..]).into_bytes();
+
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+
+            parser.advance(&mut dispatcher, &input);
+
+            assert_eq!(dispatcher.sequences.len(), 2);
+            match &dispatcher.sequences[0] {
+                Sequence::Csi(_, _, ignore, 'p') => assert!(ignore),
+                _ => panic!("expected csi sequence"),
+            }
+            match &dispatcher.sequences[1] {
+                Sequence::Csi(params, _, ignore, 'H') => {
+                    assert_eq!(params, &[[2], [3]]);
+                    assert!(!ignore);
+                }
+                _ => panic!("expected csi sequence"),
+            }
+        }
+
+        #[test]
+        fn ignore_reset_across_advance_calls() {
+            let params = "1;".repeat(param::MAX_PARAMS);
+            let input = format!("\x1b[{}", &params[..]).into_bytes();
+
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+
+            parser.advance(&mut dispatcher, &input);
+            parser.advance(&mut dispatcher, b"p");
+            parser.advance(&mut dispatcher, b"\x1b[5m");
+
+            assert_eq!(dispatcher.sequences.len(), 2);
+            match &dispatcher.sequences[1] {
+                Sequence::Csi(params, _, ignore, 'm') => {
+                    assert_eq!(params, &[[5]]);
+                    assert!(!ignore);
+                }
+                _ => panic!("expected csi sequence"),
+            }
+        }
+
+        #[test]
+        fn ignore_reset_after_csi_ignore() {
+            // A parameter after an intermediate moves the parser into CsiIgnore,
+            // which swallows the sequence up to its final byte.
+            static INPUT: &[u8] = b"\x1b[1$2p\x1b[4h";
+
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+
+            parser.advance(&mut dispatcher, INPUT);
+
+            assert_eq!(dispatcher.sequences.len(), 1);
+            match &dispatcher.sequences[0] {
+                Sequence::Csi(params, intermediates, ignore, 'h') => {
+                    assert_eq!(params, &[[4]]);
+                    assert!(intermediates.is_empty());
+                    assert!(!ignore);
+                }
+                _ => panic!("expected csi sequence"),
+            }
+        }
+
+        #[test]
+        fn params_ignore_non_digits() {
+            // Controls are executed and DEL is ignored without touching the parameters.
+            static INPUT: &[u8] = b"\x1b[1\x082;3\x7f4:5m\x1b[65535999m";
+
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+
+            parser.advance(&mut dispatcher, INPUT);
+
+            let csi: Vec<_> = dispatcher
+                .sequences
+                .iter()
+                .filter_map(|sequence| match sequence {
+                    Sequence::Csi(params, _, ignore, 'm') => Some((params.clone(), *ignore)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(
+                csi,
+                vec![
+                    (vec![vec![12], vec![34, 5]], false),
+                    (vec![vec![u16::MAX]], false)
+                ]
+            );
+        }
+
         #[test]
         fn reset() {
             static INPUT: &[u8] = b"\x1b[3;1\x1b[?1049h";
 
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, INPUT);
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
-            match &dispatcher.dispatched[0] {
+            assert_eq!(dispatcher.sequences.len(), 1);
+            match &dispatcher.sequences[0] {
                 Sequence::Csi(params, intermediates, ignore, _) => {
                     assert_eq!(intermediates, b"?");
                     assert_eq!(params, &[[1049]]);
@@ -761,14 +1348,14 @@ mod tests {
         fn subparameters() {
             static INPUT: &[u8] = b"\x1b[38:2:255:0:255;1m";
 
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, INPUT);
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
+            assert_eq!(dispatcher.sequences.len(), 1);
 
-            match &dispatcher.dispatched[0] {
+            match &dispatcher.sequences[0] {
                 Sequence::Csi(params, intermediates, ignore, _) => {
                     assert_eq!(params, &[vec![38, 2, 255, 0, 255], vec![1]]);
                     assert_eq!(intermediates, &[]);
@@ -782,14 +1369,14 @@ mod tests {
         fn params_buffer_filled_with_subparam() {
             static INPUT: &[u8] = b"\x1b[::::::::::::::::::::::::::::::::;;;;;;;;;;;;;;;;x\x1b";
 
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, INPUT);
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
+            assert_eq!(dispatcher.sequences.len(), 1);
 
-            match &dispatcher.dispatched[0] {
+            match &dispatcher.sequences[0] {
                 Sequence::Csi(params, intermediates, ignore, c) => {
                     assert_eq!(intermediates, &[]);
                     assert_eq!(
@@ -819,6 +1406,32 @@ mod tests {
                 _ => panic!("expected csi sequence"),
             }
         }
+
+        #[test]
+        fn subparams_overflow() {
+            let mut input = b"\x1b[1".to_vec();
+            input.extend([b':'; 40]);
+            input.extend(b";2x");
+
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+
+            parser.advance(&mut dispatcher, &input);
+
+            assert_eq!(dispatcher.sequences.len(), 1);
+
+            match &dispatcher.sequences[0] {
+                Sequence::Csi(params, _, ignore, c) => {
+                    let mut first = vec![0; param::MAX_SUBPARAMS];
+                    first[0] = 1;
+
+                    assert_eq!(*params, vec![first, vec![2]]);
+                    assert_eq!(c, &'x');
+                    assert!(ignore);
+                }
+                _ => panic!("expected csi sequence"),
+            }
+        }
     }
 
     mod dcs {
@@ -828,13 +1441,13 @@ mod tests {
         fn parse_max_params() {
             let params = "1;".repeat(param::MAX_PARAMS + 1);
             let input = format!("\x1bP{}p", &params[..]).into_bytes();
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, &input);
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
-            match &dispatcher.dispatched[0] {
+            assert_eq!(dispatcher.sequences.len(), 1);
+            match &dispatcher.sequences[0] {
                 Sequence::DcsHook(params, _, ignore, _) => {
                     assert_eq!(params.len(), param::MAX_PARAMS);
                     assert!(params.iter().all(|param| param == &[1]));
@@ -847,14 +1460,14 @@ mod tests {
         #[test]
         fn reset() {
             static INPUT: &[u8] = b"\x1b[3;1\x1bP1$tx\x9c";
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, INPUT);
 
-            assert_eq!(dispatcher.dispatched.len(), 3);
+            assert_eq!(dispatcher.sequences.len(), 3);
 
-            match &dispatcher.dispatched[0] {
+            match &dispatcher.sequences[0] {
                 Sequence::DcsHook(params, intermediates, ignore, _) => {
                     assert_eq!(intermediates, b"$");
                     assert_eq!(params, &[[1]]);
@@ -863,21 +1476,21 @@ mod tests {
                 _ => panic!("expected dcs sequence"),
             }
 
-            assert_eq!(dispatcher.dispatched[1], Sequence::DcsPut(b'x'));
-            assert_eq!(dispatcher.dispatched[2], Sequence::DcsUnhook);
+            assert_eq!(dispatcher.sequences[1], Sequence::DcsPut(b'x'));
+            assert_eq!(dispatcher.sequences[2], Sequence::DcsUnhook);
         }
 
         #[test]
         fn parse() {
             static INPUT: &[u8] = b"\x1bP0;1|17/ab\x9c";
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, INPUT);
 
-            assert_eq!(dispatcher.dispatched.len(), 7);
+            assert_eq!(dispatcher.sequences.len(), 7);
 
-            match &dispatcher.dispatched[0] {
+            match &dispatcher.sequences[0] {
                 Sequence::DcsHook(params, _, _, c) => {
                     assert_eq!(params, &[[0], [1]]);
                     assert_eq!(c, &'|');
@@ -886,26 +1499,84 @@ mod tests {
             }
 
             for (i, byte) in b"17/ab".iter().enumerate() {
-                assert_eq!(dispatcher.dispatched[1 + i], Sequence::DcsPut(*byte));
+                assert_eq!(dispatcher.sequences[1 + i], Sequence::DcsPut(*byte));
             }
 
-            assert_eq!(dispatcher.dispatched[6], Sequence::DcsUnhook);
+            assert_eq!(dispatcher.sequences[6], Sequence::DcsUnhook);
         }
 
         #[test]
         fn intermediate_reset_on_exit() {
             static INPUT: &[u8] = b"\x1bP=1sZZZ\x1b+\x5c";
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, INPUT);
 
-            assert_eq!(dispatcher.dispatched.len(), 6);
-            match &dispatcher.dispatched[5] {
+            assert_eq!(dispatcher.sequences.len(), 6);
+            match &dispatcher.sequences[5] {
                 Sequence::Esc(intermediates, ..) => assert_eq!(intermediates, b"+"),
                 _ => panic!("expected esc sequence"),
             }
         }
+
+        #[test]
+        fn esc_st_terminated() {
+            let inputs: [&[&[u8]]; 2] = [&[b"\x1bPqab\x1b\\x"], &[b"\x1bPqab\x1b", b"\\x"]];
+            for chunks in inputs {
+                let mut dispatcher = Recorder::default();
+                let mut parser = Parser::new();
+
+                for chunk in chunks {
+                    parser.advance(&mut dispatcher, chunk);
+                }
+
+                assert_eq!(
+                    dispatcher.sequences,
+                    vec![
+                        Sequence::DcsHook(vec![vec![0]], vec![], false, 'q'),
+                        Sequence::DcsPut(b'a'),
+                        Sequence::DcsPut(b'b'),
+                        Sequence::DcsUnhook,
+                        Sequence::Esc(vec![], false, b'\\'),
+                        Sequence::Print('x'),
+                    ]
+                );
+            }
+        }
+    }
+
+    mod sos_pm_apc {
+        use super::*;
+
+        #[test]
+        fn esc_st_terminated() {
+            // SOS, PM and APC strings are consumed without being dispatched.
+            for introducer in *b"X^_" {
+                let mut dispatcher = Recorder::default();
+                let mut parser = Parser::new();
+
+                parser.advance(&mut dispatcher, &[0x1b, introducer]);
+                parser.advance(&mut dispatcher, b"Gf=24;abc\x1b\\x");
+
+                assert_eq!(
+                    dispatcher.sequences,
+                    vec![Sequence::Esc(vec![], false, b'\\'), Sequence::Print('x')],
+                    "{}",
+                    introducer as char
+                );
+            }
+        }
+
+        #[test]
+        fn c1_st_terminated() {
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+
+            parser.advance(&mut dispatcher, b"\x1b_abc\x9cx");
+
+            assert_eq!(dispatcher.sequences, vec![Sequence::Print('x')]);
+        }
     }
 
     mod esc {
@@ -914,13 +1585,13 @@ mod tests {
         #[test]
         fn reset() {
             static INPUT: &[u8] = b"\x1b[3;1\x1b(A";
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
             let mut parser = Parser::new();
 
             parser.advance(&mut dispatcher, INPUT);
 
-            assert_eq!(dispatcher.dispatched.len(), 1);
-            match &dispatcher.dispatched[0] {
+            assert_eq!(dispatcher.sequences.len(), 1);
+            match &dispatcher.sequences[0] {
                 Sequence::Esc(intermediates, ignore, byte) => {
                     assert_eq!(intermediates, b"(");
                     assert_eq!(*byte, b'A');
@@ -931,13 +1602,124 @@ mod tests {
         }
     }
 
+    #[test]
+    fn advance_counting() {
+        let mut dispatcher = Recorder::default();
+        let mut parser = Parser::new();
+
+        let stats = parser.advance_counting(
+            &mut dispatcher,
+            "ab\r\n\x1b[1m\x1b]0;title\x07\x1b7漢\x1bPqx\x1b\\\x1b[".as_bytes(),
+        );
+
+        let expected = AdvanceStats {
+            prints: 3,
+            executes: 2,
+            dispatches: 5,
+        };
+        assert_eq!(stats, expected);
+        assert_eq!(parser.advance_counting(&mut dispatcher, b"m").dispatches, 1);
+    }
+
+    /// The parser is usable without the `std` feature.
+    #[cfg(not(feature = "std"))]
+    #[test]
+    fn advance_without_std() {
+        let mut dispatcher = Recorder::default();
+        let mut parser = Parser::new();
+
+        parser.advance(&mut dispatcher, b"a\x1b[1m\x1b]0;t\x07");
+
+        assert_eq!(
+            dispatcher.sequences,
+            vec![
+                Sequence::Print('a'),
+                Sequence::Csi(vec![vec![1]], vec![], false, 'm'),
+                Sequence::Osc(vec![b"0".to_vec(), b"t".to_vec()], true),
+            ]
+        );
+    }
+
+    /// The processor is usable without the `std` feature, synchronized updates end on ESU.
+    #[cfg(not(feature = "std"))]
+    #[test]
+    fn process_without_std() {
+        use crate::ansi::handler::{Attribute, Handler};
+        use crate::ansi::processor::Processor;
+
+        #[derive(Default)]
+        struct Screen {
+            text: String,
+            attrs: Vec<Attribute>,
+        }
+
+        impl Handler for Screen {
+            fn input(&mut self, c: char) {
+                self.text.push(c);
+            }
+
+            fn terminal_attribute(&mut self, attr: Attribute) {
+                self.attrs.push(attr);
+            }
+        }
+
+        let mut processor = Processor::new();
+        let mut screen = Screen::default();
+
+        processor.advance(&mut screen, b"\x1b[?2026h");
+        processor.advance(&mut screen, b"a\x1b[1m");
+        assert!(screen.text.is_empty());
+
+        processor.advance(&mut screen, b"b\x1b[?2026l");
+        assert_eq!(screen.text, "ab");
+        assert_eq!(screen.attrs, vec![Attribute::Bold]);
+    }
+
+    mod last_complete_offset {
+        use super::*;
+
+        #[test]
+        fn before_unterminated_csi() {
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+
+            parser.advance(&mut dispatcher, b"abc\x1b[1");
+
+            assert_eq!(parser.last_complete_offset(), 3);
+        }
+
+        #[test]
+        fn after_sequence_completed_across_calls() {
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+
+            parser.advance(&mut dispatcher, b"\x1b[1");
+            assert_eq!(parser.last_complete_offset(), 0);
+
+            parser.advance(&mut dispatcher, b"mxy\x1b");
+            assert_eq!(parser.last_complete_offset(), 3);
+        }
+
+        #[test]
+        fn before_partial_utf8() {
+            let mut dispatcher = Recorder::default();
+            let mut parser = Parser::new();
+
+            parser.advance(&mut dispatcher, &[b'a', 0xE6, 0xBC]);
+            assert_eq!(parser.last_complete_offset(), 1);
+
+            parser.advance(&mut dispatcher, b"abc");
+            assert_eq!(parser.last_complete_offset(), 3);
+        }
+    }
+
     mod utf8 {
         use super::*;
 
         #[test]
         fn process_mixed() {
             let mut parser = Parser::new();
-            let mut dispatcher = Dispatcher::default();
+            let mut dispatcher = Recorder::default();
 
             parser.advance(&mut dispatcher, b"hello\x07\x1b[38:2:255:0:255;1m");
             parser.advance(&mut dispatcher, &[0xD0]);
@@ -946,7 +1728,7 @@ mod tests {
             parser.advance(&mut dispatcher, &[0xE6, 0xBC, 0x1B]); // abort utf8 sequence
 
             assert_eq!(
-                dispatcher.dispatched,
+                dispatcher.sequences,
                 vec![
                     Sequence::Print('h'),
                     Sequence::Print('e'),
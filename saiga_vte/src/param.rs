@@ -1,4 +1,8 @@
-use std::ops::Index;
+use core::{
+    fmt::{self, Debug, Display, Formatter},
+    ops::Index,
+    slice,
+};
 
 pub const MAX_PARAMS: usize = 16;
 pub const MAX_SUBPARAMS: usize = MAX_PARAMS * 2;
@@ -7,10 +11,16 @@ pub const SUBPARAM_SEPARATOR: u8 = b':';
 
 pub type Subparam = u16;
 
-#[derive(Default, Debug)]
+/// Subparameters of a single control sequence parameter.
+///
+/// A parameter holds at most [`MAX_SUBPARAMS`] subparameters. Further ones are dropped, and the
+/// parser marks the whole sequence as ignored, the same way it does when [`MAX_PARAMS`] is
+/// exceeded.
+#[derive(Default)]
 pub struct Param {
     array: [Subparam; MAX_SUBPARAMS],
     len: usize,
+    omitted: bool,
 }
 
 impl From<Subparam> for Param {
@@ -25,7 +35,8 @@ impl From<Subparam> for Param {
 
 impl Param {
     pub fn clear(&mut self) {
-        self.len = 0
+        self.len = 0;
+        self.omitted = false;
     }
 
     #[must_use]
@@ -47,6 +58,21 @@ impl Param {
         self.len += 1;
     }
 
+    /// Push a subparameter without any digits, which defaults to zero.
+    pub fn push_omitted(&mut self) {
+        if self.is_empty() {
+            self.omitted = true;
+        }
+
+        self.push(Subparam::default());
+    }
+
+    /// Whether the parameter was left empty, as opposed to an explicit zero.
+    #[must_use]
+    pub fn is_omitted(&self) -> bool {
+        self.omitted
+    }
+
     #[must_use]
     pub fn is_full(&self) -> bool {
         self.len == MAX_SUBPARAMS
@@ -66,7 +92,38 @@ impl Index<usize> for Param {
     }
 }
 
-#[derive(Default, Debug)]
+/// Subparameters separated by `:`, with an omitted parameter left empty.
+impl Display for Param {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_omitted() && self.len == 1 {
+            return Ok(());
+        }
+
+        for (index, subparam) in self.as_slice().iter().enumerate() {
+            if index > 0 {
+                f.write_str(":")?;
+            }
+            write!(f, "{subparam}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Debug for Param {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Param")
+            .field(&format_args!("{self}"))
+            .finish()
+    }
+}
+
+/// Parameters of a control sequence.
+///
+/// Parameters are separated by `;`, and every parameter holds one or more subparameters
+/// separated by `:`. `CSI 38:2:255:0:255;1 m` has two parameters, `[38, 2, 255, 0, 255]` and
+/// `[1]`, while `CSI 38;2;255;0;255;1 m` has six with a single subparameter each.
+#[derive(Default)]
 pub struct Params {
     array: [Param; MAX_PARAMS],
     len: usize,
@@ -81,14 +138,22 @@ impl Params {
         self.len = 0
     }
 
+    /// Number of parameters, including omitted ones.
+    #[inline]
+    #[must_use]
     pub fn len(&self) -> usize {
         self.len
     }
 
+    #[inline]
+    #[must_use]
     pub fn as_slice(&self) -> &[Param] {
         &self.array[..self.len]
     }
 
+    /// Whether no parameter was pushed at all.
+    #[inline]
+    #[must_use]
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
@@ -105,9 +170,62 @@ impl Params {
         self.len += 1;
     }
 
+    /// Whether the parameter being collected can't take another subparameter.
+    #[must_use]
+    pub fn is_current_full(&self) -> bool {
+        self.array.get(self.len).is_none_or(Param::is_full)
+    }
+
     pub fn push_subparam(&mut self, subparam: Subparam) {
         self.array[self.len].push(subparam);
     }
+
+    pub fn push_omitted_subparam(&mut self) {
+        self.array[self.len].push_omitted();
+    }
+
+    /// Iterate over the subparameters of every parameter, without allocating.
+    ///
+    /// ```
+    /// use saiga_vte::{param::Params, Executor, Parser};
+    ///
+    /// struct Collect(Vec<Vec<u16>>);
+    ///
+    /// impl Executor for Collect {
+    ///     fn csi_dispatch(&mut self, params: &Params, _: &[u8], _: bool, _: char) {
+    ///         self.0.extend(params.iter().map(<[u16]>::to_vec));
+    ///     }
+    ///     # fn print(&mut self, _: char) {}
+    ///     # fn execute(&mut self, _: u8) {}
+    ///     # fn put(&mut self, _: u8) {}
+    ///     # fn hook(&mut self, _: &Params, _: &[u8], _: bool, _: char) {}
+    ///     # fn unhook(&mut self) {}
+    ///     # fn osc_dispatch(&mut self, _: &[&[u8]], _: bool) {}
+    ///     # fn esc_dispatch(&mut self, _: &[u8], _: bool, _: u8) {}
+    /// }
+    ///
+    /// let mut collect = Collect(Vec::new());
+    /// Parser::new().advance(&mut collect, b"\x1b[38:2:255:0:255;1m");
+    ///
+    /// assert_eq!(collect.0, [vec![38, 2, 255, 0, 255], vec![1]]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> ParamsIter<'_> {
+        ParamsIter {
+            params: self.as_slice().iter(),
+        }
+    }
+
+    /// Iterate over the leading subparameter of every parameter.
+    ///
+    /// Omitted parameters yield `None`, so they can be told apart from an explicit zero and
+    /// replaced by the sequence's default value.
+    pub fn iter_with_defaults(&self) -> impl Iterator<Item = (usize, Option<Subparam>)> + '_ {
+        self.as_slice()
+            .iter()
+            .enumerate()
+            .map(|(index, param)| (index, (!param.is_omitted()).then(|| param[0])))
+    }
 }
 
 impl Index<usize> for Params {
@@ -117,3 +235,156 @@ impl Index<usize> for Params {
         &self.array[index]
     }
 }
+
+/// Parameters separated by `;`, the way they appear in the control sequence.
+impl Display for Params {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, param) in self.as_slice().iter().enumerate() {
+            if index > 0 {
+                f.write_str(";")?;
+            }
+            write!(f, "{param}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Debug for Params {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Params")
+            .field(&format_args!("{self}"))
+            .finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a Params {
+    type Item = &'a [Subparam];
+    type IntoIter = ParamsIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the subparameters of every parameter, see [`Params::iter`].
+#[derive(Clone, Debug)]
+pub struct ParamsIter<'a> {
+    params: slice::Iter<'a, Param>,
+}
+
+impl<'a> Iterator for ParamsIter<'a> {
+    type Item = &'a [Subparam];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.params.next().map(Param::as_slice)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.params.size_hint()
+    }
+}
+
+impl ExactSizeIterator for ParamsIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(subparams: &[Option<Subparam>]) -> Params {
+        let mut params = Params::default();
+
+        for subparam in subparams {
+            match subparam {
+                Some(subparam) => params.push_subparam(*subparam),
+                None => params.push_omitted_subparam(),
+            }
+
+            params.next_param();
+        }
+
+        params
+    }
+
+    #[test]
+    fn empty() {
+        let params = Params::default();
+
+        assert!(params.is_empty());
+        assert_eq!(params.len(), 0);
+        assert!(params.as_slice().is_empty());
+    }
+
+    #[test]
+    fn trailing_semicolon() {
+        // CSI 4 ; m
+        let params = params(&[Some(4), None]);
+
+        assert!(!params.is_empty());
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[1].as_slice(), &[0]);
+    }
+
+    #[test]
+    fn leading_semicolon() {
+        // CSI ; 4 m
+        let params = params(&[None, Some(4)]);
+
+        assert!(!params.is_empty());
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].as_slice(), &[0]);
+    }
+
+    #[test]
+    fn single_omitted_param() {
+        // CSI m
+        let params = params(&[None]);
+
+        assert!(!params.is_empty());
+        assert_eq!(params.len(), 1);
+    }
+
+    #[test]
+    fn iter() {
+        let mut params = Params::default();
+        for subparam in [38, 2, 255, 0, 255] {
+            params.push_subparam(subparam);
+        }
+        params.next_param();
+        params.push_subparam(1);
+        params.next_param();
+
+        let iter = params.iter();
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.collect::<Vec<_>>(), [&[38, 2, 255, 0, 255][..], &[1]]);
+    }
+
+    #[test]
+    fn display() {
+        // CSI ;5H
+        let omitted = params(&[None, Some(5)]);
+        assert_eq!(omitted.to_string(), ";5");
+
+        let mut params = Params::default();
+        for subparam in [38, 2, 255, 0, 255] {
+            params.push_subparam(subparam);
+        }
+        params.next_param();
+        params.push_subparam(1);
+        params.next_param();
+
+        assert_eq!(params.to_string(), "38:2:255:0:255;1");
+        assert_eq!(format!("{params:?}"), "Params(38:2:255:0:255;1)");
+    }
+
+    #[test]
+    fn clear() {
+        let mut params = params(&[Some(1), Some(2)]);
+        params.clear();
+
+        assert!(params.is_empty());
+        assert_eq!(params.len(), 0);
+    }
+}
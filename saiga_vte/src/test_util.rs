@@ -0,0 +1,166 @@
+//! Helpers for asserting parser output in tests.
+//!
+//! ```
+//! use saiga_vte::{
+//!     test_util::{Recorder, Sequence},
+//!     Parser,
+//! };
+//!
+//! let mut recorder = Recorder::default();
+//! Parser::new().advance(&mut recorder, b"a\x1b[1m");
+//!
+//! recorder.assert_sequences(&[
+//!     Sequence::Print('a'),
+//!     Sequence::Csi(vec![vec![1]], vec![], false, 'm'),
+//! ]);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{param::Params, Executor};
+
+/// A single call made by the parser to its [`Executor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sequence {
+    Osc(Vec<Vec<u8>>, bool),
+    OscControl(u8),
+    Csi(Vec<Vec<u16>>, Vec<u8>, bool, char),
+    Esc(Vec<u8>, bool, u8),
+    DcsHook(Vec<Vec<u16>>, Vec<u8>, bool, char),
+    DcsPut(u8),
+    DcsUnhook,
+    Execute(u8),
+    Print(char),
+}
+
+/// First position where the recorded sequences differ from the expected ones.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Mismatch<'a> {
+    pub index: usize,
+    /// `None` when fewer sequences were recorded than expected.
+    pub recorded: Option<&'a Sequence>,
+    /// `None` when more sequences were recorded than expected.
+    pub expected: Option<&'a Sequence>,
+}
+
+/// Executor that records every sequence it receives.
+#[derive(Default, Debug)]
+pub struct Recorder {
+    pub sequences: Vec<Sequence>,
+}
+
+impl Recorder {
+    /// Find the first sequence that differs from `expected`.
+    #[must_use]
+    pub fn diff<'a>(&'a self, expected: &'a [Sequence]) -> Option<Mismatch<'a>> {
+        let len = self.sequences.len().max(expected.len());
+
+        (0..len)
+            .map(|index| Mismatch {
+                index,
+                recorded: self.sequences.get(index),
+                expected: expected.get(index),
+            })
+            .find(|mismatch| mismatch.recorded != mismatch.expected)
+    }
+
+    /// Panic with the first mismatch if the recorded sequences differ from `expected`.
+    #[track_caller]
+    pub fn assert_sequences(&self, expected: &[Sequence]) {
+        if let Some(mismatch) = self.diff(expected) {
+            panic!(
+                "sequence {} differs\n recorded: {:?}\n expected: {:?}",
+                mismatch.index, mismatch.recorded, mismatch.expected
+            );
+        }
+    }
+}
+
+fn params_to_vec(params: &Params) -> Vec<Vec<u16>> {
+    params
+        .as_slice()
+        .iter()
+        .map(|param| param.as_slice().to_vec())
+        .collect()
+}
+
+impl Executor for Recorder {
+    fn print(&mut self, c: char) {
+        self.sequences.push(Sequence::Print(c));
+    }
+
+    fn execute(&mut self, byte: u8) {
+        self.sequences.push(Sequence::Execute(byte))
+    }
+
+    fn put(&mut self, byte: u8) {
+        self.sequences.push(Sequence::DcsPut(byte));
+    }
+
+    fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, c: char) {
+        self.sequences.push(Sequence::DcsHook(
+            params_to_vec(params),
+            intermediates.to_vec(),
+            ignore,
+            c,
+        ));
+    }
+
+    fn unhook(&mut self) {
+        self.sequences.push(Sequence::DcsUnhook);
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        let params = params.iter().map(|p| p.to_vec()).collect();
+
+        self.sequences.push(Sequence::Osc(params, bell_terminated));
+    }
+
+    fn osc_control(&mut self, byte: u8) {
+        self.sequences.push(Sequence::OscControl(byte));
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        self.sequences
+            .push(Sequence::Esc(intermediates.to_vec(), ignore, byte));
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, c: char) {
+        self.sequences.push(Sequence::Csi(
+            params_to_vec(params),
+            intermediates.to_vec(),
+            ignore,
+            c,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+
+    #[test]
+    fn diff() {
+        let mut recorder = Recorder::default();
+        Parser::new().advance(&mut recorder, b"ab");
+
+        recorder.assert_sequences(&[Sequence::Print('a'), Sequence::Print('b')]);
+        assert_eq!(
+            recorder.diff(&[Sequence::Print('a'), Sequence::Print('c')]),
+            Some(Mismatch {
+                index: 1,
+                recorded: Some(&Sequence::Print('b')),
+                expected: Some(&Sequence::Print('c')),
+            })
+        );
+        assert_eq!(
+            recorder.diff(&[Sequence::Print('a')]),
+            Some(Mismatch {
+                index: 1,
+                recorded: Some(&Sequence::Print('b')),
+                expected: None,
+            })
+        );
+    }
+}
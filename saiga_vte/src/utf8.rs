@@ -1,5 +1,4 @@
 use core::str;
-use std::char;
 
 use simdutf8::basic::Utf8Error;
 
@@ -18,6 +17,27 @@ impl UTF8Collector {
         self.len += 1;
     }
 
+    /// Number of bytes collected for a character that isn't complete yet.
+    pub fn pending_count(&self) -> usize {
+        self.len
+    }
+
+    /// Whether `byte` can continue the collected bytes.
+    ///
+    /// Besides being a continuation byte, the byte following the first one must not lead to an
+    /// overlong encoding, a surrogate or a code point past U+10FFFF.
+    pub fn continues_with(&self, byte: u8) -> bool {
+        let range = match (self.len, self.bytes[0]) {
+            (1, 0xE0) => 0xA0..=0xBF,
+            (1, 0xED) => 0x80..=0x9F,
+            (1, 0xF0) => 0x90..=0xBF,
+            (1, 0xF4) => 0x80..=0x8F,
+            _ => 0x80..=0xBF,
+        };
+
+        range.contains(&byte)
+    }
+
     fn as_slice(&self) -> &[u8] {
         &self.bytes[..self.len]
     }
@@ -56,6 +76,14 @@ pub fn from_utf8(utf8: &[u8]) -> Result<&str, Utf8Error> {
     simdutf8::basic::from_utf8(utf8)
 }
 
+/// Longest prefix of `bytes` which is valid UTF-8.
+pub fn valid_prefix(bytes: &[u8]) -> &str {
+    match simdutf8::compat::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(err) => str::from_utf8(&bytes[..err.valid_up_to()]).unwrap_or_default(),
+    }
+}
+
 pub fn into_char(utf8: &[u8]) -> char {
     match from_utf8(utf8) {
         Ok(s) => s.chars().next().expect("No character found"),
@@ -83,6 +111,14 @@ mod tests {
             assert_eq!(case.1, expected_bytes_count(case.0))
         }
     }
+
+    #[test]
+    fn valid_prefix_stops_at_invalid_or_partial() {
+        assert_eq!(valid_prefix("héllo".as_bytes()), "héllo");
+        assert_eq!(valid_prefix(b"ab\xFFcd"), "ab");
+        assert_eq!(valid_prefix(b"ab\xE2\x82"), "ab");
+        assert_eq!(valid_prefix(b"\x80"), "");
+    }
 }
 
 #[cfg(test)]